@@ -0,0 +1,74 @@
+//! Measures how long the interpreter takes to run a few CPU-bound programs, with and without the
+//! decode cache.
+//!
+//! Run with `cargo bench --bench interpreter`.
+
+use criterion::{BatchSize, Criterion, criterion_group, criterion_main};
+use seaside_assembler::{Assembler, parser::Parser};
+use seaside_config::Config;
+use seaside_interpreter::{Interpreter, Startup};
+use std::{collections::VecDeque, hint::black_box, path::Path};
+
+const PROGRAMS: [(&str, &str); 2] = [
+    ("bubble_sort", include_str!("programs/bubble_sort.asm")),
+    ("fibonacci", include_str!("programs/fibonacci.asm")),
+];
+
+fn interpreter(c: &mut Criterion) {
+    let mut config: Config = toml::from_str(include_str!("../res/Seaside.toml")).unwrap();
+    config.features.show_crash_handler = false;
+    config.features.write_core_dump = false;
+    for (name, source) in PROGRAMS {
+        let directory = std::env::temp_dir().join(format!("seaside-bench-{name}"));
+        assemble(&config, name, source, &directory);
+        let mut group = c.benchmark_group(name);
+        for (id, decode_cache) in [("uncached", false), ("cached", true)] {
+            group.bench_function(id, |b| {
+                b.iter_batched(
+                    || load(&config, &directory, decode_cache),
+                    |mut interpreter| {
+                        black_box(interpreter.run()).unwrap();
+                        // Returning the interpreter keeps dropping it out of the measurement.
+                        interpreter
+                    },
+                    BatchSize::LargeInput,
+                )
+            });
+        }
+        group.finish();
+    }
+}
+
+/// Assembles the program `name` from `source` into `directory`.
+fn assemble(config: &Config, name: &str, source: &str, directory: &Path) {
+    let Ok(build) = Parser::new(source)
+        .collect::<Result<VecDeque<_>, _>>()
+        .and_then(|exprs| Assembler::new(config, exprs).build())
+    else {
+        panic!("failed to assemble {name}.asm");
+    };
+    std::fs::create_dir_all(directory).unwrap();
+    build.export(directory).unwrap();
+}
+
+/// Loads the program assembled into `directory`, ready to run.
+fn load(config: &Config, directory: &Path, decode_cache: bool) -> Interpreter {
+    let data = Some(directory.join("data")).filter(|path| path.exists());
+    let mut interpreter = Interpreter::init(
+        config,
+        directory.join("text"),
+        None,
+        data,
+        None,
+        None,
+        Startup::default(),
+    )
+    .unwrap();
+    if !decode_cache {
+        interpreter.decode_cache = None;
+    }
+    interpreter
+}
+
+criterion_group!(benches, interpreter);
+criterion_main!(benches);
@@ -0,0 +1,46 @@
+# Sorts 600 pseudo-random words with bubble sort.
+
+.text
+main:
+    addiu $s1, $0, 600
+    sll $a0, $s1, 2
+    addiu $v0, $0, 9
+    syscall
+    addu $s0, $0, $v0
+
+    # fill the array using a linear congruential generator
+    lui $t2, 0x0019
+    ori $t2, $t2, 0x660d
+    lui $t3, 0x3c6e
+    ori $t3, $t3, 0xf35f
+    addiu $t0, $0, 12345
+    addu $t1, $0, $0
+    fill:
+        mul $t0, $t0, $t2
+        addu $t0, $t0, $t3
+        sll $t4, $t1, 2
+        addu $t4, $s0, $t4
+        sw $t0, 0($t4)
+        addiu $t1, $t1, 1
+        bne $t1, $s1, fill
+
+    addiu $t5, $s1, -1
+    outer:
+        addu $t1, $0, $0
+        addu $t6, $s0, $0
+        inner:
+            lw $t7, 0($t6)
+            lw $t8, 4($t6)
+            slt $t9, $t8, $t7
+            beq $t9, $0, ordered
+            sw $t8, 0($t6)
+            sw $t7, 4($t6)
+        ordered:
+            addiu $t6, $t6, 4
+            addiu $t1, $t1, 1
+            bne $t1, $t5, inner
+        addiu $t5, $t5, -1
+        bgtz $t5, outer
+
+    addiu $v0, $0, 10
+    syscall
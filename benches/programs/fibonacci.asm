@@ -0,0 +1,29 @@
+# Computes the 22nd Fibonacci number recursively.
+
+.text
+main:
+    addiu $a0, $0, 22
+    jal Fib
+    addiu $v0, $0, 10
+    syscall
+
+Fib:
+    slti $t0, $a0, 2
+    beq $t0, $0, Fib_recurse
+    addu $v0, $a0, $0
+    jr $ra
+    Fib_recurse:
+        addiu $sp, $sp, -12
+        sw $ra, 8($sp)
+        sw $a0, 4($sp)
+        addiu $a0, $a0, -1
+        jal Fib
+        sw $v0, 0($sp)
+        lw $a0, 4($sp)
+        addiu $a0, $a0, -2
+        jal Fib
+        lw $t0, 0($sp)
+        addu $v0, $v0, $t0
+        lw $ra, 8($sp)
+        addiu $sp, $sp, 12
+        jr $ra
@@ -38,6 +38,8 @@ pub enum AssembleError {
     InvalidEscapeSequence,
     #[error("invalid UTF-8")]
     InvalidUtf8,
+    #[error("operator not supported by the configured instruction set")]
+    UnsupportedOperator,
 }
 
 impl From<ParseError> for AssembleError {
@@ -69,6 +71,7 @@ impl ToErrorCode for AssembleError {
             UnterminatedStringLiteral => 212,
             InvalidEscapeSequence => 213,
             InvalidUtf8 => 214,
+            UnsupportedOperator => 215,
         }
     }
 }
@@ -1,7 +1,7 @@
 use seaside_constants::{
     ConditionCode, NumberFormat,
     fn_codes::Coprocessor1RegisterImmediateFn,
-    register::{Coprocessor0Register, CpuRegister, FpuRegister},
+    register::{Coprocessor0Register, Coprocessor1ControlRegister, CpuRegister, FpuRegister},
 };
 use seaside_type_aliases::Instruction;
 
@@ -27,6 +27,17 @@ pub const fn r_type(
     insert![{5} rs, {5} rt, {5} rd, {5} shamt, {6} fn_code => &machine_code];
 }
 
+pub const fn special_3(
+    machine_code: &mut Instruction,
+    rs: CpuRegister,
+    rt: CpuRegister,
+    msb: u8,
+    lsb: u8,
+    fn_code: u8,
+) {
+    insert![{5} rs, {5} rt, {5} msb, {5} lsb, {6} fn_code => &machine_code];
+}
+
 pub const fn movc(
     machine_code: &mut Instruction,
     rs: CpuRegister,
@@ -70,6 +81,15 @@ pub const fn coprocessor_1_register_immediate(
     insert![{5} fn_code, {5} rt, {5} fs, {11} 0 => &machine_code];
 }
 
+pub const fn coprocessor_1_control(
+    machine_code: &mut Instruction,
+    fn_code: u8,
+    rt: CpuRegister,
+    fs: Coprocessor1ControlRegister,
+) {
+    insert![{5} fn_code, {5} rt, {5} fs, {11} 0 => &machine_code];
+}
+
 pub const fn bc1c(machine_code: &mut Instruction, cc: ConditionCode, condition: bool, offset: u16) {
     insert![
         {5} Coprocessor1RegisterImmediateFn::BranchCoprocessor1Flag,
@@ -208,3 +208,29 @@ macro_rules! special_2 {
     };
 }
 pub(crate) use special_2;
+
+macro_rules! special_3 {
+    ($variant:ident) => {
+        $crate::instruction::operator::Operator::Special3(
+            seaside_constants::fn_codes::Special3Fn::$variant,
+        )
+    };
+    [$variant:ident, $($variants:ident),+ $(,)?] => {
+        $crate::instruction::macros::special_3!($variant)
+        | $crate::instruction::macros::special_3!($($variants),+)
+    };
+}
+pub(crate) use special_3;
+
+macro_rules! coprocessor_1x {
+    ($variant:ident) => {
+        $crate::instruction::operator::Operator::Coprocessor1X(
+            seaside_constants::fn_codes::Coprocessor1XFn::$variant,
+        )
+    };
+    [$variant:ident, $($variants:ident),+ $(,)?] => {
+        $crate::instruction::macros::coprocessor_1x!($variant)
+        | $crate::instruction::macros::coprocessor_1x!($($variants),+)
+    };
+}
+pub(crate) use coprocessor_1x;
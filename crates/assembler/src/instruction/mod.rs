@@ -10,6 +10,7 @@ use crate::{error::AssembleError, parser::Operand};
 use assemble::insert;
 use macros::*;
 use process::{Destination, maybe, maybe_or};
+use seaside_config::features::IsaOptions;
 use seaside_constants::{
     ConditionCode, Opcode,
     register::{CpuRegister, FpuRegister},
@@ -28,6 +29,7 @@ pub fn process_instruction<'src>(
     operands: Vec<(Operand<'src>, Span)>,
     expr_span: &Span,
     pc: Address,
+    isa: &IsaOptions,
 ) -> RichResult<ProcessedInstruction<'src>> {
     use Operator::*;
 
@@ -37,11 +39,25 @@ pub fn process_instruction<'src>(
     };
 
     // Using `map_err` would cause `expr_span` to get moved out unless we cloned it. :(
+    let mnemonic = operator;
     let operator: Operator = operator.parse().map_err(|_| {
         RichError::new(AssembleError::UnknownOperator, expr_span.clone())
-            .with_narrow_span(operator_span)
+            .with_narrow_span(operator_span.clone())
             .with_help("if you are trying to use a pseudo-operator, those aren't supported yet")
     })?;
+    if !isa.allows(mnemonic) {
+        let note = if isa.deny.iter().any(|denied| denied == mnemonic) {
+            "`features.isa.deny` forbids this operator".to_string()
+        } else {
+            format!("the `{}` preset doesn't include it", isa.preset.name())
+        };
+        return Err(
+            RichError::new(AssembleError::UnsupportedOperator, expr_span.clone())
+                .with_narrow_span(operator_span)
+                .with_note(note)
+                .with_help("change `features.isa` in your config to use it"),
+        );
+    }
     let mut operands_iter = operands.iter();
     let opcode = Opcode::from(operator);
     let fn_code = operator.op_or_fn_code();
@@ -55,6 +71,13 @@ pub fn process_instruction<'src>(
             let shamt = process::shamt(operands_iter.next(), expr_span)?;
             assemble::r_type(&mut machine_code, CpuRegister::Zero, rt, rd, shamt, fn_code);
         }
+        // rotr $rd, $rt, shamt
+        RotateRight => {
+            let rd = process::cpu_register(operands_iter.next(), expr_span)?;
+            let rt = process::cpu_register(operands_iter.next(), expr_span)?;
+            let shamt = process::shamt(operands_iter.next(), expr_span)?;
+            insert!({5} 1, {5} rt, {5} rd, {5} shamt, {6} fn_code => machine_code);
+        }
         // movt $rd, $rs
         // movt $rd, $rs, cc
         special!(MoveConditional, condition: condition) => {
@@ -79,6 +102,13 @@ pub fn process_instruction<'src>(
             let rs = process::cpu_register(operands_iter.next(), expr_span)?;
             assemble::r_type(&mut machine_code, rs, rt, rd, 0, fn_code);
         }
+        // rotrv $rd, $rt, $rs
+        RotateRightVariable => {
+            let rd = process::cpu_register(operands_iter.next(), expr_span)?;
+            let rt = process::cpu_register(operands_iter.next(), expr_span)?;
+            let rs = process::cpu_register(operands_iter.next(), expr_span)?;
+            assemble::r_type(&mut machine_code, rs, rt, rd, 1, fn_code);
+        }
         // jr $rs
         special![JumpRegister, MoveToHigh, MoveToLow] => {
             let rs = process::cpu_register(operands_iter.next(), expr_span)?;
@@ -215,7 +245,7 @@ pub fn process_instruction<'src>(
         }
         // beq $rs, $rt, address
         // beq $rs, $rt, label
-        BranchEqual | BranchNotEqual => {
+        BranchEqual | BranchNotEqual | BranchEqualLikely | BranchNotEqualLikely => {
             let rs = process::cpu_register(operands_iter.next(), expr_span)?;
             let rt = process::cpu_register(operands_iter.next(), expr_span)?;
             let offset = match process::destination(operands_iter.next(), expr_span)? {
@@ -238,7 +268,10 @@ pub fn process_instruction<'src>(
         }
         // blez $rs, address
         // blez $rs, label
-        BranchLessEqualZero | BranchGreaterThanZero => {
+        BranchLessEqualZero
+        | BranchGreaterThanZero
+        | BranchLessEqualZeroLikely
+        | BranchGreaterThanZeroLikely => {
             let rs = process::cpu_register(operands_iter.next(), expr_span)?;
             let offset = match process::destination(operands_iter.next(), expr_span)? {
                 Destination::Address(address, span) => {
@@ -365,6 +398,13 @@ pub fn process_instruction<'src>(
             let fs = process::fpu_register(operands_iter.next(), expr_span)?;
             assemble::coprocessor_1_register_immediate(&mut machine_code, fn_code, rt, fs);
         }
+        // cfc1 $rt, $fs
+        // note: $fs is a floating-point control register, not an fpu register
+        coprocessor_1_register_immediate![ControlFromCoprocessor1, ControlToCoprocessor1] => {
+            let rt = process::cpu_register(operands_iter.next(), expr_span)?;
+            let fs = process::coprocessor_1_control_register(operands_iter.next(), expr_span)?;
+            assemble::coprocessor_1_control(&mut machine_code, fn_code, rt, fs);
+        }
         // bc1t address
         // bc1t label
         // bc1t cc, address
@@ -394,6 +434,66 @@ pub fn process_instruction<'src>(
             let rs = process::cpu_register(operands_iter.next(), expr_span)?;
             assemble::r_type(&mut machine_code, rs, CpuRegister::Zero, rd, 0, fn_code);
         }
+        // ext $rt, $rs, pos, size
+        special_3!(Extract) => {
+            let rt = process::cpu_register(operands_iter.next(), expr_span)?;
+            let rs = process::cpu_register(operands_iter.next(), expr_span)?;
+            let pos = process::shamt(operands_iter.next(), expr_span)?;
+            let size = process::bit_field_size(operands_iter.next(), expr_span, pos)?;
+            assemble::special_3(&mut machine_code, rs, rt, size - 1, pos, fn_code);
+        }
+        // ins $rt, $rs, pos, size
+        special_3!(Insert) => {
+            let rt = process::cpu_register(operands_iter.next(), expr_span)?;
+            let rs = process::cpu_register(operands_iter.next(), expr_span)?;
+            let pos = process::shamt(operands_iter.next(), expr_span)?;
+            let size = process::bit_field_size(operands_iter.next(), expr_span, pos)?;
+            assemble::special_3(&mut machine_code, rs, rt, pos + size - 1, pos, fn_code);
+        }
+        // seb $rd, $rt
+        ByteShuffle(r#fn) => {
+            let rd = process::cpu_register(operands_iter.next(), expr_span)?;
+            let rt = process::cpu_register(operands_iter.next(), expr_span)?;
+            insert!({5} 0, {5} rt, {5} rd, {5} r#fn, {6} fn_code => machine_code);
+        }
+        // lwxc1 $fd, $index($base)
+        coprocessor_1x![LoadWordIndexed, LoadDoubleIndexed] => {
+            let fd = process::fpu_register(operands_iter.next(), expr_span)?;
+            let index = process::cpu_register(operands_iter.next(), expr_span)?;
+            let base = process::wrapped_cpu_register(operands_iter.next(), expr_span)?;
+            insert!({5} base, {5} index, {5} 0, {5} fd, {6} fn_code => machine_code);
+        }
+        // swxc1 $fs, $index($base)
+        coprocessor_1x![StoreWordIndexed, StoreDoubleIndexed] => {
+            let fs = process::fpu_register(operands_iter.next(), expr_span)?;
+            let index = process::cpu_register(operands_iter.next(), expr_span)?;
+            let base = process::wrapped_cpu_register(operands_iter.next(), expr_span)?;
+            insert!({5} base, {5} index, {5} fs, {5} 0, {6} fn_code => machine_code);
+        }
+        // prefx hint, $index($base)
+        coprocessor_1x!(PrefetchIndexed) => {
+            let hint = process::shamt(operands_iter.next(), expr_span)?;
+            let index = process::cpu_register(operands_iter.next(), expr_span)?;
+            let base = process::wrapped_cpu_register(operands_iter.next(), expr_span)?;
+            insert!({5} base, {5} index, {5} 0, {5} hint, {6} fn_code => machine_code);
+        }
+        // madd.s $fd, $fr, $fs, $ft
+        coprocessor_1x![
+            MultiplyAddSingle,
+            MultiplyAddDouble,
+            MultiplySubtractSingle,
+            MultiplySubtractDouble,
+            NegativeMultiplyAddSingle,
+            NegativeMultiplyAddDouble,
+            NegativeMultiplySubtractSingle,
+            NegativeMultiplySubtractDouble,
+        ] => {
+            let fd = process::fpu_register(operands_iter.next(), expr_span)?;
+            let fr = process::fpu_register(operands_iter.next(), expr_span)?;
+            let fs = process::fpu_register(operands_iter.next(), expr_span)?;
+            let ft = process::fpu_register(operands_iter.next(), expr_span)?;
+            insert!({5} fr, {5} ft, {5} fs, {5} fd, {6} fn_code => machine_code);
+        }
         // lb $rt, ($rs)
         // lb $rt, imm_i16($rs)
         LoadByte | LoadHalf | LoadWordLeft | LoadWord | LoadByteUnsigned | LoadHalfUnsigned
@@ -3,8 +3,8 @@ use core::str::FromStr;
 use seaside_constants::{
     NumberFormat, Opcode,
     fn_codes::{
-        Coprocessor0Fn, Coprocessor1Fn, Coprocessor1RegisterImmediateFn, RegisterImmediateFn,
-        Special2Fn, SpecialFn,
+        ByteShuffleFn, Coprocessor0Fn, Coprocessor1Fn, Coprocessor1RegisterImmediateFn,
+        Coprocessor1XFn, RegisterImmediateFn, Special2Fn, Special3Fn, SpecialFn,
     },
 };
 
@@ -14,6 +14,10 @@ pub enum Operator {
         r#fn: SpecialFn,
         condition: bool,
     },
+    /// `rotr`, which is encoded as `srl` with the otherwise unused `$rs` field set to 1.
+    RotateRight,
+    /// `rotrv`, which is encoded as `srlv` with the otherwise unused `shamt` field set to 1.
+    RotateRightVariable,
     RegisterImmediate(RegisterImmediateFn),
     Jump,
     JumpAndLink,
@@ -21,6 +25,10 @@ pub enum Operator {
     BranchNotEqual,
     BranchLessEqualZero,
     BranchGreaterThanZero,
+    BranchEqualLikely,
+    BranchNotEqualLikely,
+    BranchLessEqualZeroLikely,
+    BranchGreaterThanZeroLikely,
     AddImmediate,
     AddImmediateUnsigned,
     SetLessThanImmediate,
@@ -39,7 +47,10 @@ pub enum Operator {
         r#fn: Coprocessor1RegisterImmediateFn,
         condition: bool,
     },
+    Coprocessor1X(Coprocessor1XFn),
     Special2(Special2Fn),
+    Special3(Special3Fn),
+    ByteShuffle(ByteShuffleFn),
     LoadByte,
     LoadHalf,
     LoadWordLeft,
@@ -65,6 +76,8 @@ impl Operator {
         use Operator::*;
         match self {
             Special { r#fn, .. } => *r#fn as u8,
+            RotateRight => SpecialFn::ShiftRightLogical as u8,
+            RotateRightVariable => SpecialFn::ShiftRightLogicalVariable as u8,
             RegisterImmediate(r#fn) => *r#fn as u8,
             Jump => Opcode::Jump as u8,
             JumpAndLink => Opcode::JumpAndLink as u8,
@@ -72,6 +85,10 @@ impl Operator {
             BranchNotEqual => Opcode::BranchNotEqual as u8,
             BranchLessEqualZero => Opcode::BranchLessEqualZero as u8,
             BranchGreaterThanZero => Opcode::BranchGreaterThanZero as u8,
+            BranchEqualLikely => Opcode::BranchEqualLikely as u8,
+            BranchNotEqualLikely => Opcode::BranchNotEqualLikely as u8,
+            BranchLessEqualZeroLikely => Opcode::BranchLessEqualZeroLikely as u8,
+            BranchGreaterThanZeroLikely => Opcode::BranchGreaterThanZeroLikely as u8,
             AddImmediate => Opcode::AddImmediate as u8,
             AddImmediateUnsigned => Opcode::AddImmediateUnsigned as u8,
             SetLessThanImmediate => Opcode::SetLessThanImmediate as u8,
@@ -83,7 +100,10 @@ impl Operator {
             Coprocessor0(r#fn) => *r#fn as u8,
             Coprocessor1 { r#fn, .. } => *r#fn as u8,
             Coprocessor1RegisterImmediate { r#fn, .. } => *r#fn as u8,
+            Coprocessor1X(r#fn) => *r#fn as u8,
             Special2(r#fn) => *r#fn as u8,
+            Special3(r#fn) => *r#fn as u8,
+            ByteShuffle(_) => Special3Fn::ByteShuffle as u8,
             LoadByte => Opcode::LoadByte as u8,
             LoadHalf => Opcode::LoadHalf as u8,
             LoadWordLeft => Opcode::LoadWordLeft as u8,
@@ -131,9 +151,11 @@ impl FromStr for Operator {
             "movt" => special!(MoveConditional, true),
             "movf" => special!(MoveConditional, false),
             "srl" => special!(ShiftRightLogical),
+            "rotr" => Self::RotateRight,
             "sra" => special!(ShiftRightArithmetic),
             "sllv" => special!(ShiftLeftLogicalVariable),
             "srlv" => special!(ShiftRightLogicalVariable),
+            "rotrv" => Self::RotateRightVariable,
             "srav" => special!(ShiftRightArithmeticVariable),
             "jr" => special!(JumpRegister),
             "jalr" => special!(JumpAndLinkRegister),
@@ -181,6 +203,10 @@ impl FromStr for Operator {
             "bne" => Self::BranchNotEqual,
             "blez" => Self::BranchLessEqualZero,
             "bgtz" => Self::BranchGreaterThanZero,
+            "beql" => Self::BranchEqualLikely,
+            "bnel" => Self::BranchNotEqualLikely,
+            "blezl" => Self::BranchLessEqualZeroLikely,
+            "bgtzl" => Self::BranchGreaterThanZeroLikely,
             "addi" => Self::AddImmediate,
             "addiu" => Self::AddImmediateUnsigned,
             "slti" => Self::SetLessThanImmediate,
@@ -219,6 +245,8 @@ impl FromStr for Operator {
             "c.le.s" => coprocessor_1!(<Single> CompareLessEqual),
             "mfc1" => coprocessor_1_register_immediate!(MoveFromCoprocessor1),
             "mtc1" => coprocessor_1_register_immediate!(MoveToCoprocessor1),
+            "cfc1" => coprocessor_1_register_immediate!(ControlFromCoprocessor1),
+            "ctc1" => coprocessor_1_register_immediate!(ControlToCoprocessor1),
             "bc1f" => coprocessor_1_register_immediate!(BranchCoprocessor1Flag, false),
             "bc1t" => coprocessor_1_register_immediate!(BranchCoprocessor1Flag, true),
             "madd" => special_2!(MultiplyAdd),
@@ -228,6 +256,24 @@ impl FromStr for Operator {
             "msubu" => special_2!(MultiplySubtractUnsigned),
             "clz" => special_2!(CountLeadingZeroes),
             "clo" => special_2!(CountLeadingOnes),
+            "lwxc1" => coprocessor_1x!(LoadWordIndexed),
+            "ldxc1" => coprocessor_1x!(LoadDoubleIndexed),
+            "swxc1" => coprocessor_1x!(StoreWordIndexed),
+            "sdxc1" => coprocessor_1x!(StoreDoubleIndexed),
+            "prefx" => coprocessor_1x!(PrefetchIndexed),
+            "madd.s" => coprocessor_1x!(MultiplyAddSingle),
+            "madd.d" => coprocessor_1x!(MultiplyAddDouble),
+            "msub.s" => coprocessor_1x!(MultiplySubtractSingle),
+            "msub.d" => coprocessor_1x!(MultiplySubtractDouble),
+            "nmadd.s" => coprocessor_1x!(NegativeMultiplyAddSingle),
+            "nmadd.d" => coprocessor_1x!(NegativeMultiplyAddDouble),
+            "nmsub.s" => coprocessor_1x!(NegativeMultiplySubtractSingle),
+            "nmsub.d" => coprocessor_1x!(NegativeMultiplySubtractDouble),
+            "ext" => special_3!(Extract),
+            "ins" => special_3!(Insert),
+            "wsbh" => Self::ByteShuffle(ByteShuffleFn::WordSwapBytesHalf),
+            "seb" => Self::ByteShuffle(ByteShuffleFn::SignExtendByte),
+            "seh" => Self::ByteShuffle(ByteShuffleFn::SignExtendHalf),
             "lb" => Self::LoadByte,
             "lh" => Self::LoadHalf,
             "lwl" => Self::LoadWordLeft,
@@ -255,7 +301,7 @@ impl From<Operator> for Opcode {
     fn from(value: Operator) -> Self {
         use Operator::*;
         match value {
-            Special { .. } => Self::Special,
+            Special { .. } | RotateRight | RotateRightVariable => Self::Special,
             RegisterImmediate(_) => Self::RegisterImmediate,
             Jump => Self::Jump,
             JumpAndLink => Self::JumpAndLink,
@@ -263,6 +309,10 @@ impl From<Operator> for Opcode {
             BranchNotEqual => Self::BranchNotEqual,
             BranchLessEqualZero => Self::BranchLessEqualZero,
             BranchGreaterThanZero => Self::BranchGreaterThanZero,
+            BranchEqualLikely => Self::BranchEqualLikely,
+            BranchNotEqualLikely => Self::BranchNotEqualLikely,
+            BranchLessEqualZeroLikely => Self::BranchLessEqualZeroLikely,
+            BranchGreaterThanZeroLikely => Self::BranchGreaterThanZeroLikely,
             AddImmediate => Self::AddImmediate,
             AddImmediateUnsigned => Self::AddImmediateUnsigned,
             SetLessThanImmediate => Self::SetLessThanImmediate,
@@ -273,7 +323,9 @@ impl From<Operator> for Opcode {
             LoadUpperImmediate => Self::LoadUpperImmediate,
             Coprocessor0(_) => Self::Coprocessor0,
             Coprocessor1 { .. } | Coprocessor1RegisterImmediate { .. } => Self::Coprocessor1,
+            Coprocessor1X(_) => Self::Coprocessor1X,
             Special2(_) => Self::Special2,
+            Special3(_) | ByteShuffle(_) => Self::Special3,
             LoadByte => Self::LoadByte,
             LoadHalf => Self::LoadHalf,
             LoadWordLeft => Self::LoadWordLeft,
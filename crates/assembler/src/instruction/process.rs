@@ -1,7 +1,7 @@
 use crate::{error::AssembleError, parser::Operand};
 use seaside_constants::{
     ConditionCode,
-    register::{Coprocessor0Register, CpuRegister, FpuRegister},
+    register::{Coprocessor0Register, Coprocessor1ControlRegister, CpuRegister, FpuRegister},
 };
 use seaside_error::rich::{Label, RichError, RichResult, Span};
 use seaside_type_aliases::Address;
@@ -156,6 +156,27 @@ pub fn coprocessor_0_register(
     }
 }
 
+/// Attempts to process `operand` as a floating-point control register.
+///
+/// As with all register processors, this will work with both named or indexed registers.
+pub fn coprocessor_1_control_register(
+    operand: Option<&(Operand<'_>, Span)>,
+    expr_span: &Span,
+) -> RichResult<Coprocessor1ControlRegister> {
+    const MESSAGE: &str = "expected floating-point control register";
+    let wrong_type_error = |span: &Span| new_error(expr_span.clone(), span.clone(), MESSAGE);
+    match operand {
+        Some((Operand::Register(register), span)) => {
+            register.parse().map_err(|_| wrong_type_error(span))
+        }
+        Some((_, span)) => Err(wrong_type_error(span)),
+        None => Err(RichError::new(
+            AssembleError::NotEnoughOperands,
+            expr_span.clone(),
+        )),
+    }
+}
+
 /// Attempts to process `operand` as a shift amount.
 pub fn shamt(operand: Option<&(Operand<'_>, Span)>, expr_span: &Span) -> RichResult<u8> {
     const MESSAGE: &str = "expected shift amount (0..32)";
@@ -169,6 +190,28 @@ pub fn shamt(operand: Option<&(Operand<'_>, Span)>, expr_span: &Span) -> RichRes
     }
 }
 
+/// Attempts to process `operand` as the size of a bit field starting at bit `pos`.
+///
+/// The bit field must fit within a single word.
+pub fn bit_field_size(
+    operand: Option<&(Operand<'_>, Span)>,
+    expr_span: &Span,
+    pos: u8,
+) -> RichResult<u8> {
+    match operand {
+        Some((Operand::Int(size), _)) if (1..=32 - pos as i64).contains(size) => Ok(*size as u8),
+        Some((_, span)) => Err(new_error(
+            expr_span.clone(),
+            span.clone(),
+            &format!("expected bit field size (1..={})", 32 - pos),
+        )),
+        None => Err(RichError::new(
+            AssembleError::NotEnoughOperands,
+            expr_span.clone(),
+        )),
+    }
+}
+
 /// Attempts to process `operand` as a [destination](Destination).
 pub fn destination<'src>(
     operand: Option<&(Operand<'src>, Span)>,
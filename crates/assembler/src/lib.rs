@@ -15,12 +15,14 @@ use crate::{
     segment::SegmentBuildInfo,
 };
 use core::iter::zip;
-use seaside_config::Config;
+use seaside_config::{Config, features::IsaOptions};
 use seaside_error::rich::{RichError, RichResult, Span};
 use seaside_int_utils::Endian;
 use seaside_type_aliases::Address;
 use std::{
     collections::{HashMap, VecDeque},
+    fs::File,
+    io::Write,
     path::Path,
 };
 
@@ -38,6 +40,8 @@ pub struct Assembler<'src> {
     unresolved: VecDeque<(Address, (UnresolvedInstruction<'src>, Span))>,
     /// The target build endianness.
     endian: Endian,
+    /// The instructions programs may use.
+    isa: IsaOptions,
 }
 
 impl<'src> Assembler<'src> {
@@ -57,13 +61,24 @@ impl<'src> Assembler<'src> {
             unresolved: VecDeque::new(),
             symbol_table: HashMap::new(),
             endian: config.endian,
+            isa: config.features.isa.clone(),
         }
     }
 
     pub fn build(mut self) -> RichResult<Build> {
         while self.build_next()? {}
         self.resolve_all()?;
-        Ok(Build::new(self.segments))
+        let mut symbols: Vec<(String, Address)> = self
+            .symbol_table
+            .into_iter()
+            .map(|(label, address)| (label.to_string(), address))
+            .collect();
+        // Labels sharing an address are ordered by name, so the symbol file is the same on every
+        // build despite the hash map.
+        symbols.sort_by(|(a_label, a_address), (b_label, b_address)| {
+            (a_address, a_label).cmp(&(b_address, b_label))
+        });
+        Ok(Build::new(self.segments, symbols))
     }
 
     fn this_segment(&self) -> &SegmentBuildInfo {
@@ -150,7 +165,8 @@ impl<'src> Assembler<'src> {
                         .with_note(Self::INSTRUCTION_IN_DATA_SEGMENT));
                 }
                 let pc = self.next_address();
-                let mut bytes = match process_instruction(operator, operands, &span, pc)? {
+                let processed = process_instruction(operator, operands, &span, pc, &self.isa)?;
+                let mut bytes = match processed {
                     ProcessedInstruction::MachineCode(machine_code) => match self.endian {
                         Endian::Little => machine_code.to_le_bytes(),
                         Endian::Big => machine_code.to_be_bytes(),
@@ -221,19 +237,32 @@ impl<'src> Assembler<'src> {
 #[derive(Clone, Debug, PartialEq)]
 pub struct Build {
     segments: [SegmentBuildInfo; 5],
+    /// Every label defined in the program, sorted by address.
+    symbols: Vec<(String, Address)>,
 }
 
 impl Build {
-    pub const fn new(segments: [SegmentBuildInfo; 5]) -> Self {
-        Self { segments }
+    pub const fn new(segments: [SegmentBuildInfo; 5], symbols: Vec<(String, Address)>) -> Self {
+        Self { segments, symbols }
     }
 
+    /// Writes each non-empty segment to its own file in `directory`.
+    ///
+    /// If any labels were defined, they're written to a file named 'symbols', one per line. Each
+    /// line contains the address of the label in hexadecimal, followed by a space and the label
+    /// itself.
     pub fn export(self, directory: &Path) -> std::io::Result<()> {
         for (segment, name) in
             zip(self.segments, SegmentDirective::names()).filter(|(segment, _)| !segment.is_empty())
         {
             segment.export(directory.join(name))?;
         }
+        if !self.symbols.is_empty() {
+            let mut file = File::create(directory.join("symbols"))?;
+            for (label, address) in &self.symbols {
+                writeln!(file, "{address:08x} {label}")?;
+            }
+        }
         Ok(())
     }
 }
@@ -526,7 +526,8 @@ impl<'src> Parser<'src> {
                 Some((Token::Register(name), span)) if !comma_status.can_have() => {
                     operands.push((Operand::Register(name), span.clone()));
                     self.consume_span(span);
-                    comma_status = CommaStatus::Need;
+                    // A register can be followed by a wrapped register, as in `$t1($t0)`.
+                    comma_status = CommaStatus::CanHave;
                 }
                 Some((Token::Ctrl('('), span)) => {
                     self.consume_span(span);
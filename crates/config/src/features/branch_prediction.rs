@@ -0,0 +1,97 @@
+use crate::{Validate, primitive_defaults::r#false};
+use anyhow::{Error, Result};
+use seaside_error::EngineError;
+use seaside_int_utils::AllZeroes;
+use serde::{Deserialize, Serialize};
+
+/// The largest number of index bits a predictor table may use.
+const MAX_TABLE_BITS: u32 = 20;
+
+/// Customizes the branch predictors simulated alongside the interpreter.
+///
+/// Every listed predictor sees the same conditional branches, so their accuracy can be compared
+/// directly. Predictors don't change the behavior of a program.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct BranchPredictionOptions {
+    /// Simulate branch predictors alongside the interpreter.
+    #[serde(default = "r#false")]
+    pub enabled: bool,
+    /// The predictors to simulate.
+    #[serde(default = "all_predictors")]
+    pub predictors: Vec<PredictorKind>,
+    /// The number of bits used to index each predictor's table.
+    #[serde(default = "ten")]
+    pub table_bits: u32,
+    /// The number of recent branch outcomes remembered by [`PredictorKind::Gshare`].
+    #[serde(default = "eight")]
+    pub history_bits: u32,
+}
+
+impl Default for BranchPredictionOptions {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            predictors: all_predictors(),
+            table_bits: ten(),
+            history_bits: eight(),
+        }
+    }
+}
+
+impl Validate for BranchPredictionOptions {
+    fn validate(&self) -> Result<()> {
+        let error_msg = if self.table_bits > MAX_TABLE_BITS {
+            Some("branch predictor tables may use at most 20 index bits")
+        } else if self.history_bits > self.table_bits {
+            Some("gshare history must not be longer than the predictor's index")
+        } else {
+            None
+        };
+        match error_msg {
+            Some(msg) => Err(Error::new(EngineError::InvalidConfig).context(msg)),
+            None => Ok(()),
+        }
+    }
+}
+
+impl AllZeroes for BranchPredictionOptions {
+    fn all_zeroes() -> Self {
+        Self {
+            enabled: false,
+            predictors: Vec::new(),
+            table_bits: 0,
+            history_bits: 0,
+        }
+    }
+}
+
+/// A strategy for predicting the outcome of conditional branches.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PredictorKind {
+    /// Always predict that branches are taken.
+    Taken,
+    /// Always predict that branches are not taken.
+    NotTaken,
+    /// Predict that each branch does whatever it did last time.
+    OneBit,
+    /// Track each branch with a two-bit saturating counter.
+    TwoBit,
+    /// Track branches with two-bit counters indexed by the pc XORed with the global history.
+    Gshare,
+    /// Predict that a branch is taken to the remembered target if it was taken last time.
+    Btb,
+}
+
+fn all_predictors() -> Vec<PredictorKind> {
+    use PredictorKind::*;
+    vec![Taken, NotTaken, OneBit, TwoBit, Gshare, Btb]
+}
+
+const fn ten() -> u32 {
+    10
+}
+
+const fn eight() -> u32 {
+    8
+}
@@ -0,0 +1,147 @@
+use crate::{Validate, primitive_defaults::r#false};
+use anyhow::{Error, Result};
+use seaside_error::EngineError;
+use seaside_int_utils::AllZeroes;
+use serde::{Deserialize, Serialize};
+
+/// Customizes the simulated caches sitting between the interpreter and memory.
+///
+/// Caches don't change the behavior of a program; they only track which accesses would hit or
+/// miss on real hardware.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CacheOptions {
+    /// Log every access made to each cache.
+    #[serde(default = "r#false")]
+    pub log_accesses: bool,
+    /// The cache used for instruction fetches. Fetches aren't simulated if this is omitted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instruction: Option<CacheConfig>,
+    /// The cache used for loads and stores. They aren't simulated if this is omitted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<CacheConfig>,
+}
+
+impl Validate for CacheOptions {
+    fn validate(&self) -> Result<()> {
+        if let Some(instruction) = &self.instruction {
+            instruction.validate()?;
+        }
+        if let Some(data) = &self.data {
+            data.validate()?;
+        }
+        Ok(())
+    }
+}
+
+impl AllZeroes for CacheOptions {
+    fn all_zeroes() -> Self {
+        Self {
+            log_accesses: false,
+            instruction: None,
+            data: None,
+        }
+    }
+}
+
+/// The geometry and policies of a single cache.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CacheConfig {
+    /// The total number of bytes the cache can hold.
+    pub size: u32,
+    /// The number of bytes in each block.
+    pub block_size: u32,
+    /// The number of blocks in each set.
+    #[serde(default = "one")]
+    pub associativity: u32,
+    /// Which block to evict when a set is full.
+    #[serde(default)]
+    pub replacement: ReplacementPolicy,
+    /// When stores are propagated to memory.
+    #[serde(default)]
+    pub write_policy: WritePolicy,
+}
+
+impl CacheConfig {
+    /// The number of sets in the cache.
+    pub const fn n_sets(&self) -> u32 {
+        self.size / (self.block_size * self.associativity)
+    }
+}
+
+impl Validate for CacheConfig {
+    fn validate(&self) -> Result<()> {
+        let error_msg = if !self.block_size.is_power_of_two() || self.block_size < 4 {
+            Some("cache block size must be a power of two no smaller than 4")
+        } else if self.associativity == 0 {
+            Some("cache associativity must be at least 1")
+        } else if self.size == 0 || self.size % (self.block_size * self.associativity) != 0 {
+            Some("cache size must be a nonzero multiple of block size times associativity")
+        } else if !self.n_sets().is_power_of_two() {
+            Some("number of sets in a cache must be a power of two")
+        } else {
+            None
+        };
+        match error_msg {
+            Some(msg) => Err(Error::new(EngineError::InvalidConfig).context(msg)),
+            None => Ok(()),
+        }
+    }
+}
+
+impl AllZeroes for CacheConfig {
+    fn all_zeroes() -> Self {
+        Self {
+            size: 0,
+            block_size: 0,
+            associativity: 0,
+            replacement: ReplacementPolicy::all_zeroes(),
+            write_policy: WritePolicy::all_zeroes(),
+        }
+    }
+}
+
+/// Decides which block in a full set gets evicted.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplacementPolicy {
+    /// Evict the least recently used block.
+    #[default]
+    #[serde(alias = "LRU")]
+    Lru,
+    /// Evict the block that has been in the set the longest.
+    #[serde(alias = "FIFO")]
+    Fifo,
+    /// Evict a block at random.
+    Random,
+}
+
+impl AllZeroes for ReplacementPolicy {
+    fn all_zeroes() -> Self {
+        Self::Lru
+    }
+}
+
+/// Decides when stores reach memory.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WritePolicy {
+    /// Stores only update the cache, and dirty blocks are written to memory upon eviction.
+    ///
+    /// Blocks are allocated in the cache when a store misses.
+    #[default]
+    WriteBack,
+    /// Stores update the cache and memory at the same time.
+    ///
+    /// Blocks are not allocated in the cache when a store misses.
+    WriteThrough,
+}
+
+impl AllZeroes for WritePolicy {
+    fn all_zeroes() -> Self {
+        Self::WriteBack
+    }
+}
+
+const fn one() -> u32 {
+    1
+}
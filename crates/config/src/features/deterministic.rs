@@ -0,0 +1,46 @@
+use crate::primitive_defaults::r#false;
+use seaside_int_utils::AllZeroes;
+use serde::{Deserialize, Serialize};
+
+/// Makes runs reproducible by replacing the wall clock and random seeds.
+///
+/// In deterministic mode, time is kept by a virtual clock that advances with each instruction
+/// executed, `sleep` advances it without blocking, and every random number generator is seeded
+/// from a single master seed.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct DeterministicOptions {
+    /// Run the program deterministically.
+    #[serde(default = "r#false")]
+    pub enabled: bool,
+    /// The master seed every random number generator is derived from.
+    #[serde(default)]
+    pub seed: u64,
+    /// How many nanoseconds pass on the virtual clock for each instruction executed. A value of 0
+    /// stops the clock, except when the program sleeps.
+    #[serde(default = "one_thousand")]
+    pub nanos_per_instruction: u32,
+}
+
+impl Default for DeterministicOptions {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            seed: 0,
+            nanos_per_instruction: one_thousand(),
+        }
+    }
+}
+
+impl AllZeroes for DeterministicOptions {
+    fn all_zeroes() -> Self {
+        Self {
+            enabled: false,
+            seed: 0,
+            nanos_per_instruction: 0,
+        }
+    }
+}
+
+const fn one_thousand() -> u32 {
+    1_000
+}
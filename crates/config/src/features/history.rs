@@ -0,0 +1,52 @@
+use crate::{Validate, primitive_defaults::r#false};
+use anyhow::{Error, Result};
+use seaside_error::EngineError;
+use seaside_int_utils::AllZeroes;
+use serde::{Deserialize, Serialize};
+
+/// Customizes the undo history used to step backwards through a program.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct HistoryOptions {
+    /// Record enough about each step to undo it.
+    #[serde(default = "r#false")]
+    pub enabled: bool,
+    /// The number of most recent steps that can be undone.
+    #[serde(default = "ten_thousand")]
+    pub window: u32,
+}
+
+impl Default for HistoryOptions {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            window: ten_thousand(),
+        }
+    }
+}
+
+impl Validate for HistoryOptions {
+    fn validate(&self) -> Result<()> {
+        let error_msg = if self.enabled && self.window == 0 {
+            Some("history window must contain at least one step")
+        } else {
+            None
+        };
+        match error_msg {
+            Some(msg) => Err(Error::new(EngineError::InvalidConfig).context(msg)),
+            None => Ok(()),
+        }
+    }
+}
+
+impl AllZeroes for HistoryOptions {
+    fn all_zeroes() -> Self {
+        Self {
+            enabled: false,
+            window: 0,
+        }
+    }
+}
+
+const fn ten_thousand() -> u32 {
+    10_000
+}
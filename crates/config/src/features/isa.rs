@@ -0,0 +1,141 @@
+use crate::Validate;
+use anyhow::{Error, Result};
+use seaside_error::EngineError;
+use seaside_int_utils::AllZeroes;
+use serde::{Deserialize, Serialize};
+
+/// The integer instructions defined by MIPS I, plus the coprocessor 0 moves.
+const MIPS1_INTEGER: &[&str] = &[
+    "sll", "srl", "sra", "sllv", "srlv", "srav", "jr", "jalr", "syscall", "break", "mfhi", "mthi",
+    "mflo", "mtlo", "mult", "multu", "div", "divu", "add", "addu", "sub", "subu", "and", "or",
+    "xor", "nor", "slt", "sltu", "bltz", "bgez", "bltzal", "bgezal", "j", "jal", "beq", "bne",
+    "blez", "bgtz", "addi", "addiu", "slti", "sltiu", "andi", "ori", "xori", "lui", "mfc0", "mtc0",
+    "lb", "lh", "lwl", "lw", "lbu", "lhu", "lwr", "sb", "sh", "swl", "sw", "swr",
+];
+
+/// The instructions MARS supports on top of [`MIPS1_INTEGER`], other than the floating-point
+/// arithmetic in [`MARS_FPU`].
+const MARS_EXTENSIONS: &[&str] = &[
+    "movt", "movf", "movz", "movn", "tge", "tgeu", "tlt", "tltu", "teq", "tne", "tgei", "tgeiu",
+    "tlti", "tltiu", "teqi", "tnei", "eret", "madd", "maddu", "mul", "msub", "msubu", "clz", "clo",
+    "ll", "sc", "mfc1", "mtc1", "cfc1", "ctc1", "bc1f", "bc1t", "lwc1", "ldc1", "swc1", "sdc1",
+    "cvt.s.d", "cvt.s.w", "cvt.d.s", "cvt.d.w", "cvt.w.s", "cvt.w.d",
+];
+
+/// The floating-point instructions MARS supports in both single and double precision, without
+/// their `.s` or `.d` suffix.
+const MARS_FPU: &[&str] = &[
+    "add", "sub", "mul", "div", "sqrt", "abs", "mov", "neg", "round.w", "trunc.w", "ceil.w",
+    "floor.w", "movt", "movf", "movz", "movn", "c.eq", "c.lt", "c.le",
+];
+
+/// The instructions added in MIPS32 Release 2, along with the branch-likely instructions, none of
+/// which MARS supports.
+const MIPS32R2_EXTENSIONS: &[&str] = &[
+    "rotr", "rotrv", "beql", "bnel", "blezl", "bgtzl", "lwxc1", "ldxc1", "swxc1", "sdxc1", "prefx",
+    "madd.s", "madd.d", "msub.s", "msub.d", "nmadd.s", "nmadd.d", "nmsub.s", "nmsub.d", "ext",
+    "ins", "wsbh", "seb", "seh",
+];
+
+/// Customizes which instructions programs may use.
+///
+/// Instructions outside the instruction set are rejected by the assembler, flagged by the
+/// disassembler and raise a reserved instruction exception at run time.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct IsaOptions {
+    /// The instruction set to start from.
+    #[serde(default)]
+    pub preset: IsaPreset,
+    /// Mnemonics to allow in addition to those in the preset.
+    #[serde(default)]
+    pub allow: Vec<String>,
+    /// Mnemonics to forbid, even if the preset or `allow` includes them.
+    #[serde(default)]
+    pub deny: Vec<String>,
+}
+
+impl IsaOptions {
+    /// Checks whether the instruction with the given `mnemonic` is part of the instruction set.
+    pub fn allows(&self, mnemonic: &str) -> bool {
+        let listed = |list: &[String]| list.iter().any(|allowed| allowed == mnemonic);
+        (self.preset.allows(mnemonic) || listed(&self.allow)) && !listed(&self.deny)
+    }
+
+    /// Checks whether every instruction seaside supports is part of the instruction set.
+    pub fn allows_everything(&self) -> bool {
+        self.preset == IsaPreset::Mips32r2 && self.deny.is_empty()
+    }
+}
+
+impl Validate for IsaOptions {
+    fn validate(&self) -> Result<()> {
+        match self
+            .allow
+            .iter()
+            .chain(&self.deny)
+            .find(|mnemonic| !IsaPreset::Mips32r2.allows(mnemonic))
+        {
+            Some(mnemonic) => Err(Error::new(EngineError::InvalidConfig)
+                .context(format!("unknown instruction `{mnemonic}` in ISA profile"))),
+            None => Ok(()),
+        }
+    }
+}
+
+impl AllZeroes for IsaOptions {
+    fn all_zeroes() -> Self {
+        Self {
+            preset: IsaPreset::all_zeroes(),
+            allow: Vec::new(),
+            deny: Vec::new(),
+        }
+    }
+}
+
+/// A built-in instruction set.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IsaPreset {
+    /// The integer instructions from MIPS I. Excludes the FPU, traps, conditional moves and
+    /// everything else added later.
+    Mips1Integer,
+    /// The basic instructions supported by MARS.
+    #[default]
+    #[serde(alias = "mars")]
+    MarsBasic,
+    /// Every instruction seaside supports, including the MIPS32 Release 2 extensions.
+    Mips32r2,
+}
+
+impl IsaPreset {
+    /// The name of this preset as written in config files.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Mips1Integer => "mips1_integer",
+            Self::MarsBasic => "mars_basic",
+            Self::Mips32r2 => "mips32r2",
+        }
+    }
+
+    /// Checks whether the instruction with the given `mnemonic` is part of this preset.
+    pub fn allows(self, mnemonic: &str) -> bool {
+        let lists: &[&[&str]] = match self {
+            Self::Mips1Integer => &[MIPS1_INTEGER],
+            Self::MarsBasic => &[MIPS1_INTEGER, MARS_EXTENSIONS],
+            Self::Mips32r2 => &[MIPS1_INTEGER, MARS_EXTENSIONS, MIPS32R2_EXTENSIONS],
+        };
+        let is_fpu = || {
+            let base = mnemonic
+                .strip_suffix(".s")
+                .or_else(|| mnemonic.strip_suffix(".d"));
+            self != Self::Mips1Integer && base.is_some_and(|base| MARS_FPU.contains(&base))
+        };
+        lists.iter().any(|list| list.contains(&mnemonic)) || is_fpu()
+    }
+}
+
+impl AllZeroes for IsaPreset {
+    fn all_zeroes() -> Self {
+        Self::Mips1Integer
+    }
+}
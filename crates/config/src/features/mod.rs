@@ -1,11 +1,32 @@
 pub mod assembler;
+pub mod branch_prediction;
+pub mod cache;
+pub mod deterministic;
+pub mod history;
+pub mod isa;
+pub mod pipeline;
+pub mod sandbox;
+pub mod scripts;
 pub mod services;
+pub mod spim;
+pub mod stack_checks;
 
 pub use assembler::AssemblerOptions;
+pub use branch_prediction::BranchPredictionOptions;
+pub use cache::CacheOptions;
+pub use deterministic::DeterministicOptions;
+pub use history::HistoryOptions;
+pub use isa::{IsaOptions, IsaPreset};
+pub use pipeline::PipelineOptions;
+pub use sandbox::{SandboxMode, SandboxOptions};
+pub use scripts::Scripts;
 pub use services::{Service, Services};
+pub use spim::SpimOptions;
+pub use stack_checks::StackCheckOptions;
 
 use crate::{Validate, primitive_defaults};
-use anyhow::Result;
+use anyhow::{Error, Result};
+use seaside_error::EngineError;
 use seaside_int_utils::AllZeroes;
 use serde::{Deserialize, Serialize};
 
@@ -27,8 +48,44 @@ pub struct Features {
     /// Enables displaying a crash handler when an unhandled exception is thrown.
     #[serde(default = "primitive_defaults::r#true")]
     pub show_crash_handler: bool,
+    /// Write a core file when an unhandled exception is thrown.
+    #[serde(default = "primitive_defaults::r#false")]
+    pub write_core_dump: bool,
+    /// Restrict which instructions programs may use.
+    #[serde(default)]
+    pub isa: IsaOptions,
     /// Set features available to assembler.
     pub assembler: AssemblerOptions,
+    /// Simulate instruction and/or data caches.
+    #[serde(default)]
+    pub cache: CacheOptions,
+    /// Estimate cycle counts with a five-stage pipeline model.
+    #[serde(default)]
+    pub pipeline: PipelineOptions,
+    /// Simulate branch predictors and compare their accuracy.
+    #[serde(default)]
+    pub branch_prediction: BranchPredictionOptions,
+    /// Keep an undo history so programs can be stepped backwards.
+    #[serde(default)]
+    pub history: HistoryOptions,
+    /// Check how programs use their stack.
+    #[serde(default)]
+    pub stack_checks: StackCheckOptions,
+    /// Check that procedure calls follow the O32 calling convention.
+    #[serde(default = "primitive_defaults::r#false")]
+    pub check_calling_convention: bool,
+    /// Restrict which files programs can reach.
+    #[serde(default)]
+    pub sandbox: SandboxOptions,
+    /// Make runs reproducible with a virtual clock and seeded randomness.
+    #[serde(default)]
+    pub deterministic: DeterministicOptions,
+    /// Bind service codes to Lua scripts.
+    #[serde(default)]
+    pub scripts: Scripts,
+    /// Run programs written for SPIM the way SPIM would.
+    #[serde(default)]
+    pub spim: SpimOptions,
     /// Set system services available to interpreter.
     #[serde(alias = "syscalls")]
     pub services: Services,
@@ -36,7 +93,57 @@ pub struct Features {
 
 impl Validate for Features {
     fn validate(&self) -> Result<()> {
-        self.services.validate()
+        self.services.validate()?;
+        self.isa.validate()?;
+        self.cache.validate()?;
+        self.branch_prediction.validate()?;
+        self.history.validate()?;
+        self.stack_checks.validate()?;
+        self.sandbox.validate()?;
+        if self.spim.enabled {
+            self.validate_spim_services()?;
+        }
+        // Undoing writes to virtual files would mean copying the whole virtual file system every
+        // time a service is called.
+        if self.history.enabled && self.sandbox.mode == SandboxMode::Memory {
+            return Err(Error::new(EngineError::InvalidConfig).context(
+                "the undo history can't be used while the sandbox keeps files in memory",
+            ));
+        }
+        match self
+            .scripts
+            .iter()
+            .find(|&(&code, _)| self.services.contains(code))
+        {
+            Some((code, _)) => Err(Error::new(EngineError::InvalidConfig).context(format!(
+                "service {code} is bound to both a built-in service and a script"
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Features {
+    /// Binds SPIM's service codes to SPIM's services, replacing whatever was bound to them.
+    pub fn bind_spim_services(&mut self) {
+        for (code, service) in spim::SPIM_SERVICES {
+            self.services.remove(code);
+            self.services.insert(code, service);
+        }
+    }
+
+    /// Checks that the services the default SPIM kernel calls are bound to SPIM's service codes.
+    fn validate_spim_services(&self) -> Result<()> {
+        match spim::SPIM_SERVICES
+            .iter()
+            .filter(|(code, _)| spim::SPIM_KERNEL_SERVICES.contains(code))
+            .find(|&(code, service)| self.services.get(*code) != Some(service))
+        {
+            Some((code, service)) => Err(Error::new(EngineError::InvalidConfig).context(format!(
+                "SPIM compatibility requires service {code} to be bound to '{service}'"
+            ))),
+            None => Ok(()),
+        }
     }
 }
 
@@ -48,7 +155,19 @@ impl AllZeroes for Features {
             delay_slot: false,
             freeable_heap_allocations: false,
             show_crash_handler: false,
+            write_core_dump: false,
+            isa: IsaOptions::all_zeroes(),
             assembler: AssemblerOptions::all_zeroes(),
+            cache: CacheOptions::all_zeroes(),
+            pipeline: PipelineOptions::all_zeroes(),
+            branch_prediction: BranchPredictionOptions::all_zeroes(),
+            history: HistoryOptions::all_zeroes(),
+            stack_checks: StackCheckOptions::all_zeroes(),
+            check_calling_convention: false,
+            sandbox: SandboxOptions::all_zeroes(),
+            deterministic: DeterministicOptions::all_zeroes(),
+            scripts: Scripts::all_zeroes(),
+            spim: SpimOptions::all_zeroes(),
             services: Services::all_zeroes(),
         }
     }
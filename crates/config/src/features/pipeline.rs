@@ -0,0 +1,64 @@
+use crate::primitive_defaults::{r#false, r#true};
+use seaside_int_utils::AllZeroes;
+use serde::{Deserialize, Serialize};
+
+/// Customizes the five-stage pipeline timing model.
+///
+/// The model doesn't change the behavior of a program; it only estimates how many cycles the
+/// program would take on a classic IF/ID/EX/MEM/WB pipeline.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PipelineOptions {
+    /// Run the pipeline model alongside the interpreter.
+    #[serde(default = "r#false")]
+    pub enabled: bool,
+    /// Forward results from the EX and MEM stages to instructions that need them.
+    #[serde(default = "r#true")]
+    pub forwarding: bool,
+    /// The stage in which conditional branches and register jumps are resolved.
+    #[serde(default)]
+    pub branch_resolution: BranchResolution,
+    /// Print a per-cycle diagram of the first few instructions executed.
+    #[serde(default = "r#false")]
+    pub diagram: bool,
+}
+
+impl Default for PipelineOptions {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            forwarding: true,
+            branch_resolution: BranchResolution::default(),
+            diagram: false,
+        }
+    }
+}
+
+impl AllZeroes for PipelineOptions {
+    fn all_zeroes() -> Self {
+        Self {
+            enabled: false,
+            forwarding: false,
+            branch_resolution: BranchResolution::all_zeroes(),
+            diagram: false,
+        }
+    }
+}
+
+/// The pipeline stage in which branches are resolved.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BranchResolution {
+    /// Branches are resolved in ID, costing one cycle when taken.
+    #[serde(alias = "id")]
+    Decode,
+    /// Branches are resolved in EX, costing two cycles when taken.
+    #[default]
+    #[serde(alias = "ex")]
+    Execute,
+}
+
+impl AllZeroes for BranchResolution {
+    fn all_zeroes() -> Self {
+        Self::Decode
+    }
+}
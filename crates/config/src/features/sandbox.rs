@@ -0,0 +1,56 @@
+use crate::Validate;
+use anyhow::{Error, Result};
+use seaside_error::EngineError;
+use seaside_int_utils::AllZeroes;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Restricts which files programs can reach through the file services.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SandboxOptions {
+    /// How the paths given to file services are resolved.
+    #[serde(default)]
+    pub mode: SandboxMode,
+    /// The directory paths are confined to in `jail` mode, or the directory or tar archive the
+    /// virtual file system starts out with in `memory` mode.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root: Option<PathBuf>,
+}
+
+impl Validate for SandboxOptions {
+    fn validate(&self) -> Result<()> {
+        if self.mode == SandboxMode::Jail && self.root.is_none() {
+            Err(Error::new(EngineError::InvalidConfig).context("jail sandbox is missing a root"))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl AllZeroes for SandboxOptions {
+    fn all_zeroes() -> Self {
+        Self {
+            mode: SandboxMode::all_zeroes(),
+            root: None,
+        }
+    }
+}
+
+/// How the paths given to file services are resolved.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SandboxMode {
+    /// Paths refer to the host file system as is.
+    #[default]
+    Off,
+    /// Paths must be relative and may not contain `..`. They're resolved against the sandbox root.
+    Jail,
+    /// Paths refer to a file system that only exists in memory, so the host is never touched.
+    Memory,
+}
+
+impl AllZeroes for SandboxMode {
+    fn all_zeroes() -> Self {
+        Self::Off
+    }
+}
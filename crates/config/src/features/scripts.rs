@@ -0,0 +1,74 @@
+use core::fmt::{Formatter, Result as FmtResult};
+use seaside_int_utils::AllZeroes;
+use serde::{
+    Deserialize, Serialize,
+    de::{MapAccess, Visitor},
+};
+use std::{
+    collections::{BTreeMap, btree_map::Iter as BTreeMapIter},
+    path::PathBuf,
+};
+
+/// Binds service codes to Lua scripts, so new services can be added without changing seaside.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct Scripts {
+    #[serde(flatten)]
+    data: BTreeMap<u32, PathBuf>,
+}
+
+impl<'de> Deserialize<'de> for Scripts {
+    fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_map(ScriptsVisitor)
+    }
+}
+
+impl AllZeroes for Scripts {
+    fn all_zeroes() -> Self {
+        Self::default()
+    }
+}
+
+impl Scripts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, code: u32, path: PathBuf) -> Option<PathBuf> {
+        self.data.insert(code, path)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Iterates over the service codes and the paths of the scripts bound to them, in order of
+    /// code.
+    pub fn iter(&self) -> BTreeMapIter<'_, u32, PathBuf> {
+        self.data.iter()
+    }
+}
+
+struct ScriptsVisitor;
+
+impl<'de> Visitor<'de> for ScriptsVisitor {
+    type Value = Scripts;
+
+    fn expecting(&self, formatter: &mut Formatter) -> FmtResult {
+        formatter.write_str("a mapping from service codes to script paths")
+    }
+
+    fn visit_map<A>(self, mut access: A) -> core::result::Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut scripts = Scripts::new();
+        while let Some((code, path)) = access.next_entry::<String, PathBuf>()? {
+            let code = code.parse::<u32>().map_err(serde::de::Error::custom)?;
+            scripts.insert(code, path);
+        }
+        Ok(scripts)
+    }
+}
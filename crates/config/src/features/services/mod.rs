@@ -1,4 +1,6 @@
 pub mod mars;
+pub mod posix;
+pub mod seaside;
 pub mod service;
 pub mod spim;
 
@@ -80,6 +82,14 @@ impl Services {
         })
     }
 
+    pub fn contains(&self, code: u32) -> bool {
+        self.data.contains_key(&code)
+    }
+
+    pub fn get(&self, code: u32) -> Option<&Service> {
+        self.data.get(&code)
+    }
+
     pub fn iter(&self) -> HashMapIter<'_, u32, Service> {
         self.data.iter()
     }
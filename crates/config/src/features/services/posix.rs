@@ -0,0 +1,28 @@
+use seaside_service_group::{NestedServiceGroup, ServiceGroup};
+use strum::{EnumString, IntoStaticStr};
+
+#[derive(Clone, Copy, Debug, Eq, NestedServiceGroup, PartialEq)]
+pub enum Posix {
+    File(File),
+    Directory(Directory),
+}
+
+#[derive(Clone, Copy, Debug, EnumString, Eq, IntoStaticStr, PartialEq, ServiceGroup)]
+#[strum(serialize_all = "snake_case")]
+pub enum File {
+    Open,
+    Lseek,
+    Fstat,
+    Stat,
+    Unlink,
+    Rename,
+}
+
+#[derive(Clone, Copy, Debug, EnumString, Eq, IntoStaticStr, PartialEq, ServiceGroup)]
+#[strum(serialize_all = "snake_case")]
+pub enum Directory {
+    Make,
+    Remove,
+    Open,
+    Read,
+}
@@ -0,0 +1,23 @@
+use seaside_service_group::{NestedServiceGroup, ServiceGroup};
+use strum::{EnumString, IntoStaticStr};
+
+#[derive(Clone, Copy, Debug, Eq, NestedServiceGroup, PartialEq)]
+pub enum Seaside {
+    Heap(Heap),
+    Process(Process),
+}
+
+#[derive(Clone, Copy, Debug, EnumString, Eq, IntoStaticStr, PartialEq, ServiceGroup)]
+#[strum(serialize_all = "snake_case")]
+pub enum Heap {
+    Malloc,
+    Calloc,
+    Realloc,
+    Free,
+}
+
+#[derive(Clone, Copy, Debug, EnumString, Eq, IntoStaticStr, PartialEq, ServiceGroup)]
+#[strum(serialize_all = "snake_case")]
+pub enum Process {
+    Getenv,
+}
@@ -1,5 +1,7 @@
 use super::{
     mars::Mars,
+    posix::Posix,
+    seaside::Seaside,
     spim::{self, Spim},
 };
 use seaside_service_group::NestedServiceGroup;
@@ -10,6 +12,8 @@ use serde::{Deserialize, Serialize};
 pub enum Service {
     Spim(Spim),
     Mars(Mars),
+    Posix(Posix),
+    Seaside(Seaside),
 }
 
 // This is needed to derive the `Serialize` trait for some reason.
@@ -0,0 +1,50 @@
+use super::{
+    Service,
+    services::spim::{self, Spim},
+};
+use crate::primitive_defaults::r#false;
+use seaside_int_utils::AllZeroes;
+use serde::{Deserialize, Serialize};
+
+/// The service codes SPIM assigns to its system services.
+pub const SPIM_SERVICES: [(u32, Service); 17] = [
+    (1, Service::Spim(Spim::Print(spim::Print::Int))),
+    (2, Service::Spim(Spim::Print(spim::Print::Float))),
+    (3, Service::Spim(Spim::Print(spim::Print::Double))),
+    (4, Service::Spim(Spim::Print(spim::Print::String))),
+    (5, Service::Spim(Spim::Read(spim::Read::Int))),
+    (6, Service::Spim(Spim::Read(spim::Read::Float))),
+    (7, Service::Spim(Spim::Read(spim::Read::Double))),
+    (8, Service::Spim(Spim::Read(spim::Read::String))),
+    (9, Service::Spim(Spim::System(spim::System::Sbrk))),
+    (10, Service::Spim(Spim::System(spim::System::Exit))),
+    (11, Service::Spim(Spim::Print(spim::Print::Char))),
+    (12, Service::Spim(Spim::Read(spim::Read::Char))),
+    (13, Service::Spim(Spim::File(spim::File::Open))),
+    (14, Service::Spim(Spim::File(spim::File::Read))),
+    (15, Service::Spim(Spim::File(spim::File::Write))),
+    (16, Service::Spim(Spim::File(spim::File::Close))),
+    (17, Service::Spim(Spim::System(spim::System::Exit2))),
+];
+
+/// The services the default SPIM kernel calls, which must keep their SPIM service codes.
+pub const SPIM_KERNEL_SERVICES: [u32; 3] = [1, 4, 10];
+
+/// Runs programs written for SPIM the way SPIM would.
+///
+/// Unless the project provides its own kernel, a default one is loaded into kernel space. Its
+/// exception handler reports and skips faulting instructions like SPIM's `exceptions.s`, and its
+/// `__start` routine calls `main`, then exits once it returns. Console services also follow SPIM's
+/// rules for reading and printing.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SpimOptions {
+    /// Run programs in SPIM compatibility mode.
+    #[serde(default = "r#false")]
+    pub enabled: bool,
+}
+
+impl AllZeroes for SpimOptions {
+    fn all_zeroes() -> Self {
+        Self { enabled: false }
+    }
+}
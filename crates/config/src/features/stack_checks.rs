@@ -0,0 +1,42 @@
+use crate::{Validate, primitive_defaults::r#false};
+use anyhow::{Error, Result};
+use seaside_error::EngineError;
+use seaside_int_utils::AllZeroes;
+use serde::{Deserialize, Serialize};
+
+/// Customizes the checks made on how a program uses its stack.
+///
+/// Stack overflows are always reported, regardless of these options.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct StackCheckOptions {
+    /// Report procedures that return with a different `$sp` than they were called with.
+    #[serde(default = "r#false")]
+    pub enabled: bool,
+    /// Report `$sp` whenever it isn't a multiple of this many bytes. A value of 0 disables the
+    /// check.
+    #[serde(default)]
+    pub alignment: u32,
+}
+
+impl Validate for StackCheckOptions {
+    fn validate(&self) -> Result<()> {
+        let error_msg = if self.alignment != 0 && !self.alignment.is_power_of_two() {
+            Some("stack alignment must be a power of two")
+        } else {
+            None
+        };
+        match error_msg {
+            Some(msg) => Err(Error::new(EngineError::InvalidConfig).context(msg)),
+            None => Ok(()),
+        }
+    }
+}
+
+impl AllZeroes for StackCheckOptions {
+    fn all_zeroes() -> Self {
+        Self {
+            enabled: false,
+            alignment: 0,
+        }
+    }
+}
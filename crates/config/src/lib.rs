@@ -51,11 +51,28 @@ impl Validate for Config {
                 )
             }),
         }?;
-        self.features.services.validate()?;
+        self.features.validate()?;
+        if self.features.spim.enabled && self.memory_map.exception_handler.is_none() {
+            return Err(Error::new(EngineError::InvalidConfig)
+                .context("SPIM compatibility requires an exception handler"));
+        }
         self.memory_map.validate()
     }
 }
 
+impl Config {
+    /// Sets up the config to run programs written for SPIM.
+    ///
+    /// This enables [SPIM compatibility](features::SpimOptions), binds SPIM's service codes and
+    /// restores the memory map and register defaults, which match SPIM's.
+    pub fn apply_spim_preset(&mut self) {
+        self.features.spim.enabled = true;
+        self.features.bind_spim_services();
+        self.memory_map = MemoryMap::default();
+        self.register_defaults = RegisterDefaults::default();
+    }
+}
+
 impl AllZeroes for Config {
     fn all_zeroes() -> Self {
         Self {
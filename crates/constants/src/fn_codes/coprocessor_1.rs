@@ -93,14 +93,14 @@ pub enum Coprocessor1RegisterImmediateFn {
     MoveFromCoprocessor1 = 0x00,
     // #[error("dmfc1")]
     // DoubleMoveFromCoprocessor1 = 0x01,
-    // #[error("cfc1")]
-    // ControlFromCoprocessor1 = 0x02,
+    #[error("cfc1")]
+    ControlFromCoprocessor1 = 0x02,
     #[error("mtc1")]
     MoveToCoprocessor1 = 0x04,
     // #[error("dmtc1")]
     // DoubleMoveToCoprocessor1 = 0x05,
-    // #[error("ctc1")]
-    // ControlToCoprocessor1 = 0x06,
+    #[error("ctc1")]
+    ControlToCoprocessor1 = 0x06,
     #[error("bc1")] // will be properly suffixed later
     BranchCoprocessor1Flag = 0x08,
 }
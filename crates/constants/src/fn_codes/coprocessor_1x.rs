@@ -1,5 +1,6 @@
 //! Coprocessor 1X is an interesting class of instructions that was added in MIPS IV. It's fairly
-//! small, and since MARS doesn't support any of these, I probably won't bother either.
+//! small, and MARS doesn't support any of these, so seaside only accepts them when the MIPS32
+//! Release 2 extensions are enabled.
 
 use num_derive::FromPrimitive;
 use thiserror::Error; // these aren't errors, but i want to convert them to strings, soooo
@@ -16,8 +17,8 @@ pub enum Coprocessor1XFn {
     StoreDoubleIndexed = 0x09,
     #[error("prefx")]
     PrefetchIndexed = 0x0f,
-    // If I were actually going to implement these into seaside, I would probably take the time to
-    // factor out the `fmt` field.
+    // The low 3 bits of these are really a `fmt` field, but only single and double precision are
+    // allowed, so it's simpler to list them out.
     #[error("madd.s")]
     MultiplyAddSingle = 0x20,
     #[error("madd.d")]
@@ -1,12 +1,15 @@
 pub mod coprocessor_0;
 pub mod coprocessor_1;
-// pub mod coprocessor_1x;
+pub mod coprocessor_1x;
 pub mod register_immediate;
 pub mod special;
 pub mod special_2;
+pub mod special_3;
 
 pub use coprocessor_0::Coprocessor0Fn;
 pub use coprocessor_1::{Coprocessor1Fn, Coprocessor1RegisterImmediateFn};
+pub use coprocessor_1x::Coprocessor1XFn;
 pub use register_immediate::RegisterImmediateFn;
 pub use special::SpecialFn;
 pub use special_2::Special2Fn;
+pub use special_3::{ByteShuffleFn, Special3Fn};
@@ -0,0 +1,24 @@
+use num_derive::FromPrimitive;
+use thiserror::Error; // these aren't errors, but i want to convert them to strings, soooo
+
+#[derive(Clone, Copy, Debug, Eq, Error, FromPrimitive, PartialEq)]
+pub enum Special3Fn {
+    #[error("ext")]
+    Extract = 0x00,
+    #[error("ins")]
+    Insert = 0x04,
+    #[error("<byte shuffle fn>")]
+    ByteShuffle = 0x20,
+}
+
+/// The instructions sharing the [`Special3Fn::ByteShuffle`] function code, told apart by their
+/// `shamt` field.
+#[derive(Clone, Copy, Debug, Eq, Error, FromPrimitive, PartialEq)]
+pub enum ByteShuffleFn {
+    #[error("wsbh")]
+    WordSwapBytesHalf = 0x02,
+    #[error("seb")]
+    SignExtendByte = 0x10,
+    #[error("seh")]
+    SignExtendHalf = 0x18,
+}
@@ -1,12 +1,15 @@
 use crate::Opcode;
 
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub enum InstructionFormat {
     Special,
     Immediate,
     Jump,
     Coprocessor0,
     Coprocessor1,
+    Coprocessor1X,
     Special2,
+    Special3,
 }
 
 impl From<Opcode> for InstructionFormat {
@@ -17,7 +20,9 @@ impl From<Opcode> for InstructionFormat {
             Jump | JumpAndLink => Self::Jump,
             Coprocessor0 => Self::Coprocessor0,
             Coprocessor1 => Self::Coprocessor1,
+            Coprocessor1X => Self::Coprocessor1X,
             Special2 => Self::Special2,
+            Special3 => Self::Special3,
             _ => Self::Immediate,
         }
     }
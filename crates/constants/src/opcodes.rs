@@ -41,16 +41,16 @@ pub enum Opcode {
     Coprocessor1 = 0x11,
     // #[error("<coprocessor 2 fn>")]
     // Coprocessor2 = 0x12,
-    // #[error("<coprocessor 1x fn>")]
-    // Coprocessor1X = 0x13,
-    // #[error("beql")]
-    // BranchEqualLikely = 0x14,
-    // #[error("bnel")]
-    // BranchNotEqualLikely = 0x15,
-    // #[error("blezl")]
-    // BranchLessEqualZeroLikely = 0x16,
-    // #[error("bgtzl")]
-    // BranchGreaterThanZeroLikely = 0x17,
+    #[error("<coprocessor 1x fn>")]
+    Coprocessor1X = 0x13,
+    #[error("beql")]
+    BranchEqualLikely = 0x14,
+    #[error("bnel")]
+    BranchNotEqualLikely = 0x15,
+    #[error("blezl")]
+    BranchLessEqualZeroLikely = 0x16,
+    #[error("bgtzl")]
+    BranchGreaterThanZeroLikely = 0x17,
     // #[error("daddi")]
     // DoubleAddImmediate = 0x18,
     // #[error("daddiu")]
@@ -61,6 +61,8 @@ pub enum Opcode {
     // LoadDoubleRight = 0x1b,
     #[error("<special 2 fn>")]
     Special2 = 0x1c,
+    #[error("<special 3 fn>")]
+    Special3 = 0x1f,
     #[error("lb")]
     LoadByte = 0x20,
     #[error("lh")]
@@ -0,0 +1,67 @@
+use super::ParseError;
+use core::{
+    fmt::{Display, Formatter, Result as FmtResult, Write},
+    str::FromStr,
+};
+use num_derive::FromPrimitive;
+use strum_macros::EnumIter;
+
+/// The floating-point control registers accessed by `cfc1` and `ctc1`.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, EnumIter, Eq, FromPrimitive, Ord, PartialEq, PartialOrd)]
+pub enum Coprocessor1ControlRegister {
+    /// The floating-point implementation register (FIR), which is read-only.
+    Implementation = 0,
+    /// The floating-point condition codes register (FCCR).
+    ConditionCodes = 25,
+    /// The floating-point exceptions register (FEXR).
+    Exceptions = 26,
+    /// The floating-point enables register (FENR).
+    Enables = 28,
+    /// The floating-point control and status register (FCSR).
+    ControlStatus = 31,
+}
+
+impl Display for Coprocessor1ControlRegister {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        if f.alternate() {
+            f.write_char('$')?;
+        }
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Coprocessor1ControlRegister {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.strip_prefix('$').unwrap_or(s) {
+            "fir" | "0" => Ok(Self::Implementation),
+            "fccr" | "25" => Ok(Self::ConditionCodes),
+            "fexr" | "26" => Ok(Self::Exceptions),
+            "fenr" | "28" => Ok(Self::Enables),
+            "fcsr" | "31" => Ok(Self::ControlStatus),
+            "" => Err(ParseError::Empty),
+            _ => Err(ParseError::BadValue),
+        }
+    }
+}
+
+impl Coprocessor1ControlRegister {
+    pub const N_REGISTERS: usize = 5;
+    pub const NAMES: [&str; Self::N_REGISTERS] = ["fir", "fccr", "fexr", "fenr", "fcsr"];
+
+    pub const fn name(&self) -> &'static str {
+        Self::NAMES[self.into_index()]
+    }
+
+    pub const fn into_index(self) -> usize {
+        match self {
+            Self::Implementation => 0,
+            Self::ConditionCodes => 1,
+            Self::Exceptions => 2,
+            Self::Enables => 3,
+            Self::ControlStatus => 4,
+        }
+    }
+}
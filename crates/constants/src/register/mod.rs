@@ -1,10 +1,12 @@
 mod coprocessor_0;
+mod coprocessor_1_control;
 mod cpu;
 mod fpu;
 mod indexed;
 mod parse_error;
 
 pub use coprocessor_0::Coprocessor0Register;
+pub use coprocessor_1_control::Coprocessor1ControlRegister;
 pub use cpu::CpuRegister;
 pub use fpu::FpuRegister;
 pub use indexed::IndexedRegister;
@@ -22,6 +24,10 @@ impl IndexedRegister {
     pub const fn try_into_coprocessor_0(self) -> Option<Coprocessor0Register> {
         Coprocessor0Register::try_from_indexed(self)
     }
+
+    pub const fn try_into_coprocessor_1_control(self) -> Option<Coprocessor1ControlRegister> {
+        Coprocessor1ControlRegister::try_from_indexed(self)
+    }
 }
 
 impl CpuRegister {
@@ -47,6 +53,20 @@ impl Coprocessor0Register {
     }
 }
 
+impl Coprocessor1ControlRegister {
+    pub const fn try_from_indexed(register: IndexedRegister) -> Option<Self> {
+        use IndexedRegister::*;
+        match register {
+            _0 => Some(Self::Implementation),
+            _25 => Some(Self::ConditionCodes),
+            _26 => Some(Self::Exceptions),
+            _28 => Some(Self::Enables),
+            _31 => Some(Self::ControlStatus),
+            _ => None,
+        }
+    }
+}
+
 impl FpuRegister {
     pub const fn to_cpu(self) -> CpuRegister {
         unsafe { core::mem::transmute(self) }
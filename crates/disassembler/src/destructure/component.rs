@@ -1,6 +1,6 @@
 use seaside_constants::{
     ConditionCode, NumberFormat,
-    register::{Coprocessor0Register, CpuRegister, FpuRegister},
+    register::{Coprocessor0Register, Coprocessor1ControlRegister, CpuRegister, FpuRegister},
 };
 
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
@@ -11,6 +11,7 @@ pub enum Component {
     CpuRegister(CpuRegister),
     WrappedCpuRegister(CpuRegister),
     Coprocessor0Register(Coprocessor0Register),
+    Coprocessor1ControlRegister(Coprocessor1ControlRegister),
     FpuRegister(FpuRegister),
     Cc(ConditionCode),
     Condition(bool),
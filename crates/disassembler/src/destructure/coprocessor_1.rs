@@ -24,6 +24,19 @@ pub fn destructure(instruction: Instruction) -> Option<DestructuredInstruction>
                 components,
             ));
         }
+        Some(
+            r#fn @ (Coprocessor1RegisterImmediateFn::ControlFromCoprocessor1
+            | Coprocessor1RegisterImmediateFn::ControlToCoprocessor1),
+        ) => {
+            let fs = fields::fs(instruction).to_indexed();
+            components[0] = Component::CpuRegister(ft.to_cpu());
+            components[1] =
+                Component::Coprocessor1ControlRegister(fs.try_into_coprocessor_1_control()?);
+            return Some(DestructuredInstruction::new(
+                Operation::Coprocessor1RegisterImmediateFn(r#fn),
+                components,
+            ));
+        }
         Some(r#fn @ Coprocessor1RegisterImmediateFn::BranchCoprocessor1Flag) => {
             components[0] = Component::Condition(fields::condition_from_fpu_register(ft));
             components[1] = Component::Cc(fields::cc_from_fpu_register(ft));
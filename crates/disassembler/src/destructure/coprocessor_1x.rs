@@ -0,0 +1,50 @@
+use super::{Component, DestructuredInstruction, Operation};
+use crate::fields;
+use num_traits::FromPrimitive;
+use seaside_constants::fn_codes::Coprocessor1XFn;
+use seaside_type_aliases::Instruction;
+
+pub fn destructure(instruction: Instruction) -> Option<DestructuredInstruction> {
+    use Coprocessor1XFn::*;
+    let base = fields::rs(instruction);
+    let index = fields::rt(instruction);
+    let fs = fields::fs(instruction);
+    let fd = fields::fd(instruction);
+    let r#fn = Coprocessor1XFn::from_u8(fields::r#fn(instruction))?;
+    let mut components = [Component::default(); 5];
+    match r#fn {
+        LoadWordIndexed | LoadDoubleIndexed => {
+            components[0] = Component::FpuRegister(fd);
+            components[1] = Component::CpuRegister(index);
+            components[2] = Component::WrappedCpuRegister(base);
+        }
+        StoreWordIndexed | StoreDoubleIndexed => {
+            components[0] = Component::FpuRegister(fs);
+            components[1] = Component::CpuRegister(index);
+            components[2] = Component::WrappedCpuRegister(base);
+        }
+        PrefetchIndexed => {
+            // The hint is stored where `fd` would be.
+            components[0] = Component::Shamt(fields::shamt(instruction));
+            components[1] = Component::CpuRegister(index);
+            components[2] = Component::WrappedCpuRegister(base);
+        }
+        MultiplyAddSingle
+        | MultiplyAddDouble
+        | MultiplySubtractSingle
+        | MultiplySubtractDouble
+        | NegativeMultiplyAddSingle
+        | NegativeMultiplyAddDouble
+        | NegativeMultiplySubtractSingle
+        | NegativeMultiplySubtractDouble => {
+            components[0] = Component::FpuRegister(fd);
+            components[1] = Component::FpuRegister(fields::fr(instruction));
+            components[2] = Component::FpuRegister(fs);
+            components[3] = Component::FpuRegister(fields::ft(instruction));
+        }
+    }
+    Some(DestructuredInstruction::new(
+        Operation::Coprocessor1XFn(r#fn),
+        components,
+    ))
+}
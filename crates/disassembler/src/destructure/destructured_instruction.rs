@@ -36,6 +36,11 @@ impl Display for DestructuredInstruction {
             Coprocessor1Fn(r#fn) => write!(f, "{fn}"),
             Coprocessor1RegisterImmediateFn(r#fn) => write!(f, "{fn}"),
             Special2Fn(r#fn) => write!(f, "{fn}"),
+            Special3Fn(r#fn) => write!(f, "{fn}"),
+            ByteShuffleFn(r#fn) => write!(f, "{fn}"),
+            Coprocessor1XFn(r#fn) => write!(f, "{fn}"),
+            RotateRight => write!(f, "rotr"),
+            RotateRightVariable => write!(f, "rotrv"),
         }?;
         let mut needs_comma: bool = false;
         for component in self.components {
@@ -49,6 +54,7 @@ impl Display for DestructuredInstruction {
                 Component::CpuRegister(register) => write!(f, " {register:#}"),
                 Component::WrappedCpuRegister(register) => write!(f, "({register:#})"),
                 Component::Coprocessor0Register(register) => write!(f, " {register:#}"),
+                Component::Coprocessor1ControlRegister(register) => write!(f, " {register:#}"),
                 Component::FpuRegister(register) => write!(f, " {register:#}"),
                 Component::Cc(cc) => write!(f, " {cc}"),
                 Component::Condition(c) => write!(f, "{}", if c { 't' } else { 'f' }),
@@ -12,12 +12,15 @@ pub fn destructure(opcode: Opcode, instruction: Instruction) -> Option<Destructu
     let mut components = [Component::default(); 5];
     match opcode {
         RegisterImmediate => return destructure_regimm(rs, rt, imm),
-        BranchEqual | BranchNotEqual => {
+        BranchEqual | BranchNotEqual | BranchEqualLikely | BranchNotEqualLikely => {
             components[0] = Component::CpuRegister(rs);
             components[1] = Component::CpuRegister(rt);
             components[2] = Component::Offset(imm);
         }
-        BranchLessEqualZero | BranchGreaterThanZero => {
+        BranchLessEqualZero
+        | BranchGreaterThanZero
+        | BranchLessEqualZeroLikely
+        | BranchGreaterThanZeroLikely => {
             components[0] = Component::CpuRegister(rs);
             components[1] = Component::Offset(imm);
         }
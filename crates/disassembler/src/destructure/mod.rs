@@ -3,11 +3,13 @@ pub mod destructured_instruction;
 
 mod coprocessor_0;
 mod coprocessor_1;
+mod coprocessor_1x;
 mod immediate;
 mod jump;
 mod operation;
 mod special;
 mod special_2;
+mod special_3;
 
 pub use component::Component;
 pub use destructured_instruction::DestructuredInstruction;
@@ -27,7 +29,9 @@ pub fn destructure(instruction: Instruction, address: Address) -> Option<Destruc
             Jump => jump::destructure(opcode, instruction),
             Coprocessor0 => coprocessor_0::destructure(instruction),
             Coprocessor1 => coprocessor_1::destructure(instruction),
+            Coprocessor1X => coprocessor_1x::destructure(instruction),
             Special2 => special_2::destructure(instruction),
+            Special3 => special_3::destructure(instruction),
         }?
         .with_address(address),
     )
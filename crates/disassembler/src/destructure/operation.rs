@@ -1,8 +1,8 @@
 use seaside_constants::{
     Opcode,
     fn_codes::{
-        Coprocessor0Fn, Coprocessor1Fn, Coprocessor1RegisterImmediateFn, RegisterImmediateFn,
-        Special2Fn, SpecialFn,
+        ByteShuffleFn, Coprocessor0Fn, Coprocessor1Fn, Coprocessor1RegisterImmediateFn,
+        Coprocessor1XFn, RegisterImmediateFn, Special2Fn, Special3Fn, SpecialFn,
     },
 };
 
@@ -15,6 +15,13 @@ pub enum Operation {
     Coprocessor1Fn(Coprocessor1Fn),
     Coprocessor1RegisterImmediateFn(Coprocessor1RegisterImmediateFn),
     Special2Fn(Special2Fn),
+    Special3Fn(Special3Fn),
+    ByteShuffleFn(ByteShuffleFn),
+    Coprocessor1XFn(Coprocessor1XFn),
+    /// `rotr`, which shares its function code with `srl`.
+    RotateRight,
+    /// `rotrv`, which shares its function code with `srlv`.
+    RotateRightVariable,
 }
 
 impl Default for Operation {
@@ -11,7 +11,22 @@ pub fn destructure(instruction: Instruction) -> Option<DestructuredInstruction>
     let rd = fields::rd(instruction);
     let r#fn = SpecialFn::from_u8(fields::r#fn(instruction))?;
     let mut components = [Component::default(); 5];
+    let mut operation = Operation::SpecialFn(r#fn);
     match r#fn {
+        // MIPS32 Release 2 reuses the otherwise unused `$rs` and `shamt` fields of `srl` and `srlv`
+        // to encode rotations.
+        ShiftRightLogical if rs as u8 == 1 => {
+            operation = Operation::RotateRight;
+            components[0] = Component::CpuRegister(rd);
+            components[1] = Component::CpuRegister(rt);
+            components[2] = Component::Shamt(fields::shamt(instruction));
+        }
+        ShiftRightLogicalVariable if fields::shamt(instruction) == 1 => {
+            operation = Operation::RotateRightVariable;
+            components[0] = Component::CpuRegister(rd);
+            components[1] = Component::CpuRegister(rt);
+            components[2] = Component::CpuRegister(rs);
+        }
         ShiftLeftLogical | ShiftRightLogical | ShiftRightArithmetic => {
             components[0] = Component::CpuRegister(rd);
             components[1] = Component::CpuRegister(rt);
@@ -58,8 +73,5 @@ pub fn destructure(instruction: Instruction) -> Option<DestructuredInstruction>
         }
         SystemCall => {}
     };
-    Some(DestructuredInstruction::new(
-        Operation::SpecialFn(r#fn),
-        components,
-    ))
+    Some(DestructuredInstruction::new(operation, components))
 }
@@ -0,0 +1,40 @@
+use super::{Component, DestructuredInstruction, Operation};
+use crate::fields;
+use num_traits::FromPrimitive;
+use seaside_constants::fn_codes::{ByteShuffleFn, Special3Fn};
+use seaside_type_aliases::Instruction;
+
+pub fn destructure(instruction: Instruction) -> Option<DestructuredInstruction> {
+    use Special3Fn::*;
+    let rs = fields::rs(instruction);
+    let rt = fields::rt(instruction);
+    let rd = fields::rd(instruction);
+    let shamt = fields::shamt(instruction);
+    let r#fn = Special3Fn::from_u8(fields::r#fn(instruction))?;
+    let mut components = [Component::default(); 5];
+    let operation = match r#fn {
+        Extract => {
+            // `rd` holds the size of the bit field minus one, and `shamt` holds its position.
+            components[0] = Component::CpuRegister(rt);
+            components[1] = Component::CpuRegister(rs);
+            components[2] = Component::Shamt(shamt);
+            components[3] = Component::Shamt(rd as u8 + 1);
+            Operation::Special3Fn(r#fn)
+        }
+        Insert => {
+            // `rd` holds the position of the most significant bit of the bit field, and `shamt`
+            // holds the position of the least significant bit.
+            components[0] = Component::CpuRegister(rt);
+            components[1] = Component::CpuRegister(rs);
+            components[2] = Component::Shamt(shamt);
+            components[3] = Component::Shamt((rd as u8).checked_sub(shamt)? + 1);
+            Operation::Special3Fn(r#fn)
+        }
+        ByteShuffle => {
+            components[0] = Component::CpuRegister(rd);
+            components[1] = Component::CpuRegister(rt);
+            Operation::ByteShuffleFn(ByteShuffleFn::from_u8(shamt)?)
+        }
+    };
+    Some(DestructuredInstruction::new(operation, components))
+}
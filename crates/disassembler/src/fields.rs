@@ -85,3 +85,8 @@ pub const fn fs(instruction: Instruction) -> FpuRegister {
 pub const fn fd(instruction: Instruction) -> FpuRegister {
     unsafe { transmute::<u8, _>(shamt(instruction)) }
 }
+
+/// Gets the `fr` field of a coprocessor 1X instruction, which occupies the same bits as `rs`.
+pub const fn fr(instruction: Instruction) -> FpuRegister {
+    unsafe { transmute::<u8, _>(rs_raw(instruction)) }
+}
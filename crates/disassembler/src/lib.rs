@@ -27,6 +27,12 @@ pseudoblock! {
         Some(destructure(instruction, 0x00000000)?.to_string())
     }
 
+    /// Gets the mnemonic of `instruction`, which is the operator the assembler accepts for it.
+    pub fn mnemonic(instruction: Instruction) -> Option<String> {
+        let disassembled = disassemble(instruction)?;
+        disassembled.split(' ').next().map(str::to_string)
+    }
+
     #[cfg(test)]
     mod tests {
         use super::*;
@@ -67,6 +73,12 @@ pseudoblock! {
             assert_eq!(disassembled, "bc1t 6, 0x0013");
         }
 
+        #[test]
+        fn cfc1() {
+            let disassembled = disassemble(0x4448f800).expect("disassembly failed");
+            assert_eq!(disassembled, "cfc1 $t0, $fcsr");
+        }
+
         #[test]
         fn movt_s() {
             let disassembled = disassemble(0x46190811).expect("disassembly failed");
@@ -78,5 +90,23 @@ pseudoblock! {
             let disassembled = disassemble(0x83a80004).expect("disassembly failed");
             assert_eq!(disassembled, "lb $t0, 4($sp)");
         }
+
+        #[test]
+        fn rotr() {
+            let disassembled = disassemble(0x00294102).expect("disassembly failed");
+            assert_eq!(disassembled, "rotr $t0, $t1, 4");
+        }
+
+        #[test]
+        fn ext() {
+            let disassembled = disassemble(0x7d283900).expect("disassembly failed");
+            assert_eq!(disassembled, "ext $t0, $t1, 4, 8");
+        }
+
+        #[test]
+        fn seb() {
+            let disassembled = disassemble(0x7c094420).expect("disassembly failed");
+            assert_eq!(disassembled, "seb $t0, $t1");
+        }
     }
 }
@@ -0,0 +1,374 @@
+//! Branch prediction.
+//!
+//! Provides the trait [`BranchPredictor`] along with several classic implementations of it, and
+//! the struct [`BranchPredictors`], which feeds every conditional branch the interpreter executes
+//! to a set of predictors and tracks how accurate each of them is at each branch site.
+
+use crate::{profiler::percentage, symbols::SymbolTable};
+use seaside_config::features::branch_prediction::{BranchPredictionOptions, PredictorKind};
+use seaside_type_aliases::Address;
+use std::{collections::HashMap, io::Write};
+
+/// How many branch sites to show in the report.
+const MAX_ROWS: usize = 20;
+
+/// What actually happened when a conditional branch was executed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct BranchOutcome {
+    pub taken: bool,
+    /// Where the branch goes if it is taken.
+    pub target: Address,
+}
+
+/// What a [`BranchPredictor`] expects a conditional branch to do.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Prediction {
+    pub taken: bool,
+    /// Where the branch is expected to go if it is taken.
+    ///
+    /// Predictors that only guess the direction of a branch leave this as [`None`], in which case
+    /// the target is assumed to be computed correctly once the branch is decoded.
+    pub target: Option<Address>,
+}
+
+impl Prediction {
+    const fn direction(taken: bool) -> Self {
+        Self {
+            taken,
+            target: None,
+        }
+    }
+
+    /// Checks whether this prediction matches `outcome`.
+    pub fn is_correct(&self, outcome: BranchOutcome) -> bool {
+        match (self.taken, outcome.taken) {
+            (true, true) => self.target.is_none_or(|target| target == outcome.target),
+            (false, false) => true,
+            _ => false,
+        }
+    }
+}
+
+/// A strategy for predicting the outcome of conditional branches.
+pub trait BranchPredictor {
+    /// A short name used to label this predictor in reports.
+    fn name(&self) -> &'static str;
+
+    /// Predicts what the branch at `pc` will do.
+    fn predict(&self, pc: Address) -> Prediction;
+
+    /// Learns from what the branch at `pc` actually did.
+    fn update(&mut self, pc: Address, outcome: BranchOutcome);
+}
+
+/// Always predicts the same direction.
+#[derive(Clone, Copy, Debug)]
+pub struct StaticPredictor {
+    taken: bool,
+}
+
+impl StaticPredictor {
+    pub const fn new(taken: bool) -> Self {
+        Self { taken }
+    }
+}
+
+impl BranchPredictor for StaticPredictor {
+    fn name(&self) -> &'static str {
+        if self.taken { "taken" } else { "not-taken" }
+    }
+
+    fn predict(&self, _pc: Address) -> Prediction {
+        Prediction::direction(self.taken)
+    }
+
+    fn update(&mut self, _pc: Address, _outcome: BranchOutcome) {}
+}
+
+/// Predicts that each branch will do whatever it did last time.
+#[derive(Clone, Debug)]
+pub struct OneBitPredictor {
+    last_taken: Vec<bool>,
+}
+
+impl OneBitPredictor {
+    pub fn new(table_bits: u32) -> Self {
+        Self {
+            last_taken: vec![false; 1 << table_bits],
+        }
+    }
+}
+
+impl BranchPredictor for OneBitPredictor {
+    fn name(&self) -> &'static str {
+        "1-bit"
+    }
+
+    fn predict(&self, pc: Address) -> Prediction {
+        Prediction::direction(self.last_taken[index(pc, self.last_taken.len())])
+    }
+
+    fn update(&mut self, pc: Address, outcome: BranchOutcome) {
+        let index = index(pc, self.last_taken.len());
+        self.last_taken[index] = outcome.taken;
+    }
+}
+
+/// Tracks each branch with a two-bit saturating counter, so a single surprise isn't enough to
+/// change its prediction.
+#[derive(Clone, Debug)]
+pub struct TwoBitPredictor {
+    counters: Vec<u8>,
+}
+
+impl TwoBitPredictor {
+    pub fn new(table_bits: u32) -> Self {
+        Self {
+            counters: vec![WEAKLY_NOT_TAKEN; 1 << table_bits],
+        }
+    }
+}
+
+impl BranchPredictor for TwoBitPredictor {
+    fn name(&self) -> &'static str {
+        "2-bit"
+    }
+
+    fn predict(&self, pc: Address) -> Prediction {
+        Prediction::direction(self.counters[index(pc, self.counters.len())] >= WEAKLY_TAKEN)
+    }
+
+    fn update(&mut self, pc: Address, outcome: BranchOutcome) {
+        let index = index(pc, self.counters.len());
+        update_counter(&mut self.counters[index], outcome.taken);
+    }
+}
+
+/// Tracks branches with two-bit saturating counters indexed by the pc XORed with the outcomes of
+/// the most recent branches, letting it pick up on patterns shared between branches.
+#[derive(Clone, Debug)]
+pub struct GsharePredictor {
+    counters: Vec<u8>,
+    /// The outcomes of the most recent branches, with the latest in the lowest bit.
+    history: u32,
+    history_mask: u32,
+}
+
+impl GsharePredictor {
+    pub fn new(table_bits: u32, history_bits: u32) -> Self {
+        Self {
+            counters: vec![WEAKLY_NOT_TAKEN; 1 << table_bits],
+            history: 0,
+            history_mask: (1 << history_bits) - 1,
+        }
+    }
+
+    fn index(&self, pc: Address) -> usize {
+        index(pc ^ (self.history << 2), self.counters.len())
+    }
+}
+
+impl BranchPredictor for GsharePredictor {
+    fn name(&self) -> &'static str {
+        "gshare"
+    }
+
+    fn predict(&self, pc: Address) -> Prediction {
+        Prediction::direction(self.counters[self.index(pc)] >= WEAKLY_TAKEN)
+    }
+
+    fn update(&mut self, pc: Address, outcome: BranchOutcome) {
+        let index = self.index(pc);
+        update_counter(&mut self.counters[index], outcome.taken);
+        self.history = ((self.history << 1) | outcome.taken as u32) & self.history_mask;
+    }
+}
+
+/// Remembers the targets of recently taken branches, predicting that a branch is taken if and
+/// only if it has an entry.
+#[derive(Clone, Debug)]
+pub struct BranchTargetBuffer {
+    /// The address and target of the branch occupying each entry.
+    entries: Vec<Option<(Address, Address)>>,
+}
+
+impl BranchTargetBuffer {
+    pub fn new(table_bits: u32) -> Self {
+        Self {
+            entries: vec![None; 1 << table_bits],
+        }
+    }
+}
+
+impl BranchPredictor for BranchTargetBuffer {
+    fn name(&self) -> &'static str {
+        "btb"
+    }
+
+    fn predict(&self, pc: Address) -> Prediction {
+        match self.entries[index(pc, self.entries.len())] {
+            Some((branch, target)) if branch == pc => Prediction {
+                taken: true,
+                target: Some(target),
+            },
+            _ => Prediction::direction(false),
+        }
+    }
+
+    fn update(&mut self, pc: Address, outcome: BranchOutcome) {
+        let entry = &mut self.entries[index(pc, self.entries.len())];
+        if outcome.taken {
+            *entry = Some((pc, outcome.target));
+        } else if entry.is_some_and(|(branch, _)| branch == pc) {
+            *entry = None;
+        }
+    }
+}
+
+const WEAKLY_NOT_TAKEN: u8 = 1;
+const WEAKLY_TAKEN: u8 = 2;
+const STRONGLY_TAKEN: u8 = 3;
+
+fn update_counter(counter: &mut u8, taken: bool) {
+    *counter = if taken {
+        (*counter + 1).min(STRONGLY_TAKEN)
+    } else {
+        counter.saturating_sub(1)
+    };
+}
+
+/// Maps `pc` to an entry in a table of `len` entries, where `len` is a power of two.
+const fn index(pc: Address, len: usize) -> usize {
+    (pc >> 2) as usize & (len - 1)
+}
+
+/// How a single branch site behaved, and how often each predictor got it right.
+#[derive(Clone, Debug, Default)]
+struct SiteStats {
+    executed: u64,
+    taken: u64,
+    /// The number of correct predictions made by each predictor, in the same order as
+    /// [`BranchPredictors::predictors`].
+    correct: Vec<u64>,
+}
+
+/// Runs several branch predictors side by side on the same stream of branches.
+#[derive(Default)]
+pub struct BranchPredictors {
+    predictors: Vec<Box<dyn BranchPredictor>>,
+    sites: HashMap<Address, SiteStats>,
+}
+
+impl BranchPredictors {
+    pub fn new(options: &BranchPredictionOptions) -> Self {
+        let mut predictors = Self::default();
+        for &kind in &options.predictors {
+            let predictor: Box<dyn BranchPredictor> = match kind {
+                PredictorKind::Taken => Box::new(StaticPredictor::new(true)),
+                PredictorKind::NotTaken => Box::new(StaticPredictor::new(false)),
+                PredictorKind::OneBit => Box::new(OneBitPredictor::new(options.table_bits)),
+                PredictorKind::TwoBit => Box::new(TwoBitPredictor::new(options.table_bits)),
+                PredictorKind::Gshare => Box::new(GsharePredictor::new(
+                    options.table_bits,
+                    options.history_bits,
+                )),
+                PredictorKind::Btb => Box::new(BranchTargetBuffer::new(options.table_bits)),
+            };
+            predictors.push(predictor);
+        }
+        predictors
+    }
+
+    /// Adds `predictor` to the set being compared.
+    ///
+    /// This should be done before any branches are recorded; otherwise, the new predictor's
+    /// accuracy will be understated.
+    pub fn push(&mut self, predictor: Box<dyn BranchPredictor>) {
+        self.predictors.push(predictor);
+        for site in self.sites.values_mut() {
+            site.correct.push(0);
+        }
+    }
+
+    /// Asks every predictor what the branch at `pc` will do, then tells them what it did.
+    pub fn record(&mut self, pc: Address, outcome: BranchOutcome) {
+        let n_predictors = self.predictors.len();
+        let site = self.sites.entry(pc).or_insert_with(|| SiteStats {
+            correct: vec![0; n_predictors],
+            ..Default::default()
+        });
+        site.executed += 1;
+        if outcome.taken {
+            site.taken += 1;
+        }
+        for (predictor, correct) in self.predictors.iter_mut().zip(&mut site.correct) {
+            if predictor.predict(pc).is_correct(outcome) {
+                *correct += 1;
+            }
+            predictor.update(pc, outcome);
+        }
+    }
+
+    /// Writes the overall accuracy of each predictor to `writer`, followed by its accuracy at
+    /// each of the most frequently executed branch sites.
+    ///
+    /// `symbols` is used to describe branch sites in terms of the labels enclosing them.
+    pub fn write_report<W>(&self, writer: &mut W, symbols: &SymbolTable) -> std::io::Result<()>
+    where
+        W: Write,
+    {
+        let total: u64 = self.sites.values().map(|site| site.executed).sum();
+        writeln!(
+            writer,
+            "branch prediction: {total} conditional branches at {} sites",
+            self.sites.len(),
+        )?;
+        writeln!(
+            writer,
+            "  {:<24} {:>12} {:>8}",
+            "predictor", "correct", "accuracy"
+        )?;
+        for (i, predictor) in self.predictors.iter().enumerate() {
+            let correct: u64 = self.sites.values().map(|site| site.correct[i]).sum();
+            writeln!(
+                writer,
+                "  {:<24} {correct:>12} {:>7.2}%",
+                predictor.name(),
+                percentage(correct, total),
+            )?;
+        }
+
+        let mut sites: Vec<(&Address, &SiteStats)> = self.sites.iter().collect();
+        sites.sort_by(|(pc_a, a), (pc_b, b)| b.executed.cmp(&a.executed).then(pc_a.cmp(pc_b)));
+        write!(
+            writer,
+            "\nper-site accuracy:\n  {:<24} {:>10} {:>8}",
+            "branch", "executed", "taken %"
+        )?;
+        for predictor in &self.predictors {
+            write!(writer, " {:>9}", predictor.name())?;
+        }
+        writeln!(writer)?;
+        for (&pc, site) in sites.iter().take(MAX_ROWS) {
+            write!(
+                writer,
+                "  {:<24} {:>10} {:>7.2}%",
+                symbols.describe(pc),
+                site.executed,
+                percentage(site.taken, site.executed),
+            )?;
+            for &correct in &site.correct {
+                write!(writer, " {:>8.2}%", percentage(correct, site.executed))?;
+            }
+            writeln!(writer)?;
+        }
+        if sites.len() > MAX_ROWS {
+            writeln!(
+                writer,
+                "  ({} more sites not shown)",
+                sites.len() - MAX_ROWS
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,284 @@
+//! Calling convention checks.
+//!
+//! Provides the struct [`ConventionChecker`], which follows every procedure call and return to
+//! catch violations of the O32 calling convention: procedures that don't restore the registers
+//! they're supposed to preserve, and callers that rely on registers a call may have overwritten.
+
+use crate::{
+    RegisterFile,
+    calls::{self, Transfer},
+    is_syscall,
+    pipeline::{FPU_BASE, HI, LO, Operands},
+    symbols::SymbolTable,
+};
+use core::fmt::{Display, Formatter, Result as FmtResult};
+use num_traits::FromPrimitive;
+use seaside_constants::register::{CpuRegister, FpuRegister};
+use seaside_type_aliases::{Address, Instruction};
+use std::{collections::HashMap, io::Write};
+
+/// The registers a procedure must restore before returning: `$s0`-`$s7`, `$sp`, `$fp`, `$ra` and
+/// `$f20`-`$f31`.
+///
+/// Registers are numbered like in [`Operands`], with floating-point registers following the CPU
+/// registers.
+const CALLEE_SAVED: [u8; 23] = [
+    16,
+    17,
+    18,
+    19,
+    20,
+    21,
+    22,
+    23,
+    29,
+    30,
+    31,
+    FPU_BASE + 20,
+    FPU_BASE + 21,
+    FPU_BASE + 22,
+    FPU_BASE + 23,
+    FPU_BASE + 24,
+    FPU_BASE + 25,
+    FPU_BASE + 26,
+    FPU_BASE + 27,
+    FPU_BASE + 28,
+    FPU_BASE + 29,
+    FPU_BASE + 30,
+    FPU_BASE + 31,
+];
+
+/// The registers a procedure may overwrite without restoring them: `$at`, `$a0`-`$a3`,
+/// `$t0`-`$t9`, `$f4`-`$f19`, `hi` and `lo`.
+///
+/// The return values in `$v0`, `$v1` and `$f0`-`$f3` are left out, since callers are meant to
+/// read them.
+const CALLER_SAVED: u128 = (1 << 1)
+    | (0b1111 << 4)
+    | (0xff << 8)
+    | (0b11 << 24)
+    | (0xffff << (FPU_BASE + 4))
+    | (1 << HI)
+    | (1 << LO);
+
+/// A way a program can violate the calling convention.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ConventionWarningKind {
+    /// A procedure returned without restoring a register it was meant to preserve.
+    CalleeSavedNotRestored,
+    /// A caller read a register that a procedure it called may have overwritten.
+    CallerSavedReadAfterCall,
+}
+
+/// A violation of the calling convention found by [`ConventionChecker`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ConventionWarning {
+    pub kind: ConventionWarningKind,
+    /// The address of the instruction that returned or read the register.
+    pub pc: Address,
+    /// The register involved. CPU registers are numbered 0-31, followed by the floating-point
+    /// registers, `hi` and `lo`.
+    pub register: u8,
+    /// The address of the procedure that should have restored the register, or that may have
+    /// overwritten it.
+    pub procedure: Address,
+    /// How many times the instruction raised this warning.
+    pub occurrences: u64,
+}
+
+/// A procedure call that hasn't returned yet.
+#[derive(Clone, Copy, Debug)]
+struct Frame {
+    /// The address of the procedure that was called.
+    entry: Address,
+    /// The values of [`CALLEE_SAVED`] just after the call, in the same order.
+    saved: [u32; CALLEE_SAVED.len()],
+    /// The registers overwritten by the procedure this frame most recently called, minus those
+    /// written since it returned.
+    clobbered: u128,
+    /// The procedure this frame most recently called.
+    callee: Address,
+}
+
+/// Watches procedure calls for violations of the O32 calling convention.
+#[derive(Clone, Debug)]
+pub struct ConventionChecker {
+    /// The procedure calls in progress, starting with the program's entry point.
+    frames: Vec<Frame>,
+    warnings: Vec<ConventionWarning>,
+    /// Maps each instruction, kind of warning and register to the warning's index in `warnings`.
+    warning_indices: HashMap<(Address, ConventionWarningKind, u8), usize>,
+}
+
+impl ConventionChecker {
+    pub fn new(entry_point: Address) -> Self {
+        Self {
+            frames: vec![Frame {
+                entry: entry_point,
+                saved: [0; CALLEE_SAVED.len()],
+                clobbered: 0,
+                callee: entry_point,
+            }],
+            warnings: Vec::new(),
+            warning_indices: HashMap::new(),
+        }
+    }
+
+    /// The warnings raised so far, in the order they were first raised.
+    pub fn warnings(&self) -> &[ConventionWarning] {
+        &self.warnings
+    }
+
+    /// Records that `instruction` was executed at `pc`, leaving the registers as `registers`,
+    /// after which execution continued at `next_pc`.
+    pub fn record(
+        &mut self,
+        pc: Address,
+        instruction: Instruction,
+        next_pc: Address,
+        registers: &RegisterFile,
+    ) {
+        let operands = Operands::of(instruction);
+        let frame = self.frames.last_mut().expect("there is always a frame");
+        let (clobbered, callee) = (frame.clobbered, frame.callee);
+        // The registers a system call reads are only guessed, so they can't be trusted here.
+        if !is_syscall(instruction) {
+            for register in operands.reads() {
+                if clobbered & (1 << register) != 0 {
+                    self.warn(
+                        ConventionWarningKind::CallerSavedReadAfterCall,
+                        pc,
+                        register,
+                        callee,
+                    );
+                }
+            }
+        }
+        let frame = self.frames.last_mut().expect("there is always a frame");
+        for register in operands.writes() {
+            frame.clobbered &= !(1 << register);
+        }
+
+        match calls::transfer(pc, instruction, next_pc) {
+            Some(Transfer::Call(entry)) => {
+                let words = registers.to_words();
+                self.frames.push(Frame {
+                    entry,
+                    saved: CALLEE_SAVED.map(|register| word(&words, register)),
+                    clobbered: 0,
+                    callee: entry,
+                });
+            }
+            Some(Transfer::Return) if self.frames.len() > 1 => {
+                let frame = self.frames.pop().expect("there is more than one frame");
+                let words = registers.to_words();
+                for (&register, &saved) in CALLEE_SAVED.iter().zip(&frame.saved) {
+                    if word(&words, register) != saved {
+                        self.warn(
+                            ConventionWarningKind::CalleeSavedNotRestored,
+                            pc,
+                            register,
+                            frame.entry,
+                        );
+                    }
+                }
+                let caller = self.frames.last_mut().expect("there is always a frame");
+                caller.clobbered = CALLER_SAVED;
+                caller.callee = frame.entry;
+            }
+            _ => {}
+        }
+    }
+
+    /// Writes every warning raised so far to `writer`.
+    ///
+    /// `symbols` is used to name procedures and describe code in terms of the labels enclosing
+    /// it. Warnings don't include source lines, since the assembler doesn't emit debug info mapping
+    /// addresses back to lines; a build only records its labels.
+    pub fn write_report<W>(&self, writer: &mut W, symbols: &SymbolTable) -> std::io::Result<()>
+    where
+        W: Write,
+    {
+        let total: u64 = self
+            .warnings
+            .iter()
+            .map(|warning| warning.occurrences)
+            .sum();
+        writeln!(
+            writer,
+            "calling convention: {total} warnings from {} instructions",
+            self.warnings.len(),
+        )?;
+        for warning in &self.warnings {
+            let register = RegisterName(warning.register);
+            let procedure = symbols.describe(warning.procedure);
+            write!(
+                writer,
+                "  {} (pc: 0x{:08x}): ",
+                symbols.describe(warning.pc),
+                warning.pc,
+            )?;
+            match warning.kind {
+                ConventionWarningKind::CalleeSavedNotRestored => {
+                    write!(writer, "{procedure} returned without restoring {register}")?
+                }
+                ConventionWarningKind::CallerSavedReadAfterCall => write!(
+                    writer,
+                    "read {register} after calling {procedure}, which may have overwritten it",
+                )?,
+            }
+            if warning.occurrences > 1 {
+                write!(writer, " ({} times)", warning.occurrences)?;
+            }
+            writeln!(writer)?;
+        }
+        Ok(())
+    }
+
+    fn warn(&mut self, kind: ConventionWarningKind, pc: Address, register: u8, procedure: Address) {
+        match self.warning_indices.get(&(pc, kind, register)) {
+            Some(&index) => self.warnings[index].occurrences += 1,
+            None => {
+                self.warning_indices
+                    .insert((pc, kind, register), self.warnings.len());
+                self.warnings.push(ConventionWarning {
+                    kind,
+                    pc,
+                    register,
+                    procedure,
+                    occurrences: 1,
+                });
+            }
+        }
+    }
+}
+
+/// Displays a register numbered like in [`ConventionWarning::register`].
+struct RegisterName(u8);
+
+impl Display for RegisterName {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self.0 {
+            HI => write!(f, "hi"),
+            LO => write!(f, "lo"),
+            register if register >= FPU_BASE => match FpuRegister::from_u8(register - FPU_BASE) {
+                Some(register) => write!(f, "{register:#}"),
+                None => write!(f, "${register}"),
+            },
+            register => match CpuRegister::from_u8(register) {
+                Some(register) => write!(f, "{register:#}"),
+                None => write!(f, "${register}"),
+            },
+        }
+    }
+}
+
+/// Gets the value of `register` from `words`, as produced by [`RegisterFile::to_words`].
+fn word(words: &[u32; RegisterFile::N_WORDS], register: u8) -> u32 {
+    if register >= FPU_BASE {
+        // The floating-point registers come after `hi` and `lo`.
+        words[register as usize + 2]
+    } else {
+        words[register as usize]
+    }
+}
@@ -0,0 +1,49 @@
+//! Recognizing procedure calls and returns.
+
+use num_traits::FromPrimitive;
+use seaside_constants::{
+    Opcode,
+    fn_codes::{RegisterImmediateFn, SpecialFn},
+    register::CpuRegister,
+};
+use seaside_disassembler::fields;
+use seaside_type_aliases::{Address, Instruction};
+
+/// How an instruction moved execution between procedures.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Transfer {
+    /// Called the procedure starting at the given address.
+    Call(Address),
+    /// Returned to the caller of the current procedure.
+    Return,
+}
+
+/// Determines whether `instruction`, which was executed at `pc` and continued at `next_pc`,
+/// called or returned from a procedure.
+///
+/// Calls are made with `jal`, `jalr`, and taken `bltzal` and `bgezal` instructions. Returns are
+/// made with `jr $ra`.
+pub fn transfer(pc: Address, instruction: Instruction, next_pc: Address) -> Option<Transfer> {
+    match fields::opcode(instruction)? {
+        Opcode::JumpAndLink => Some(Transfer::Call(next_pc)),
+        Opcode::Special => match SpecialFn::from_u8(fields::r#fn(instruction))? {
+            SpecialFn::JumpAndLinkRegister => Some(Transfer::Call(next_pc)),
+            SpecialFn::JumpRegister if fields::rs(instruction) == CpuRegister::ReturnAddr => {
+                Some(Transfer::Return)
+            }
+            _ => None,
+        },
+        Opcode::RegisterImmediate => {
+            use RegisterImmediateFn::*;
+            match RegisterImmediateFn::from_u8(fields::rt_raw(instruction))? {
+                BranchLessThanZeroAndLink | BranchGreaterEqualZeroAndLink
+                    if next_pc != pc.wrapping_add(4) =>
+                {
+                    Some(Transfer::Call(next_pc))
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,55 @@
+//! Virtual time for deterministic runs.
+//!
+//! Provides the struct [`VirtualClock`], which stands in for the wall clock so a program sees the
+//! same times no matter how fast the host runs it.
+
+use core::time::Duration;
+use std::time::SystemTime;
+
+/// A clock that advances with each instruction executed instead of with real time.
+///
+/// The clock starts at the Unix epoch. Sleeping moves it forward without blocking.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct VirtualClock {
+    /// The current time in nanoseconds.
+    nanos: u64,
+    nanos_per_instruction: u64,
+}
+
+impl VirtualClock {
+    pub fn new(nanos_per_instruction: u32) -> Self {
+        Self {
+            nanos: 0,
+            nanos_per_instruction: nanos_per_instruction as u64,
+        }
+    }
+
+    /// Advances the clock by the time one instruction takes.
+    pub fn tick(&mut self) {
+        self.nanos = self.nanos.wrapping_add(self.nanos_per_instruction);
+    }
+
+    /// Advances the clock by `millis` milliseconds.
+    pub fn sleep(&mut self, millis: u32) {
+        self.nanos = self.nanos.wrapping_add(millis as u64 * 1_000_000);
+    }
+
+    /// The current time in milliseconds.
+    pub fn millis(&self) -> u64 {
+        self.nanos / 1_000_000
+    }
+
+    /// The current time in nanoseconds.
+    pub fn nanos(&self) -> u64 {
+        self.nanos
+    }
+
+    /// The current time as a [`SystemTime`].
+    pub fn time(&self) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_nanos(self.nanos)
+    }
+
+    pub fn set_nanos(&mut self, nanos: u64) {
+        self.nanos = nanos;
+    }
+}
@@ -0,0 +1,167 @@
+//! Post-mortem records of crashed programs.
+//!
+//! Provides the struct [`CoreDump`], which pairs a [`Snapshot`] of a program with the exception
+//! that ended it, along with its on-disk format.
+//!
+//! # Format
+//!
+//! All integers are little-endian.
+//!
+//! ```text
+//! magic             8 bytes    "SEACORE\0"
+//! version           u32
+//! exception         u32 code, then u32 length and message
+//! faulting pc       u32
+//! fd table          u32 count, then per fd: u32 fd, u32 length, description
+//! snapshot          see `snapshot`
+//! ```
+
+use crate::{
+    Exception, InterpreterState,
+    snapshot::{Snapshot, read_bytes, read_u32, write_u32},
+};
+use anyhow::{Context, Error, Result};
+use seaside_error::EngineError;
+use seaside_type_aliases::{Address, Instruction};
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+const MAGIC: [u8; 8] = *b"SEACORE\0";
+const VERSION: u32 = 2;
+
+/// The state of a program at the moment an unhandled exception ended it.
+#[derive(Clone)]
+pub struct CoreDump {
+    /// The code of the exception, as it would appear in the cause register.
+    pub exception_code: u32,
+    /// A description of the exception.
+    pub exception: String,
+    /// The address of the instruction that raised the exception.
+    pub pc: Address,
+    /// Every open file descriptor, along with a description of what it refers to.
+    pub fds: Vec<(u32, String)>,
+    pub snapshot: Snapshot,
+}
+
+impl CoreDump {
+    /// Captures the current state of `state`, which was just ended by `exception`.
+    pub fn capture(state: &InterpreterState, exception: Exception) -> Result<Self> {
+        // The pc has already moved past the faulting instruction, unless the instruction itself
+        // couldn't be fetched.
+        let pc = if exception == Exception::InvalidLoad(state.pc) {
+            state.pc
+        } else {
+            state.pc.wrapping_sub(4)
+        };
+        let mut fds: Vec<(u32, String)> = state
+            .files
+            .iter()
+            .map(|(&fd, handle)| (fd, handle.describe()))
+            .collect();
+        fds.sort();
+        Ok(Self {
+            exception_code: exception.code(),
+            exception: exception.to_string(),
+            pc,
+            fds,
+            snapshot: Snapshot::capture(state)?,
+        })
+    }
+
+    /// Gets the byte at `address`, if it was part of the program's memory.
+    pub fn read_u8(&self, address: Address) -> Option<u8> {
+        self.snapshot.regions.iter().find_map(|(start, bytes)| {
+            let offset = address.checked_sub(*start)? as usize;
+            bytes.get(offset).copied()
+        })
+    }
+
+    /// Gets the instruction at `address`, if it was part of the program's text.
+    ///
+    /// Instructions are stored in little-endian order regardless of the program's byte order, so
+    /// this only makes sense for addresses in a text segment.
+    pub fn read_instruction(&self, address: Address) -> Option<Instruction> {
+        let mut bytes = [0; 4];
+        for (offset, byte) in (0..).zip(&mut bytes) {
+            *byte = self.read_u8(address.checked_add(offset)?)?;
+        }
+        Some(Instruction::from_le_bytes(bytes))
+    }
+
+    /// Writes this core dump to the file at `path`, replacing it if it already exists.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Reads a core dump from the file at `path`.
+    pub fn load(path: &Path) -> Result<Self> {
+        Self::read_from(&mut BufReader::new(File::open(path)?))
+            .with_context(|| format!("failed to read core dump from {}", path.display()))
+    }
+
+    pub fn write_to<W>(&self, writer: &mut W) -> std::io::Result<()>
+    where
+        W: Write,
+    {
+        writer.write_all(&MAGIC)?;
+        write_u32(writer, VERSION)?;
+        write_u32(writer, self.exception_code)?;
+        write_string(writer, &self.exception)?;
+        write_u32(writer, self.pc)?;
+        write_u32(writer, self.fds.len() as u32)?;
+        for (fd, description) in &self.fds {
+            write_u32(writer, *fd)?;
+            write_string(writer, description)?;
+        }
+        self.snapshot.write_to(writer)
+    }
+
+    pub fn read_from<R>(reader: &mut R) -> Result<Self>
+    where
+        R: Read,
+    {
+        let mut magic = [0; 8];
+        reader.read_exact(&mut magic)?;
+        if magic != MAGIC {
+            return Err(Error::new(EngineError::ParsingFailure).context("not a seaside core dump"));
+        }
+        let version = read_u32(reader)?;
+        if version != VERSION {
+            return Err(Error::new(EngineError::OutdatedVersion))
+                .with_context(|| format!("unsupported core dump version {version}"));
+        }
+        let exception_code = read_u32(reader)?;
+        let exception = read_string(reader)?;
+        let pc = read_u32(reader)?;
+        let n_fds = read_u32(reader)?;
+        let mut fds = Vec::new();
+        for _ in 0..n_fds {
+            let fd = read_u32(reader)?;
+            fds.push((fd, read_string(reader)?));
+        }
+        Ok(Self {
+            exception_code,
+            exception,
+            pc,
+            fds,
+            snapshot: Snapshot::read_from(reader)?,
+        })
+    }
+}
+
+fn write_string<W: Write>(writer: &mut W, string: &str) -> std::io::Result<()> {
+    write_u32(writer, string.len() as u32)?;
+    writer.write_all(string.as_bytes())
+}
+
+fn read_string<R: Read>(reader: &mut R) -> Result<String> {
+    let len = read_u32(reader)?;
+    String::from_utf8(read_bytes(reader, len)?)
+        .map_err(|_| Error::new(EngineError::ParsingFailure).context("string is not valid UTF-8"))
+}
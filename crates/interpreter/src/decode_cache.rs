@@ -0,0 +1,255 @@
+//! Decoded instruction caching.
+//!
+//! Provides the struct [`DecodeCache`], which remembers how the instructions at recently executed
+//! addresses were decoded, so running them again skips both fetching them from instruction memory
+//! and decoding them.
+
+use crate::{Exception, Interpreter};
+use num_traits::FromPrimitive;
+use seaside_constants::{
+    InstructionFormat, NumberFormat, Opcode,
+    fn_codes::{
+        ByteShuffleFn, Coprocessor0Fn, Coprocessor1Fn, Coprocessor1RegisterImmediateFn,
+        Coprocessor1XFn, RegisterImmediateFn, Special2Fn, Special3Fn, SpecialFn,
+    },
+};
+use seaside_disassembler::fields;
+use seaside_type_aliases::{Address, Instruction};
+
+/// What an instruction does, with its opcode and every function code it depends on resolved.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Operation {
+    Special(SpecialFn),
+    RegisterImmediate(RegisterImmediateFn),
+    /// Any instruction in the immediate format besides the register immediate ones.
+    Immediate(Opcode),
+    Jump(Opcode),
+    Coprocessor0(Coprocessor0Fn),
+    Coprocessor1RegisterImmediate(Coprocessor1RegisterImmediateFn),
+    /// Floating-point arithmetic, which is only defined for some [`NumberFormat`]s.
+    Coprocessor1(NumberFormat, Coprocessor1Fn),
+    Coprocessor1X(Coprocessor1XFn),
+    Special2(Special2Fn),
+    /// Any instruction in the special 3 format besides the byte shuffles.
+    Special3(Special3Fn),
+    ByteShuffle(ByteShuffleFn),
+}
+
+impl Operation {
+    /// Decodes `instruction`, returning [`None`] if its opcode or any of its function codes are
+    /// reserved.
+    pub fn decode(instruction: Instruction) -> Option<Self> {
+        use InstructionFormat::*;
+        let opcode = fields::opcode(instruction)?;
+        let r#fn = fields::r#fn(instruction);
+        let operation = match InstructionFormat::from(opcode) {
+            Special => Self::Special(SpecialFn::from_u8(r#fn)?),
+            Immediate if opcode == Opcode::RegisterImmediate => {
+                Self::RegisterImmediate(RegisterImmediateFn::from_u8(fields::rt_raw(instruction))?)
+            }
+            Immediate => Self::Immediate(opcode),
+            Jump => Self::Jump(opcode),
+            Coprocessor0 => {
+                Self::Coprocessor0(Coprocessor0Fn::from_u8(fields::rs_raw(instruction))?)
+            }
+            Coprocessor1 => {
+                let fmt = fields::fmt(instruction);
+                match Coprocessor1RegisterImmediateFn::from_u8(fmt) {
+                    Some(r#fn) => Self::Coprocessor1RegisterImmediate(r#fn),
+                    None => Self::Coprocessor1(
+                        NumberFormat::from_u8(fmt)?,
+                        Coprocessor1Fn::from_u8(r#fn)?,
+                    ),
+                }
+            }
+            Coprocessor1X => Self::Coprocessor1X(Coprocessor1XFn::from_u8(r#fn)?),
+            Special2 => Self::Special2(Special2Fn::from_u8(r#fn)?),
+            Special3 => match Special3Fn::from_u8(r#fn)? {
+                Special3Fn::ByteShuffle => {
+                    Self::ByteShuffle(ByteShuffleFn::from_u8(fields::shamt(instruction))?)
+                }
+                r#fn => Self::Special3(r#fn),
+            },
+        };
+        Some(operation)
+    }
+}
+
+/// An instruction along with everything needed to dispatch it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DecodedInstruction {
+    pub instruction: Instruction,
+    /// What the instruction does, or [`None`] if executing it raises a
+    /// [reserved instruction](Exception::ReservedInstruction) exception.
+    pub operation: Option<Operation>,
+}
+
+/// Remembers the decoded instructions at recently executed addresses.
+///
+/// The cache is direct-mapped, so two instructions whose addresses are a multiple of
+/// [`DecodeCache::N_ENTRIES`] words apart evict each other.
+#[derive(Clone, Debug)]
+pub struct DecodeCache {
+    /// Each entry holds the address of the instruction it caches, which is used as its tag.
+    entries: Box<[Option<(Address, DecodedInstruction)>]>,
+    hits: u64,
+    misses: u64,
+}
+
+impl Default for DecodeCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DecodeCache {
+    pub const N_ENTRIES: usize = 1 << 12;
+
+    pub fn new() -> Self {
+        Self {
+            entries: vec![None; Self::N_ENTRIES].into_boxed_slice(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// The number of lookups that found the instruction in the cache.
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// The number of lookups that had to decode the instruction.
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// Looks up the decoded instruction at `pc`.
+    pub fn get(&mut self, pc: Address) -> Option<DecodedInstruction> {
+        let decoded = match self.entries[Self::index(pc)] {
+            Some((address, decoded)) if address == pc => Some(decoded),
+            _ => None,
+        };
+        match decoded {
+            Some(_) => self.hits += 1,
+            None => self.misses += 1,
+        }
+        decoded
+    }
+
+    /// Remembers that the instruction at `pc` decodes to `decoded`, evicting whichever instruction
+    /// shared its entry.
+    pub fn insert(&mut self, pc: Address, decoded: DecodedInstruction) {
+        self.entries[Self::index(pc)] = Some((pc, decoded));
+    }
+
+    /// Forgets every instruction that overlaps the `len` bytes starting at `address`.
+    pub fn invalidate(&mut self, address: Address, len: u32) {
+        if len as usize / 4 >= Self::N_ENTRIES {
+            self.clear();
+            return;
+        }
+        let start = address & !3;
+        let end = address.saturating_add(len);
+        for word in (start..end).step_by(4) {
+            let entry = &mut self.entries[Self::index(word)];
+            if entry.is_some_and(|(tag, _)| tag == word) {
+                *entry = None;
+            }
+        }
+    }
+
+    /// Forgets every instruction.
+    pub fn clear(&mut self) {
+        self.entries.fill(None);
+    }
+
+    const fn index(address: Address) -> usize {
+        (address as usize >> 2) % Self::N_ENTRIES
+    }
+}
+
+impl Interpreter {
+    /// Fetches the instruction at `pc` and decodes it, or reuses the previous decoding if the
+    /// decode cache has it.
+    pub(crate) fn fetch_decoded(&mut self, pc: Address) -> Result<DecodedInstruction, Exception> {
+        // Self-modifying code can overwrite instructions that have already been decoded.
+        let text_writes = self.state.memory.take_text_writes();
+        if let Some(cache) = &mut self.decode_cache {
+            for (address, len) in text_writes {
+                cache.invalidate(address, len);
+            }
+            if let Some(decoded) = cache.get(pc) {
+                self.state.memory.simulate_fetch(pc);
+                return Ok(decoded);
+            }
+        }
+        let instruction = self.state.memory.fetch_instruction(pc)?;
+        let decoded = self.decode(instruction);
+        if let Some(cache) = &mut self.decode_cache {
+            cache.insert(pc, decoded);
+        }
+        Ok(decoded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DecodeCache, DecodedInstruction, Operation};
+    use seaside_constants::fn_codes::{ByteShuffleFn, RegisterImmediateFn, Special3Fn, SpecialFn};
+
+    #[test]
+    fn decode_resolves_function_codes() {
+        // add $t0, $t1, $t2
+        assert_eq!(
+            Operation::decode(0x012a_4020),
+            Some(Operation::Special(SpecialFn::Add))
+        );
+        // bltz $t1, 4
+        assert_eq!(
+            Operation::decode(0x0520_0004),
+            Some(Operation::RegisterImmediate(
+                RegisterImmediateFn::BranchLessThanZero
+            ))
+        );
+        // ext $zero, $zero, 0, 1
+        assert_eq!(
+            Operation::decode(0x7c00_0000),
+            Some(Operation::Special3(Special3Fn::Extract))
+        );
+        // seb $t0, $t1
+        assert_eq!(
+            Operation::decode(0x7c09_4420),
+            Some(Operation::ByteShuffle(ByteShuffleFn::SignExtendByte))
+        );
+    }
+
+    #[test]
+    fn decode_rejects_reserved_function_codes() {
+        // A special instruction with function code 0x3f (dsra32).
+        assert_eq!(Operation::decode(0x0000_003f), None);
+        // A byte shuffle with an unused `shamt`.
+        assert_eq!(Operation::decode(0x7c09_4460), None);
+        // Coprocessor 1 arithmetic in the unsupported long format.
+        assert_eq!(Operation::decode(0x46a0_0000), None);
+    }
+
+    #[test]
+    fn invalidate_forgets_overlapping_instructions() {
+        let decoded = DecodedInstruction {
+            instruction: 0,
+            operation: Operation::decode(0),
+        };
+        let mut cache = DecodeCache::new();
+        cache.insert(0x0040_0000, decoded);
+        cache.insert(0x0040_0004, decoded);
+        cache.insert(0x0040_0008, decoded);
+        cache.invalidate(0x0040_0006, 1);
+        assert_eq!(cache.get(0x0040_0000), Some(decoded));
+        assert_eq!(cache.get(0x0040_0004), None);
+        assert_eq!(cache.get(0x0040_0008), Some(decoded));
+        // Instructions a multiple of `N_ENTRIES` words apart share an entry.
+        cache.insert(0x0040_0000 + 4 * DecodeCache::N_ENTRIES as u32, decoded);
+        assert_eq!(cache.get(0x0040_0000), None);
+        assert_eq!((cache.hits(), cache.misses()), (2, 2));
+    }
+}
@@ -0,0 +1,203 @@
+//! Terminal stand-ins for MARS's dialog services.
+//!
+//! Provides the struct [`Dialogs`], which shows the messages and asks the questions that MARS
+//! would use dialog boxes for. Answers can come from the user or, for non-interactive runs, from a
+//! script.
+
+use console::{Key, Term, style};
+use std::{
+    collections::VecDeque,
+    io::{Result, stdin},
+    path::Path,
+};
+
+/// The line in a dialog script that presses "Cancel" instead of giving an answer.
+pub const CANCEL: &str = "<cancel>";
+
+/// The button chosen in a confirmation dialog, as returned in `$a0`.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Confirmation {
+    Yes = 0,
+    No = 1,
+    Cancel = 2,
+}
+
+/// The outcome of an input dialog, as returned in `$a1`.
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum InputStatus {
+    /// The input was read successfully.
+    Ok = 0,
+    /// The input couldn't be parsed as the requested type.
+    ParseError = -1,
+    /// The dialog was cancelled.
+    Cancelled = -2,
+    /// The dialog was accepted without any input.
+    NoData = -3,
+    /// The input string didn't fit in the buffer, so it was truncated.
+    TooLong = -4,
+}
+
+/// The kind of message shown by a message dialog, as given in `$a1`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MessageKind {
+    Error,
+    Info,
+    Warning,
+    Question,
+    Plain,
+}
+
+impl From<u32> for MessageKind {
+    fn from(value: u32) -> Self {
+        match value {
+            0 => Self::Error,
+            1 => Self::Info,
+            2 => Self::Warning,
+            3 => Self::Question,
+            _ => Self::Plain,
+        }
+    }
+}
+
+/// Shows dialogs in the terminal.
+#[derive(Clone, Debug)]
+pub struct Dialogs {
+    term: Term,
+    /// The answers to give to upcoming input dialogs, one per line, if they're scripted.
+    script: Option<VecDeque<String>>,
+}
+
+impl Default for Dialogs {
+    fn default() -> Self {
+        Self::interactive()
+    }
+}
+
+impl Dialogs {
+    /// Creates dialogs that ask the user.
+    pub fn interactive() -> Self {
+        Self {
+            term: Term::stdout(),
+            script: None,
+        }
+    }
+
+    /// Creates dialogs that take their answers from the script at `path`.
+    ///
+    /// Each input dialog consumes one line of the script: the text to enter, "yes" or "no" for
+    /// confirmation dialogs, or [`CANCEL`] to cancel. Once the script runs out, every dialog is
+    /// cancelled. Message dialogs are shown as usual but don't consume anything.
+    pub fn scripted(path: &Path) -> Result<Self> {
+        let script = std::fs::read_to_string(path)?;
+        Ok(Self {
+            term: Term::stdout(),
+            script: Some(script.lines().map(str::to_string).collect()),
+        })
+    }
+
+    /// Asks a yes-or-no question.
+    pub fn confirm(&mut self, message: &str) -> Result<Confirmation> {
+        self.term
+            .write_str(&format!("{} {message} [y/n/c] ", style("?").cyan().bold()))?;
+        let answer = match &mut self.script {
+            Some(script) => {
+                let line = script.pop_front();
+                self.term.write_line(line.as_deref().unwrap_or(CANCEL))?;
+                parse_confirmation(line.as_deref())
+            }
+            None if !self.term.is_term() => parse_confirmation(read_line()?.as_deref()),
+            None => self.read_confirmation()?,
+        };
+        Ok(answer)
+    }
+
+    /// Asks for a line of input, returning [`None`] if the dialog was cancelled.
+    pub fn input(&mut self, message: &str) -> Result<Option<String>> {
+        self.term
+            .write_str(&format!("{} {message} ", style("?").cyan().bold()))?;
+        match &mut self.script {
+            Some(script) => {
+                let line = script.pop_front().filter(|line| line != CANCEL);
+                self.term.write_line(line.as_deref().unwrap_or(CANCEL))?;
+                Ok(line)
+            }
+            None if !self.term.is_term() => read_line(),
+            None => self.read_input(),
+        }
+    }
+
+    /// Shows a message.
+    pub fn show(&mut self, kind: MessageKind, message: &str) -> Result<()> {
+        let label = match kind {
+            MessageKind::Error => style("error:").red().bold(),
+            MessageKind::Info => style("info:").blue().bold(),
+            MessageKind::Warning => style("warning:").yellow().bold(),
+            MessageKind::Question => style("question:").cyan().bold(),
+            MessageKind::Plain => return self.term.write_line(message),
+        };
+        self.term.write_line(&format!("{label} {message}"))
+    }
+
+    /// Reads a single keypress from the terminal: y, n, or c or escape to cancel.
+    fn read_confirmation(&self) -> Result<Confirmation> {
+        loop {
+            let (answer, echo) = match self.term.read_key()? {
+                Key::Char('y' | 'Y') => (Confirmation::Yes, "yes"),
+                Key::Char('n' | 'N') => (Confirmation::No, "no"),
+                Key::Char('c' | 'C') | Key::Escape => (Confirmation::Cancel, CANCEL),
+                _ => continue,
+            };
+            self.term.write_line(echo)?;
+            return Ok(answer);
+        }
+    }
+
+    /// Reads a line from the terminal, treating escape as cancelling the dialog.
+    fn read_input(&self) -> Result<Option<String>> {
+        let mut input = String::new();
+        loop {
+            match self.term.read_key()? {
+                Key::Enter => {
+                    self.term.write_line("")?;
+                    return Ok(Some(input));
+                }
+                Key::Escape => {
+                    self.term.write_line(CANCEL)?;
+                    return Ok(None);
+                }
+                Key::Backspace => {
+                    if input.pop().is_some() {
+                        self.term.clear_chars(1)?;
+                    }
+                }
+                Key::Char(c) if !c.is_control() => {
+                    input.push(c);
+                    self.term.write_str(c.encode_utf8(&mut [0; 4]))?;
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Interprets a written answer to a confirmation dialog. Anything but yes or no cancels it.
+fn parse_confirmation(answer: Option<&str>) -> Confirmation {
+    match answer.map(str::trim) {
+        Some("y" | "yes") => Confirmation::Yes,
+        Some("n" | "no") => Confirmation::No,
+        _ => Confirmation::Cancel,
+    }
+}
+
+/// Reads a line from stdin without its line ending, returning [`None`] at the end of the input.
+fn read_line() -> Result<Option<String>> {
+    let mut line = String::new();
+    if stdin().read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    let len = line.trim_end_matches(['\n', '\r']).len();
+    line.truncate(len);
+    Ok(Some(line))
+}
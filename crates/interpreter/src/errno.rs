@@ -0,0 +1,63 @@
+//! Error numbers returned by the POSIX-like system services.
+//!
+//! Services report failures by returning the negated error number in `$v0`, like Linux system
+//! calls do. The numbers are the ones Linux uses on MIPS, so they don't depend on the host.
+
+use std::io::{Error, ErrorKind};
+
+pub const ENOENT: i32 = 2;
+pub const EIO: i32 = 5;
+pub const EBADF: i32 = 9;
+pub const EACCES: i32 = 13;
+pub const EEXIST: i32 = 17;
+pub const EXDEV: i32 = 18;
+pub const ENOTDIR: i32 = 20;
+pub const EISDIR: i32 = 21;
+pub const EINVAL: i32 = 22;
+pub const ENOSPC: i32 = 28;
+pub const ESPIPE: i32 = 29;
+pub const EROFS: i32 = 30;
+pub const ENAMETOOLONG: i32 = 78;
+pub const EOVERFLOW: i32 = 79;
+pub const ENOTEMPTY: i32 = 93;
+
+/// Gets the error number that best describes `error`, falling back to [`EIO`].
+pub fn from_io_error(error: &Error) -> i32 {
+    match error.kind() {
+        ErrorKind::NotFound => ENOENT,
+        ErrorKind::PermissionDenied => EACCES,
+        ErrorKind::AlreadyExists => EEXIST,
+        ErrorKind::CrossesDevices => EXDEV,
+        ErrorKind::NotADirectory => ENOTDIR,
+        ErrorKind::IsADirectory => EISDIR,
+        ErrorKind::InvalidInput => EINVAL,
+        ErrorKind::StorageFull => ENOSPC,
+        ErrorKind::NotSeekable => ESPIPE,
+        ErrorKind::ReadOnlyFilesystem => EROFS,
+        ErrorKind::InvalidFilename => ENAMETOOLONG,
+        ErrorKind::DirectoryNotEmpty => ENOTEMPTY,
+        _ => EIO,
+    }
+}
+
+/// Describes the error number `errno`, for use in traces.
+pub fn name(errno: i32) -> Option<&'static str> {
+    Some(match errno {
+        ENOENT => "ENOENT",
+        EIO => "EIO",
+        EBADF => "EBADF",
+        EACCES => "EACCES",
+        EEXIST => "EEXIST",
+        EXDEV => "EXDEV",
+        ENOTDIR => "ENOTDIR",
+        EISDIR => "EISDIR",
+        EINVAL => "EINVAL",
+        ENOSPC => "ENOSPC",
+        ESPIPE => "ESPIPE",
+        EROFS => "EROFS",
+        ENAMETOOLONG => "ENAMETOOLONG",
+        EOVERFLOW => "EOVERFLOW",
+        ENOTEMPTY => "ENOTEMPTY",
+        _ => return None,
+    })
+}
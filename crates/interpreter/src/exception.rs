@@ -10,6 +10,8 @@ pub enum Exception {
     InvalidLoad(Address),
     #[error("invalid load (address: 0x{0:08x})")]
     InvalidStore(Address),
+    #[error("stack overflow (address: 0x{0:08x})")]
+    StackOverflow(Address),
     #[error("{0}")]
     SyscallFailure(#[from] SyscallFailureKind),
     #[error("break exception thrown")]
@@ -26,6 +28,10 @@ pub enum Exception {
     FloatOverflow,
     #[error("floating-point operation underflowed")]
     FloatUnderflow,
+    #[error("invalid floating-point operation")]
+    FloatInvalid,
+    #[error("floating-point operation was inexact")]
+    FloatInexact,
     #[error("the interpreter did a goof (pls contact rose)")]
     InterpreterFailure, // hopefully you never see this one
 }
@@ -36,13 +42,13 @@ impl Exception {
         match *self {
             MalformedInstruction => 0,
             InvalidLoad(_) => 4,
-            InvalidStore(_) => 5,
+            InvalidStore(_) | StackOverflow(_) => 5,
             SyscallFailure(_) => 8,
             Break => 9,
             ReservedInstruction => 10,
             IntegerOverflowOrUnderflow => 12,
             Trap => 13,
-            DivideByZero => 15,
+            DivideByZero | FloatInvalid | FloatInexact => 15,
             FloatOverflow => 16,
             FloatUnderflow => 17,
             InterpreterFailure => 21,
@@ -50,7 +56,9 @@ impl Exception {
     }
 
     pub const fn vaddr(&self) -> Option<Address> {
-        if let Self::InvalidLoad(vaddr) | Self::InvalidStore(vaddr) = *self {
+        if let Self::InvalidLoad(vaddr) | Self::InvalidStore(vaddr) | Self::StackOverflow(vaddr) =
+            *self
+        {
             Some(vaddr)
         } else {
             None
@@ -1,5 +1,4 @@
 use crate::{Exception, Interpreter, InterpreterState, register_file::IndexByRegister};
-use num_traits::FromPrimitive;
 use seaside_constants::{
     fn_codes::Coprocessor0Fn,
     register::{Coprocessor0Register, CpuRegister},
@@ -14,10 +13,12 @@ impl Interpreter {
     /// 010000 x0x00 xxxxx xxxxx 000000xx000
     /// opcode  fn    $rt   $rd     ???
     /// ```
-    pub fn execute_coprocessor_0(&mut self, instruction: Instruction) -> Result<(), Exception> {
+    pub fn execute_coprocessor_0(
+        &mut self,
+        r#fn: Coprocessor0Fn,
+        instruction: Instruction,
+    ) -> Result<(), Exception> {
         use Coprocessor0Fn::*;
-        let r#fn = Coprocessor0Fn::from_u8(fields::rs_raw(instruction))
-            .ok_or(Exception::ReservedInstruction)?;
         let rt = fields::rt(instruction);
         let rd = fields::rd_raw(instruction);
         match r#fn {
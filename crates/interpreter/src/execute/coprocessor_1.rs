@@ -1,12 +1,13 @@
 use crate::{
     Exception, Interpreter, InterpreterState,
+    fpu::{self, FpuExceptions, RoundingMode},
     register_file::{IndexByRegister, TryIndexByRegister},
 };
-use num_traits::{FromPrimitive, Zero};
+use num_traits::Zero;
 use seaside_constants::{
     NumberFormat,
     fn_codes::{Coprocessor1Fn, Coprocessor1RegisterImmediateFn},
-    register::{CpuRegister, FpuRegister},
+    register::{CpuRegister, FpuRegister, IndexedRegister},
 };
 use seaside_disassembler::fields;
 use seaside_type_aliases::Instruction;
@@ -53,33 +54,44 @@ impl Interpreter {
     /// 010001 00xxx xxxxx xxxxx 00000000000
     /// cop. 1
     /// ```
-    pub fn execute_coprocessor_1(&mut self, instruction: Instruction) -> Result<(), Exception> {
+    ///
+    /// The register immediate instructions are executed by
+    /// [`Self::execute_coprocessor_1_register_immediate`].
+    pub fn execute_coprocessor_1(
+        &mut self,
+        fmt: NumberFormat,
+        r#fn: Coprocessor1Fn,
+        instruction: Instruction,
+    ) -> Result<(), Exception> {
         use NumberFormat::*;
         let ft = fields::ft(instruction);
         let fs = fields::fs(instruction);
         let fd = fields::fd(instruction);
-        let fmt = fields::fmt(instruction);
-        match Coprocessor1RegisterImmediateFn::from_u8(fmt) {
-            Some(Coprocessor1RegisterImmediateFn::MoveFromCoprocessor1) => {
-                return self.state.mfc1(ft.to_cpu(), self.state.registers.read(fs));
-            }
-            Some(Coprocessor1RegisterImmediateFn::MoveToCoprocessor1) => {
-                return self.state.mtc1(fs, self.state.registers.read(ft.to_cpu()));
-            }
-            Some(Coprocessor1RegisterImmediateFn::BranchCoprocessor1Flag) => {
-                return self.state.bc1c(ft, instruction);
-            }
-            None => {}
+        match fmt {
+            Single => self.execute_coprocessor_1_single(ft, fs, fd, r#fn),
+            Double => self.execute_coprocessor_1_double(ft, fs, fd, r#fn),
+            Word => self.execute_coprocessor_1_word(fs, fd, r#fn),
         }
-        let r#fn = match Coprocessor1Fn::from_u8(fields::r#fn(instruction)) {
-            Some(r#fn) => r#fn,
-            None => return Err(Exception::ReservedInstruction),
-        };
-        match NumberFormat::from_u8(fmt) {
-            Some(Single) => self.execute_coprocessor_1_single(ft, fs, fd, r#fn),
-            Some(Double) => self.execute_coprocessor_1_double(ft, fs, fd, r#fn),
-            Some(Word) => self.execute_coprocessor_1_word(fs, fd, r#fn),
-            _ => Err(Exception::ReservedInstruction),
+    }
+
+    /// Executes `instruction`, which must be one of the "register immediate" coprocessor 1
+    /// instructions.
+    pub fn execute_coprocessor_1_register_immediate(
+        &mut self,
+        r#fn: Coprocessor1RegisterImmediateFn,
+        instruction: Instruction,
+    ) -> Result<(), Exception> {
+        use Coprocessor1RegisterImmediateFn::*;
+        let ft = fields::ft(instruction);
+        let fs = fields::fs(instruction);
+        match r#fn {
+            MoveFromCoprocessor1 => self.state.mfc1(ft.to_cpu(), self.state.registers.read(fs)),
+            MoveToCoprocessor1 => self.state.mtc1(fs, self.state.registers.read(ft.to_cpu())),
+            ControlFromCoprocessor1 => self.state.cfc1(ft.to_cpu(), fs.to_indexed()),
+            ControlToCoprocessor1 => self
+                .state
+                .ctc1(fs.to_indexed(), self.state.registers.read(ft.to_cpu())),
+            BranchCoprocessor1Flag => self.state.bc1c(ft, instruction),
         }
     }
 
@@ -178,57 +190,77 @@ impl Interpreter {
 impl InterpreterState {
     /// Adds `fs_value` and `ft_value`, storing the sum in FPU register `fd`.
     fn add_s(&mut self, fd: FpuRegister, fs_value: f32, ft_value: f32) -> Result<(), Exception> {
-        self.registers.write(fd, fs_value + ft_value);
+        let (sum, exceptions) = fpu::add(fs_value, ft_value, self.rounding_mode());
+        self.registers.fcsr.signal(exceptions)?;
+        self.registers.write(fd, sum);
         Ok(())
     }
 
     /// Adds `fs_value` and `ft_value`, storing the sum in FPU register `fd`.
     fn add_d(&mut self, fd: FpuRegister, fs_value: f64, ft_value: f64) -> Result<(), Exception> {
-        self.registers.try_write(fd, fs_value + ft_value)
+        let (sum, exceptions) = fpu::add(fs_value, ft_value, self.rounding_mode());
+        self.registers.fcsr.signal(exceptions)?;
+        self.registers.try_write(fd, sum)
     }
 
     /// Subtracts `ft_value` from `fs_value`, storing the difference in FPU register `fd`.
     fn sub_s(&mut self, fd: FpuRegister, fs_value: f32, ft_value: f32) -> Result<(), Exception> {
-        self.registers.write(fd, fs_value - ft_value);
+        let (difference, exceptions) = fpu::sub(fs_value, ft_value, self.rounding_mode());
+        self.registers.fcsr.signal(exceptions)?;
+        self.registers.write(fd, difference);
         Ok(())
     }
 
     /// Subtracts `ft_value` from `fs_value`, storing the difference in FPU register `fd`.
     fn sub_d(&mut self, fd: FpuRegister, fs_value: f64, ft_value: f64) -> Result<(), Exception> {
-        self.registers.try_write(fd, fs_value - ft_value)
+        let (difference, exceptions) = fpu::sub(fs_value, ft_value, self.rounding_mode());
+        self.registers.fcsr.signal(exceptions)?;
+        self.registers.try_write(fd, difference)
     }
 
     /// Multiplies `fs_value` and `ft_value`, storing the product in FPU register `fd`.
     fn mul_s(&mut self, fd: FpuRegister, fs_value: f32, ft_value: f32) -> Result<(), Exception> {
-        self.registers.write(fd, fs_value * ft_value);
+        let (product, exceptions) = fpu::mul(fs_value, ft_value, self.rounding_mode());
+        self.registers.fcsr.signal(exceptions)?;
+        self.registers.write(fd, product);
         Ok(())
     }
 
     /// Multiplies `fs_value` and `ft_value`, storing the product in FPU register `fd`.
     fn mul_d(&mut self, fd: FpuRegister, fs_value: f64, ft_value: f64) -> Result<(), Exception> {
-        self.registers.try_write(fd, fs_value * ft_value)
+        let (product, exceptions) = fpu::mul(fs_value, ft_value, self.rounding_mode());
+        self.registers.fcsr.signal(exceptions)?;
+        self.registers.try_write(fd, product)
     }
 
     /// Divides `fs_value` by `ft_value`, storing the quotient in FPU register `fd`.
     fn div_s(&mut self, fd: FpuRegister, fs_value: f32, ft_value: f32) -> Result<(), Exception> {
-        self.registers.write(fd, fs_value / ft_value);
+        let (quotient, exceptions) = fpu::div(fs_value, ft_value, self.rounding_mode());
+        self.registers.fcsr.signal(exceptions)?;
+        self.registers.write(fd, quotient);
         Ok(())
     }
 
     /// Divides `fs_value` by `ft_value`, storing the quotient in FPU register `fd`.
     fn div_d(&mut self, fd: FpuRegister, fs_value: f64, ft_value: f64) -> Result<(), Exception> {
-        self.registers.try_write(fd, fs_value / ft_value)
+        let (quotient, exceptions) = fpu::div(fs_value, ft_value, self.rounding_mode());
+        self.registers.fcsr.signal(exceptions)?;
+        self.registers.try_write(fd, quotient)
     }
 
     /// Computes the square root of `fs_value`, storing the result in FPU register `fd`.
     fn sqrt_s(&mut self, fd: FpuRegister, fs_value: f32) -> Result<(), Exception> {
-        self.registers.write(fd, fs_value.sqrt());
+        let (root, exceptions) = fpu::sqrt(fs_value, self.rounding_mode());
+        self.registers.fcsr.signal(exceptions)?;
+        self.registers.write(fd, root);
         Ok(())
     }
 
     /// Computes the square root of `fs_value`, storing the result in FPU register `fd`.
     fn sqrt_d(&mut self, fd: FpuRegister, fs_value: f64) -> Result<(), Exception> {
-        self.registers.try_write(fd, fs_value.sqrt())
+        let (root, exceptions) = fpu::sqrt(fs_value, self.rounding_mode());
+        self.registers.fcsr.signal(exceptions)?;
+        self.registers.try_write(fd, root)
     }
 
     /// Computes the absolute value of `fs_value`, storing the result in FPU register `fd`.
@@ -270,64 +302,58 @@ impl InterpreterState {
         let cc = fields::cc_from_fpu_register(ft);
         let condition = fields::condition_from_fpu_register(ft);
         let offset = (instruction & 0xffff) as u16;
-        if self.registers.read_fpu_flag(cc) == condition {
-            self.branch(offset);
-        }
+        self.branch_if(self.registers.read_fpu_flag(cc) == condition, offset);
         Ok(())
     }
 
     /// Rounds `fs_value` to the nearest integer, storing the result in FPU register `fd`.
     ///
-    /// If `fs_value` is exactly halfway between two integers, rounds away from 0.0.
+    /// If `fs_value` is exactly halfway between two integers, rounds to the even one.
     fn round_w_s(&mut self, fd: FpuRegister, fs_value: f32) -> Result<(), Exception> {
-        self.registers.write(fd, fs_value.round() as i32);
-        Ok(())
+        self.word_s(fd, fs_value, RoundingMode::Nearest)
     }
 
     /// Rounds `fs_value` to the nearest integer, storing the result in FPU register `fd`.
     ///
-    /// If `fs_value` is exactly halfway between two integers, rounds away from 0.0.
+    /// If `fs_value` is exactly halfway between two integers, rounds to the even one.
     fn round_w_d(&mut self, fd: FpuRegister, fs_value: f64) -> Result<(), Exception> {
-        self.registers.try_write(fd, fs_value.round() as i64)
+        self.word_d(fd, fs_value, RoundingMode::Nearest)
     }
 
     /// Converts `fs_value` to an integer by discarding the fractional component, storing the result
     /// in FPU register `fd`.
     fn trunc_w_s(&mut self, fd: FpuRegister, fs_value: f32) -> Result<(), Exception> {
-        self.registers.write(fd, fs_value.trunc() as i32);
-        Ok(())
+        self.word_s(fd, fs_value, RoundingMode::Zero)
     }
 
     /// Converts `fs_value` to an integer by discarding the fractional component, storing the result
     /// in FPU register `fd`.
     fn trunc_w_d(&mut self, fd: FpuRegister, fs_value: f64) -> Result<(), Exception> {
-        self.registers.try_write(fd, fs_value.trunc() as i64)
+        self.word_d(fd, fs_value, RoundingMode::Zero)
     }
 
     /// Finds the smallest integer greater than or equal to `fs_value`, storing the result in FPU
     /// register `fd`.
     fn ceil_w_s(&mut self, fd: FpuRegister, fs_value: f32) -> Result<(), Exception> {
-        self.registers.write(fd, fs_value.ceil() as i32);
-        Ok(())
+        self.word_s(fd, fs_value, RoundingMode::PositiveInfinity)
     }
 
     /// Finds the smallest integer greater than or equal to `fs_value`, storing the result in FPU
     /// register `fd`.
     fn ceil_w_d(&mut self, fd: FpuRegister, fs_value: f64) -> Result<(), Exception> {
-        self.registers.try_write(fd, fs_value.ceil() as i64)
+        self.word_d(fd, fs_value, RoundingMode::PositiveInfinity)
     }
 
     /// Finds the largest integer less than or equal to `fs_value`, storing the result in FPU
     /// register `fd`.
     fn floor_w_s(&mut self, fd: FpuRegister, fs_value: f32) -> Result<(), Exception> {
-        self.registers.write(fd, fs_value.floor() as i32);
-        Ok(())
+        self.word_s(fd, fs_value, RoundingMode::NegativeInfinity)
     }
 
     /// Finds the largest integer less than or equal to `fs_value`, storing the result in FPU
     /// register `fd`.
     fn floor_w_d(&mut self, fd: FpuRegister, fs_value: f64) -> Result<(), Exception> {
-        self.registers.try_write(fd, fs_value.floor() as i64)
+        self.word_d(fd, fs_value, RoundingMode::NegativeInfinity)
     }
 
     /// If the condition flag specified by `ft` matches the condition, stores `fs_value` in FPU
@@ -393,46 +419,79 @@ impl InterpreterState {
 
     /// Converts `fs_value` to a double, storing the result in FPU register `fd`.
     fn cvt_d_s(&mut self, fd: FpuRegister, fs_value: f32) -> Result<(), Exception> {
+        self.registers.fcsr.signal(FpuExceptions::NONE)?;
         self.registers.try_write(fd, fs_value as f64)
     }
 
     /// Converts `fs_value` to a signed 32-bit integer, storing the result in FPU register `fd`.
+    ///
+    /// The rounding mode in FCSR decides how `fs_value` is rounded.
     fn cvt_w_s(&mut self, fd: FpuRegister, fs_value: f32) -> Result<(), Exception> {
-        // The manual I'm referencing mentions something calling FCSR (which supposedly would
-        // influence what kind of rounding is used here), but I have no idea what they're talking
-        // about.
-        self.round_w_s(fd, fs_value)
+        self.word_s(fd, fs_value, self.rounding_mode())
     }
 
     /// Converts `fs_value` to a float, storing the result in FPU register `fd`.
     fn cvt_s_d(&mut self, fd: FpuRegister, fs_value: f64) -> Result<(), Exception> {
-        self.registers.write(fd, fs_value as f32);
+        let (single, exceptions) = fpu::to_single(fs_value, self.rounding_mode());
+        self.registers.fcsr.signal(exceptions)?;
+        self.registers.write(fd, single);
         Ok(())
     }
 
     /// Converts `fs_value` to a signed 32-bit integer, storing the result in FPU register `fd`.
+    ///
+    /// The rounding mode in FCSR decides how `fs_value` is rounded.
     fn cvt_w_d(&mut self, fd: FpuRegister, fs_value: f64) -> Result<(), Exception> {
-        // The manual I'm referencing mentions something calling FCSR (which supposedly would
-        // influence what kind of rounding is used here), but I have no idea what they're talking
-        // about.
-        self.round_w_d(fd, fs_value)
+        self.word_d(fd, fs_value, self.rounding_mode())
     }
 
     /// Converts `fs_value` to a float, storing the result in FPU register `fd`.
     fn cvt_s_w(&mut self, fd: FpuRegister, fs_value: i32) -> Result<(), Exception> {
-        self.registers.write(fd, fs_value as f32);
+        let (single, exceptions) = fpu::word_to_single(fs_value, self.rounding_mode());
+        self.registers.fcsr.signal(exceptions)?;
+        self.registers.write(fd, single);
         Ok(())
     }
 
     /// Converts `fs_value` to a double, storing the result in FPU register `fd`.
     fn cvt_d_w(&mut self, fd: FpuRegister, fs_value: i32) -> Result<(), Exception> {
+        self.registers.fcsr.signal(FpuExceptions::NONE)?;
         self.registers.try_write(fd, fs_value as f64)
     }
 
+    /// Converts `fs_value` to a signed 32-bit integer, rounding it according to `mode`, and stores
+    /// the result in FPU register `fd`.
+    fn word_s(
+        &mut self,
+        fd: FpuRegister,
+        fs_value: f32,
+        mode: RoundingMode,
+    ) -> Result<(), Exception> {
+        let (word, exceptions) = fpu::to_word(fs_value, mode);
+        self.registers.fcsr.signal(exceptions)?;
+        self.registers.write(fd, word);
+        Ok(())
+    }
+
+    /// Converts `fs_value` to a signed 32-bit integer, rounding it according to `mode`, and stores
+    /// the result in FPU register `fd`.
+    fn word_d(
+        &mut self,
+        fd: FpuRegister,
+        fs_value: f64,
+        mode: RoundingMode,
+    ) -> Result<(), Exception> {
+        let (word, exceptions) = fpu::to_word(fs_value, mode);
+        self.registers.fcsr.signal(exceptions)?;
+        self.registers.write(fd, word);
+        Ok(())
+    }
+
     /// Checks if `fs_value` is equal to `ft_value`, setting the FPU condition flag specified by
     /// `fd` accordingly.
     fn c_eq_s(&mut self, fd: FpuRegister, fs_value: f32, ft_value: f32) -> Result<(), Exception> {
         let cc = fields::cc_from_fpu_register(fd);
+        self.registers.fcsr.signal(FpuExceptions::NONE)?;
         self.registers.write_fpu_flag(cc, fs_value == ft_value);
         Ok(())
     }
@@ -441,6 +500,7 @@ impl InterpreterState {
     /// `fd` accordingly.
     fn c_eq_d(&mut self, fd: FpuRegister, fs_value: f64, ft_value: f64) -> Result<(), Exception> {
         let cc = fields::cc_from_fpu_register(fd);
+        self.registers.fcsr.signal(FpuExceptions::NONE)?;
         self.registers.write_fpu_flag(cc, fs_value == ft_value);
         Ok(())
     }
@@ -449,6 +509,9 @@ impl InterpreterState {
     /// `fd` accordingly.
     fn c_lt_s(&mut self, fd: FpuRegister, fs_value: f32, ft_value: f32) -> Result<(), Exception> {
         let cc = fields::cc_from_fpu_register(fd);
+        self.registers
+            .fcsr
+            .signal(fpu::compare_signaling(fs_value, ft_value))?;
         self.registers.write_fpu_flag(cc, fs_value < ft_value);
         Ok(())
     }
@@ -457,6 +520,9 @@ impl InterpreterState {
     /// `fd` accordingly.
     fn c_lt_d(&mut self, fd: FpuRegister, fs_value: f64, ft_value: f64) -> Result<(), Exception> {
         let cc = fields::cc_from_fpu_register(fd);
+        self.registers
+            .fcsr
+            .signal(fpu::compare_signaling(fs_value, ft_value))?;
         self.registers.write_fpu_flag(cc, fs_value < ft_value);
         Ok(())
     }
@@ -465,6 +531,9 @@ impl InterpreterState {
     /// specified by `fd` accordingly.
     fn c_le_s(&mut self, fd: FpuRegister, fs_value: f32, ft_value: f32) -> Result<(), Exception> {
         let cc = fields::cc_from_fpu_register(fd);
+        self.registers
+            .fcsr
+            .signal(fpu::compare_signaling(fs_value, ft_value))?;
         self.registers.write_fpu_flag(cc, fs_value <= ft_value);
         Ok(())
     }
@@ -473,6 +542,9 @@ impl InterpreterState {
     /// specified by `fd` accordingly.
     fn c_le_d(&mut self, fd: FpuRegister, fs_value: f64, ft_value: f64) -> Result<(), Exception> {
         let cc = fields::cc_from_fpu_register(fd);
+        self.registers
+            .fcsr
+            .signal(fpu::compare_signaling(fs_value, ft_value))?;
         self.registers.write_fpu_flag(cc, fs_value <= ft_value);
         Ok(())
     }
@@ -488,4 +560,28 @@ impl InterpreterState {
         self.registers.write(fs, rt_value);
         Ok(())
     }
+
+    /// Stores the value of floating-point control register `fs` in CPU register `rt`.
+    fn cfc1(&mut self, rt: CpuRegister, fs: IndexedRegister) -> Result<(), Exception> {
+        let fs = fs
+            .try_into_coprocessor_1_control()
+            .ok_or(Exception::ReservedInstruction)?;
+        self.registers.write(rt, self.registers.fcsr.read(fs));
+        Ok(())
+    }
+
+    /// Stores `rt_value` in floating-point control register `fs`.
+    ///
+    /// Raises an exception if this leaves an enabled exception in the cause field of FCSR.
+    fn ctc1(&mut self, fs: IndexedRegister, rt_value: u32) -> Result<(), Exception> {
+        let fs = fs
+            .try_into_coprocessor_1_control()
+            .ok_or(Exception::ReservedInstruction)?;
+        self.registers.fcsr.write(fs, rt_value)
+    }
+
+    /// The rounding mode currently selected in FCSR.
+    pub(super) fn rounding_mode(&self) -> RoundingMode {
+        self.registers.fcsr.rounding_mode()
+    }
 }
@@ -0,0 +1,177 @@
+use crate::{
+    Exception, Interpreter, InterpreterState,
+    fpu::{self, Ieee754},
+    memory::AccessKind,
+    register_file::{IndexByRegister, TryIndexByRegister},
+};
+use seaside_constants::{fn_codes::Coprocessor1XFn, register::FpuRegister};
+use seaside_disassembler::fields;
+use seaside_type_aliases::Instruction;
+
+impl Interpreter {
+    /// Executes `instruction`, which must follow one of the "coprocessor 1X" instruction formats.
+    ///
+    /// The indexed loads and stores add the CPU registers `$base` and `$index` to get their
+    /// address. Loads write to `$fd`, while stores read from `$fs`:
+    ///
+    /// ```text
+    ///   op   $base $index $fs  $fd    fn
+    /// 010011 xxxxx xxxxx xxxxx xxxxx 00xxxx
+    /// cop. 1x
+    /// ```
+    ///
+    /// The fused multiply-add instructions compute `$fs * $ft` and add or subtract `$fr`:
+    ///
+    /// ```text
+    ///   op    $fr   $ft   $fs   $fd    fn
+    /// 010011 xxxxx xxxxx xxxxx xxxxx xxxxxx
+    /// cop. 1x
+    /// ```
+    pub fn execute_coprocessor_1x(
+        &mut self,
+        r#fn: Coprocessor1XFn,
+        instruction: Instruction,
+    ) -> Result<(), Exception> {
+        use Coprocessor1XFn::*;
+        let base_value: u32 = self.state.registers.read(fields::rs(instruction));
+        let index_value: u32 = self.state.registers.read(fields::rt(instruction));
+        let address = u32::wrapping_add(base_value, index_value);
+        let fr = fields::fr(instruction);
+        let ft = fields::ft(instruction);
+        let fs = fields::fs(instruction);
+        let fd = fields::fd(instruction);
+        match r#fn {
+            LoadWordIndexed => self.state.lwxc1(fd, address),
+            LoadDoubleIndexed => self.state.ldxc1(fd, address),
+            StoreWordIndexed => self.state.swxc1(fs, address),
+            StoreDoubleIndexed => self.state.sdxc1(fs, address),
+            // seaside doesn't simulate caches closely enough for prefetching to matter.
+            PrefetchIndexed => Ok(()),
+            MultiplyAddSingle => self.state.madd_s(fd, fr, fs, ft, false, false),
+            MultiplyAddDouble => self.state.madd_d(fd, fr, fs, ft, false, false),
+            MultiplySubtractSingle => self.state.madd_s(fd, fr, fs, ft, true, false),
+            MultiplySubtractDouble => self.state.madd_d(fd, fr, fs, ft, true, false),
+            NegativeMultiplyAddSingle => self.state.madd_s(fd, fr, fs, ft, false, true),
+            NegativeMultiplyAddDouble => self.state.madd_d(fd, fr, fs, ft, false, true),
+            NegativeMultiplySubtractSingle => self.state.madd_s(fd, fr, fs, ft, true, true),
+            NegativeMultiplySubtractDouble => self.state.madd_d(fd, fr, fs, ft, true, true),
+        }
+    }
+}
+
+impl InterpreterState {
+    /// Loads the word stored at `address` into FPU register `fd`.
+    ///
+    /// # Exceptions
+    ///
+    /// Raises an [invalid load][Exception::InvalidLoad] exception if `address` points to
+    /// currently inaccessible memory or is not aligned to 4 bytes.
+    fn lwxc1(&mut self, fd: FpuRegister, address: u32) -> Result<(), Exception> {
+        let value = self.memory.read_u32(address, true)?;
+        self.memory
+            .simulate_data_access(address, 4, AccessKind::Read);
+        self.registers.write(fd, value);
+        Ok(())
+    }
+
+    /// Loads the double stored at `address` into FPU register `fd`.
+    ///
+    /// # Exceptions
+    ///
+    /// Raises an [invalid load][Exception::InvalidLoad] exception if `address` points to
+    /// currently inaccessible memory, `address` is not aligned to 8 bytes, or `fd` is not
+    /// divisible by two.
+    fn ldxc1(&mut self, fd: FpuRegister, address: u32) -> Result<(), Exception> {
+        let value = self.memory.read_u64(address, true)?;
+        self.memory
+            .simulate_data_access(address, 8, AccessKind::Read);
+        self.registers.try_write(fd, value)
+    }
+
+    /// Stores the value of FPU register `fs` at `address`.
+    ///
+    /// # Exceptions
+    ///
+    /// Raises an [invalid store][Exception::InvalidStore] exception if `address` points to
+    /// currently inaccessible memory or is not aligned to 4 bytes.
+    fn swxc1(&mut self, fs: FpuRegister, address: u32) -> Result<(), Exception> {
+        let fs_value = self.registers.read(fs);
+        self.memory.write_u32(address, fs_value, true)?;
+        self.memory
+            .simulate_data_access(address, 4, AccessKind::Write);
+        Ok(())
+    }
+
+    /// Stores the value of FPU register `fs` at `address`.
+    ///
+    /// # Exceptions
+    ///
+    /// Raises an [invalid store][Exception::InvalidStore] exception if `address` points to
+    /// currently inaccessible memory, `address` is not aligned to 8 bytes, or `fs` is not
+    /// divisible by two.
+    fn sdxc1(&mut self, fs: FpuRegister, address: u32) -> Result<(), Exception> {
+        let fs_value = self.registers.try_read(fs)?;
+        self.memory.write_u64(address, fs_value, true)?;
+        self.memory
+            .simulate_data_access(address, 8, AccessKind::Write);
+        Ok(())
+    }
+
+    /// Multiplies the values of FPU registers `fs` and `ft`, then adds the value of `fr` (or
+    /// subtracts it if `subtract` is set), rounding only once. If `negate` is set, the rounded
+    /// result is negated. The result is stored in FPU register `fd`.
+    fn madd_s(
+        &mut self,
+        fd: FpuRegister,
+        fr: FpuRegister,
+        fs: FpuRegister,
+        ft: FpuRegister,
+        subtract: bool,
+        negate: bool,
+    ) -> Result<(), Exception> {
+        let fr_value: f32 = self.registers.read(fr);
+        let fs_value: f32 = self.registers.read(fs);
+        let ft_value: f32 = self.registers.read(ft);
+        let result = self.fused_multiply_add(fr_value, fs_value, ft_value, subtract, negate)?;
+        self.registers.write(fd, result);
+        Ok(())
+    }
+
+    /// Multiplies the values of FPU registers `fs` and `ft`, then adds the value of `fr` (or
+    /// subtracts it if `subtract` is set), rounding only once. If `negate` is set, the rounded
+    /// result is negated. The result is stored in FPU register `fd`.
+    ///
+    /// # Exceptions
+    ///
+    /// Raises a [malformed instruction][Exception::MalformedInstruction] exception if any of the
+    /// registers are not divisible by two.
+    fn madd_d(
+        &mut self,
+        fd: FpuRegister,
+        fr: FpuRegister,
+        fs: FpuRegister,
+        ft: FpuRegister,
+        subtract: bool,
+        negate: bool,
+    ) -> Result<(), Exception> {
+        let fr_value: f64 = self.registers.try_read(fr)?;
+        let fs_value: f64 = self.registers.try_read(fs)?;
+        let ft_value: f64 = self.registers.try_read(ft)?;
+        let result = self.fused_multiply_add(fr_value, fs_value, ft_value, subtract, negate)?;
+        self.registers.try_write(fd, result)
+    }
+
+    fn fused_multiply_add<T: Ieee754>(
+        &mut self,
+        fr_value: T,
+        fs_value: T,
+        ft_value: T,
+        subtract: bool,
+        negate: bool,
+    ) -> Result<T, Exception> {
+        let addend = if subtract { -fr_value } else { fr_value };
+        let (result, exceptions) = fpu::mul_add(fs_value, ft_value, addend, self.rounding_mode());
+        self.registers.fcsr.signal(exceptions)?;
+        Ok(if negate { -result } else { result })
+    }
+}
@@ -0,0 +1,128 @@
+//! The heap allocator services.
+//!
+//! These hand out memory from a [`HeapAllocator`](crate::heap_allocator::HeapAllocator), which
+//! grows the heap through the same break as `sbrk` whenever it runs out of room.
+
+use crate::{
+    Exception, InterpreterState, SyscallFailureKind,
+    heap_allocator::{ALIGNMENT, HeapAllocator},
+    memory::Region,
+    register_file::IndexByRegister,
+};
+use seaside_constants::register::CpuRegister;
+use seaside_type_aliases::Address;
+
+impl InterpreterState {
+    /// Allocates `$a0` bytes, returning the address of the block, or 0 if the heap is full.
+    pub fn malloc(&mut self) -> Result<(), Exception> {
+        let size: u32 = self.registers.read(CpuRegister::Arg0);
+        let address = self.allocate(size).unwrap_or(0);
+        self.registers.write(CpuRegister::Val0, address);
+        Ok(())
+    }
+
+    /// Allocates room for `$a0` elements of `$a1` bytes each and fills it with zeroes. Returns the
+    /// address of the block, or 0 if the heap is full.
+    pub fn calloc(&mut self) -> Result<(), Exception> {
+        let count: u32 = self.registers.read(CpuRegister::Arg0);
+        let element_size: u32 = self.registers.read(CpuRegister::Arg1);
+        let address = match count
+            .checked_mul(element_size)
+            .and_then(|size| self.allocate(size).map(|address| (address, size)))
+        {
+            Some((address, size)) => {
+                self.write_bytes(address, &vec![0; size as usize])?;
+                address
+            }
+            None => 0,
+        };
+        self.registers.write(CpuRegister::Val0, address);
+        Ok(())
+    }
+
+    /// Resizes the block at `$a0` to `$a1` bytes, returning its new address.
+    ///
+    /// The block is moved if it can't grow in place, keeping as much of its contents as fits. If
+    /// the heap is full, 0 is returned and the block is left alone. A null address allocates a new
+    /// block, and a size of 0 frees the block and returns 0.
+    pub fn realloc(&mut self) -> Result<(), Exception> {
+        let old_address: Address = self.registers.read(CpuRegister::Arg0);
+        let size: u32 = self.registers.read(CpuRegister::Arg1);
+        let address = if old_address == 0 {
+            self.allocate(size).unwrap_or(0)
+        } else {
+            self.reallocate(old_address, size)?
+        };
+        self.registers.write(CpuRegister::Val0, address);
+        Ok(())
+    }
+
+    /// Frees the block at `$a0`. Freeing a null address does nothing.
+    pub fn free(&mut self) -> Result<(), Exception> {
+        let address: Address = self.registers.read(CpuRegister::Arg0);
+        if address != 0 {
+            self.heap_allocator
+                .free(address)
+                .map_err(Exception::SyscallFailure)?;
+        }
+        Ok(())
+    }
+
+    /// Resizes the allocated block at `old_address` to `size` bytes, returning its new address.
+    fn reallocate(&mut self, old_address: Address, size: u32) -> Result<Address, Exception> {
+        let old = self
+            .heap_allocator
+            .allocation(old_address)
+            .map_err(Exception::SyscallFailure)?;
+        let address = if size == 0 {
+            self.heap_allocator
+                .free(old_address)
+                .map_err(Exception::SyscallFailure)?;
+            0
+        } else if self.heap_allocator.resize_in_place(old_address, size) {
+            old_address
+        } else if let Some(address) = self.allocate(size) {
+            let contents = self.memory.get_slice(old_address)?[..old.size as usize].to_vec();
+            self.write_bytes(address, &contents)?;
+            self.heap_allocator
+                .free(old_address)
+                .map_err(Exception::SyscallFailure)?;
+            address
+        } else {
+            0
+        };
+        Ok(address)
+    }
+
+    /// Allocates a block of `size` bytes on behalf of the current `syscall`, growing the heap if
+    /// necessary.
+    fn allocate(&mut self, size: u32) -> Option<Address> {
+        // The pc has already moved past the `syscall` instruction.
+        let site = self.pc.wrapping_sub(4);
+        let address = match self.heap_allocator.allocate(size, site) {
+            Some(address) => address,
+            None => {
+                let capacity = HeapAllocator::capacity_for(size)?;
+                let padding = self.memory.next_heap_address().wrapping_neg() % ALIGNMENT;
+                let start = self.grow_heap(padding.checked_add(capacity)?)? + padding;
+                self.heap_allocator.add_memory(start, capacity);
+                self.heap_allocator.allocate(size, site)?
+            }
+        };
+        if let Some(memcheck) = self.memory.memcheck_mut() {
+            memcheck.allocate(address, size);
+        }
+        Some(address)
+    }
+
+    /// Copies `bytes` into memory starting at `address`.
+    fn write_bytes(&mut self, address: Address, bytes: &[u8]) -> Result<(), Exception> {
+        self.memory.prepare_slice_write(address, bytes.len());
+        let buffer = self.memory.get_slice_mut(address)?;
+        buffer
+            .get_mut(..bytes.len())
+            .ok_or(Exception::SyscallFailure(SyscallFailureKind::WriteFailed))?
+            .copy_from_slice(bytes);
+        Ok(())
+    }
+}
@@ -1,16 +1,16 @@
 use crate::{
     Exception, Interpreter, InterpreterState,
-    memory::Region,
+    memory::{AccessKind, Region},
     register_file::{IndexByRegister, TryIndexByRegister},
 };
-use num_traits::FromPrimitive;
 use seaside_constants::{
     Opcode,
+    fn_codes::RegisterImmediateFn,
     register::{CpuRegister, FpuRegister},
 };
 use seaside_disassembler::fields;
 use seaside_int_utils::{Endian, SignExtend};
-use seaside_type_aliases::Instruction;
+use seaside_type_aliases::{Address, Instruction};
 
 impl Interpreter {
     /// Executes `instruction`, which must follow the "immediate" instruction format:
@@ -31,11 +31,16 @@ impl Interpreter {
         let rs_value: u32 = self.state.registers.read(rs);
         let rt_value: u32 = self.state.registers.read(rt);
         match opcode {
-            RegisterImmediate => self.execute_regimm(rt, rs_value, imm),
             BranchEqual => self.state.beq(rs_value, rt_value, imm),
             BranchNotEqual => self.state.bne(rs_value, rt_value, imm),
             BranchLessEqualZero => self.state.blez(rs_value, imm),
             BranchGreaterThanZero => self.state.bgtz(rs_value, imm),
+            // Since seaside doesn't simulate the delay slot, there's no instruction for a
+            // branch-likely to nullify, so they behave exactly like their ordinary counterparts.
+            BranchEqualLikely => self.state.beq(rs_value, rt_value, imm),
+            BranchNotEqualLikely => self.state.bne(rs_value, rt_value, imm),
+            BranchLessEqualZeroLikely => self.state.blez(rs_value, imm),
+            BranchGreaterThanZeroLikely => self.state.bgtz(rs_value, imm),
             AddImmediate => self.state.addi(rt, rs_value, imm),
             AddImmediateUnsigned => self.state.addiu(rt, rs_value, imm),
             SetLessThanImmediate => self.state.slti(rt, rs_value, imm),
@@ -74,12 +79,12 @@ impl Interpreter {
     /// ```
     pub fn execute_regimm(
         &mut self,
-        rt: CpuRegister,
-        rs_value: u32,
-        imm: u16,
+        r#fn: RegisterImmediateFn,
+        instruction: Instruction,
     ) -> Result<(), Exception> {
-        use seaside_constants::fn_codes::RegisterImmediateFn::{self, *};
-        let r#fn = RegisterImmediateFn::from_u8(rt as u8).ok_or(Exception::ReservedInstruction)?;
+        use RegisterImmediateFn::*;
+        let rs_value: u32 = self.state.registers.read(fields::rs(instruction));
+        let imm = fields::imm(instruction);
         match r#fn {
             BranchLessThanZero => self.state.bltz(rs_value, imm, false),
             BranchGreaterEqualZero => self.state.bgez(rs_value, imm, false),
@@ -98,33 +103,25 @@ impl Interpreter {
 impl InterpreterState {
     /// If `rs_value` is equal to `rt_value`, branches `offset` instructions ahead.
     fn beq(&mut self, rs_value: u32, rt_value: u32, offset: u16) -> Result<(), Exception> {
-        if rs_value == rt_value {
-            self.branch(offset);
-        }
+        self.branch_if(rs_value == rt_value, offset);
         Ok(())
     }
 
     /// If `rs_value` is not equal to `rt_value`, branches `offset` instructions ahead.
     fn bne(&mut self, rs_value: u32, rt_value: u32, offset: u16) -> Result<(), Exception> {
-        if rs_value != rt_value {
-            self.branch(offset);
-        }
+        self.branch_if(rs_value != rt_value, offset);
         Ok(())
     }
 
     /// If `rs_value` is non-positive, branches `offset` instructions ahead.
     fn blez(&mut self, rs_value: u32, offset: u16) -> Result<(), Exception> {
-        if rs_value as i32 <= 0 {
-            self.branch(offset);
-        }
+        self.branch_if(rs_value as i32 <= 0, offset);
         Ok(())
     }
 
     /// If `rs_value` is strictly positive, branches `offset` instructions ahead.
     fn bgtz(&mut self, rs_value: u32, offset: u16) -> Result<(), Exception> {
-        if rs_value as i32 > 0 {
-            self.branch(offset);
-        }
+        self.branch_if(rs_value as i32 > 0, offset);
         Ok(())
     }
 
@@ -206,6 +203,8 @@ impl InterpreterState {
         let offset: i32 = offset.sign_extend();
         let address = u32::wrapping_add_signed(rs_value, offset);
         let value: i32 = self.memory.read_u8(address)?.sign_extend();
+        self.memory
+            .simulate_data_access(address, 1, AccessKind::Read);
         self.registers.write(rt, value);
         Ok(())
     }
@@ -221,6 +220,8 @@ impl InterpreterState {
         let offset: i32 = offset.sign_extend();
         let address = u32::wrapping_add_signed(rs_value, offset);
         let value: i32 = self.memory.read_u16(address, true)?.sign_extend();
+        self.memory
+            .simulate_data_access(address, 2, AccessKind::Read);
         self.registers.write(rt, value);
         Ok(())
     }
@@ -243,7 +244,11 @@ impl InterpreterState {
             }
         };
         let mask: u32 = !(u32::MAX << shift);
-        let loaded: u32 = self.memory.read_u32(word_address, false)? << shift;
+        let loaded: u32 = self.memory.read_u32_raw(word_address, false)? << shift;
+        let (start, len) = partial_word(address, self.memory.endian() == Endian::Big);
+        self.memory.check_read(start, len);
+        self.memory
+            .simulate_data_access(word_address, 4, AccessKind::Read);
         self.registers.write(rt, (rt_value & mask) | loaded);
         Ok(())
     }
@@ -258,6 +263,8 @@ impl InterpreterState {
         let offset: i32 = offset.sign_extend();
         let address = u32::wrapping_add_signed(rs_value, offset);
         let value: u32 = self.memory.read_u32(address, true)?;
+        self.memory
+            .simulate_data_access(address, 4, AccessKind::Read);
         self.registers.write(rt, value);
         Ok(())
     }
@@ -272,6 +279,8 @@ impl InterpreterState {
         let offset: i32 = offset.sign_extend();
         let address = u32::wrapping_add_signed(rs_value, offset);
         let value = self.memory.read_u8(address)? as u32;
+        self.memory
+            .simulate_data_access(address, 1, AccessKind::Read);
         self.registers.write(rt, value);
         Ok(())
     }
@@ -287,6 +296,8 @@ impl InterpreterState {
         let offset: i32 = offset.sign_extend();
         let address = u32::wrapping_add_signed(rs_value, offset);
         let value = self.memory.read_u16(address, true)? as u32;
+        self.memory
+            .simulate_data_access(address, 2, AccessKind::Read);
         self.registers.write(rt, value);
         Ok(())
     }
@@ -309,7 +320,11 @@ impl InterpreterState {
             }
         };
         let mask: u32 = !(u32::MAX >> shift);
-        let loaded: u32 = self.memory.read_u32(word_address, false)? >> shift;
+        let loaded: u32 = self.memory.read_u32_raw(word_address, false)? >> shift;
+        let (start, len) = partial_word(address, self.memory.endian() == Endian::Little);
+        self.memory.check_read(start, len);
+        self.memory
+            .simulate_data_access(word_address, 4, AccessKind::Read);
         self.registers.write(rt, (rt_value & mask) | loaded);
         Ok(())
     }
@@ -325,7 +340,10 @@ impl InterpreterState {
         let offset: i32 = offset.sign_extend();
         let address = u32::wrapping_add_signed(rs_value, offset);
         let byte = (rt_value & u8::MAX as u32) as u8;
-        self.memory.write_u8(address, byte)
+        self.memory.write_u8(address, byte)?;
+        self.memory
+            .simulate_data_access(address, 1, AccessKind::Write);
+        Ok(())
     }
 
     /// Stores the least significant halfword of `rt_value` at the address
@@ -339,7 +357,10 @@ impl InterpreterState {
         let offset: i32 = offset.sign_extend();
         let address = u32::wrapping_add_signed(rs_value, offset);
         let half = (rt_value & u16::MAX as u32) as u16;
-        self.memory.write_u16(address, half, true)
+        self.memory.write_u16(address, half, true)?;
+        self.memory
+            .simulate_data_access(address, 2, AccessKind::Write);
+        Ok(())
     }
 
     fn swl(&mut self, rs_value: u32, rt_value: u32, offset: u16) -> Result<(), Exception> {
@@ -355,9 +376,15 @@ impl InterpreterState {
         };
         let mask: u32 = !(u32::MAX >> shift);
         let to_store: u32 = rt_value >> shift;
-        let old_value: u32 = self.memory.read_u32(word_address, true)?;
+        let old_value: u32 = self.memory.read_u32_raw(word_address, true)?;
+        self.memory
+            .write_u32_raw(word_address, (old_value & mask) | to_store, true)?;
+        let (start, len) = partial_word(address, self.memory.endian() == Endian::Big);
+        self.memory.check_write(start, len);
+        // Merging the bytes into the word is part of the store, not a separate load.
         self.memory
-            .write_u32(word_address, (old_value & mask) | to_store, true)
+            .simulate_data_access(word_address, 4, AccessKind::Write);
+        Ok(())
     }
 
     /// Stores `rt_value` at the address `rs_value + offset.sign_extend()`.
@@ -369,7 +396,10 @@ impl InterpreterState {
     fn sw(&mut self, rs_value: u32, rt_value: u32, offset: u16) -> Result<(), Exception> {
         let offset: i32 = offset.sign_extend();
         let address = u32::wrapping_add_signed(rs_value, offset);
-        self.memory.write_u32(address, rt_value, true)
+        self.memory.write_u32(address, rt_value, true)?;
+        self.memory
+            .simulate_data_access(address, 4, AccessKind::Write);
+        Ok(())
     }
 
     /// Stores `rt_value` at the address `rs_value + offset.sign_extend()`. If the store was
@@ -411,9 +441,15 @@ impl InterpreterState {
         };
         let mask: u32 = !(u32::MAX << shift);
         let to_store: u32 = rt_value << shift;
-        let old_value: u32 = self.memory.read_u32(word_address, true)?;
+        let old_value: u32 = self.memory.read_u32_raw(word_address, true)?;
         self.memory
-            .write_u32(word_address, (old_value & mask) | to_store, true)
+            .write_u32_raw(word_address, (old_value & mask) | to_store, true)?;
+        let (start, len) = partial_word(address, self.memory.endian() == Endian::Little);
+        self.memory.check_write(start, len);
+        // Merging the bytes into the word is part of the store, not a separate load.
+        self.memory
+            .simulate_data_access(word_address, 4, AccessKind::Write);
+        Ok(())
     }
 
     /// Loads the word stored at address `rs_value + offset.sign_extend()` into CPU register `rt`.
@@ -441,6 +477,8 @@ impl InterpreterState {
         let offset: i32 = offset.sign_extend();
         let address = u32::wrapping_add_signed(rs_value, offset);
         let value = self.memory.read_u32(address, true)?;
+        self.memory
+            .simulate_data_access(address, 4, AccessKind::Read);
         self.registers.write(ft, value);
         Ok(())
     }
@@ -456,6 +494,8 @@ impl InterpreterState {
         let offset: i32 = offset.sign_extend();
         let address = u32::wrapping_add_signed(rs_value, offset);
         let value = self.memory.read_u64(address, true)?;
+        self.memory
+            .simulate_data_access(address, 8, AccessKind::Read);
         self.registers.try_write(ft, value)
     }
 
@@ -469,7 +509,10 @@ impl InterpreterState {
         let offset: i32 = offset.sign_extend();
         let address = u32::wrapping_add_signed(rs_value, offset);
         let ft_value = self.registers.read(ft);
-        self.memory.write_u32(address, ft_value, true)
+        self.memory.write_u32(address, ft_value, true)?;
+        self.memory
+            .simulate_data_access(address, 4, AccessKind::Write);
+        Ok(())
     }
 
     /// Stores the value of FPU register `ft` at address `rs_value + offset.sign_extend()`.
@@ -483,30 +526,31 @@ impl InterpreterState {
         let offset: i32 = offset.sign_extend();
         let address = u32::wrapping_add_signed(rs_value, offset);
         let ft_value = self.registers.try_read(ft)?;
-        self.memory.write_u64(address, ft_value, true)
+        self.memory.write_u64(address, ft_value, true)?;
+        self.memory
+            .simulate_data_access(address, 8, AccessKind::Write);
+        Ok(())
     }
 
     /// If `rs_value` is negative, branches `offset` instructions ahead. Also performs a link if
     /// `link` is set to `true`.
     fn bltz(&mut self, rs_value: u32, offset: u16, link: bool) -> Result<(), Exception> {
-        if (rs_value as i32) < 0 {
-            if link {
-                self.link();
-            }
-            self.branch(offset);
+        let condition = (rs_value as i32) < 0;
+        if condition && link {
+            self.link();
         }
+        self.branch_if(condition, offset);
         Ok(())
     }
 
     /// If `rs_value` is non-negative, branches `offset` instructions ahead. Also performs a link if
     /// `link` is set to `true`.
     fn bgez(&mut self, rs_value: u32, offset: u16, link: bool) -> Result<(), Exception> {
-        if (rs_value as i32) >= 0 {
-            if link {
-                self.link();
-            }
-            self.branch(offset);
+        let condition = (rs_value as i32) >= 0;
+        if condition && link {
+            self.link();
         }
+        self.branch_if(condition, offset);
         Ok(())
     }
 
@@ -596,3 +640,59 @@ impl InterpreterState {
         }
     }
 }
+
+/// Finds the bytes of the word containing `address` that an unaligned load or store touches,
+/// returning the address of the first one and how many there are.
+///
+/// If `from_address` is true, they run from `address` to the end of the word. Otherwise, they run
+/// from the start of the word up to and including `address`.
+fn partial_word(address: Address, from_address: bool) -> (Address, u32) {
+    let offset = address % 4;
+    if from_address {
+        (address, 4 - offset)
+    } else {
+        (address - offset, offset + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        memory::{MemCheckError, MemCheckErrorKind},
+        register_file::IndexByRegister,
+        snapshot::tests::interpreter,
+    };
+    use seaside_constants::register::CpuRegister;
+    use seaside_type_aliases::Address;
+    use std::fs;
+
+    #[test]
+    fn unaligned_accesses_only_check_bytes_they_touch() {
+        // Memory is little-endian, so each of these unaligned instructions touches a single byte.
+        let text = [
+            0x27bd_fff8, // addiu $sp, $sp, -8
+            0xa3a0_0000, // sb $zero, 0($sp)
+            0x8ba8_0000, // lwl $t0, 0($sp)
+            0xaba0_0004, // swl $zero, 4($sp)
+            0x8fa8_0004, // lw $t0, 4($sp)
+        ];
+        let (mut interpreter, directory) = interpreter("unaligned-memcheck", &text, |_| {});
+        let start = interpreter.state.pc;
+        interpreter.enable_memcheck();
+        for _ in 0..text.len() {
+            interpreter.step().unwrap();
+        }
+        let sp: Address = interpreter.state.registers.read(CpuRegister::StackPtr);
+        assert_eq!(
+            interpreter.state.memory.memcheck().unwrap().errors(),
+            [MemCheckError {
+                kind: MemCheckErrorKind::UninitializedRead,
+                pc: start + 16,
+                address: sp + 4,
+                size: 4,
+                occurrences: 1,
+            }],
+        );
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -1,8 +1,10 @@
-use crate::{Exception, Interpreter, InterpreterState, register_file::IndexByRegister};
+use crate::{
+    BranchOutcome, Exception, Interpreter, InterpreterState, register_file::IndexByRegister,
+};
 use seaside_constants::{Opcode, register::CpuRegister};
 use seaside_disassembler::fields;
 use seaside_int_utils::SignExtend;
-use seaside_type_aliases::Instruction;
+use seaside_type_aliases::{Address, Instruction};
 
 impl Interpreter {
     pub fn execute_jump_format(
@@ -22,8 +24,25 @@ impl Interpreter {
 
 impl InterpreterState {
     pub fn branch(&mut self, offset: u16) {
+        self.pc = self.branch_target(offset);
+    }
+
+    /// Branches `offset` instructions ahead if `condition` is true, recording the outcome for
+    /// branch predictors either way.
+    pub fn branch_if(&mut self, condition: bool, offset: u16) {
+        let target = self.branch_target(offset);
+        self.last_branch = Some(BranchOutcome {
+            taken: condition,
+            target,
+        });
+        if condition {
+            self.pc = target;
+        }
+    }
+
+    fn branch_target(&self, offset: u16) -> Address {
         let offset = <u16 as SignExtend<i32>>::sign_extend(&offset) << 2;
-        self.pc = u32::wrapping_add_signed(self.pc, offset);
+        u32::wrapping_add_signed(self.pc, offset)
     }
 
     pub fn link(&mut self) {
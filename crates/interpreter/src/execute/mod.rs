@@ -1,27 +1,62 @@
 mod coprocessor_0;
 mod coprocessor_1;
+mod coprocessor_1x;
+mod heap;
 mod immediate;
 mod jump;
+mod posix;
+mod process;
 mod special;
 mod special_2;
+mod special_3;
 mod syscall;
 
-use crate::{Exception, Interpreter};
-use seaside_constants::InstructionFormat;
-use seaside_disassembler::fields;
+use crate::{
+    Exception, Interpreter,
+    decode_cache::{DecodedInstruction, Operation},
+};
 use seaside_type_aliases::Instruction;
 
 impl Interpreter {
     pub fn execute(&mut self, instruction: Instruction) -> Result<(), Exception> {
-        use InstructionFormat::*;
-        let opcode = fields::opcode(instruction).ok_or(Exception::ReservedInstruction)?;
-        match InstructionFormat::from(opcode) {
-            Special => self.execute_special(instruction),
-            Immediate => self.execute_immediate_format(opcode, instruction),
-            Jump => self.execute_jump_format(opcode, instruction),
-            Coprocessor0 => self.execute_coprocessor_0(instruction),
-            Coprocessor1 => self.execute_coprocessor_1(instruction),
-            Special2 => self.execute_special_2(instruction),
+        let decoded = self.decode(instruction);
+        self.execute_decoded(decoded)
+    }
+
+    /// Works out how to dispatch `instruction`, including whether it's part of the configured
+    /// instruction set.
+    pub(crate) fn decode(&mut self, instruction: Instruction) -> DecodedInstruction {
+        let operation = Operation::decode(instruction).filter(|_| {
+            self.instruction_set
+                .as_mut()
+                .is_none_or(|instruction_set| instruction_set.allows(instruction))
+        });
+        DecodedInstruction {
+            instruction,
+            operation,
+        }
+    }
+
+    pub(crate) fn execute_decoded(&mut self, decoded: DecodedInstruction) -> Result<(), Exception> {
+        use Operation::*;
+        let DecodedInstruction {
+            instruction,
+            operation,
+        } = decoded;
+        match operation.ok_or(Exception::ReservedInstruction)? {
+            Special(r#fn) => self.execute_special(r#fn, instruction),
+            RegisterImmediate(r#fn) => self.execute_regimm(r#fn, instruction),
+            Immediate(opcode) => self.execute_immediate_format(opcode, instruction),
+            Jump(opcode) => self.execute_jump_format(opcode, instruction),
+            Coprocessor0(r#fn) => self.execute_coprocessor_0(r#fn, instruction),
+            Coprocessor1RegisterImmediate(r#fn) => {
+                self.execute_coprocessor_1_register_immediate(r#fn, instruction)
+            }
+            Coprocessor1(fmt, r#fn) => self.execute_coprocessor_1(fmt, r#fn, instruction),
+            Coprocessor1X(r#fn) => self.execute_coprocessor_1x(r#fn, instruction),
+            Special2(r#fn) => self.execute_special_2(r#fn, instruction),
+            Special3(r#fn) => self.execute_special_3(r#fn, instruction),
+            ByteShuffle(r#fn) => self.execute_byte_shuffle(r#fn, instruction),
         }
     }
 }
@@ -0,0 +1,271 @@
+//! The POSIX-like file system services.
+//!
+//! Unlike the `spim.file` services, these report failures by returning a negated error number from
+//! [`errno`](crate::errno) in `$v0`.
+
+use crate::{
+    Exception, InterpreterState, SyscallFailureKind,
+    errno::{self, EBADF, EINVAL, EOVERFLOW},
+    file_handle::{FileHandle, FileStatus},
+    memory::Region,
+    register_file::IndexByRegister,
+};
+use core::ffi::CStr;
+use seaside_constants::register::CpuRegister;
+use seaside_type_aliases::Address;
+use std::{
+    io::{self, SeekFrom},
+    path::PathBuf,
+    time::SystemTime,
+};
+
+/// The file type bits of a regular file's mode.
+const S_IFREG: u32 = 0o100000;
+/// The file type bits of a directory's mode.
+const S_IFDIR: u32 = 0o040000;
+/// The file type bits of a character device's mode, which is what the standard streams report.
+const S_IFCHR: u32 = 0o020000;
+
+/// Either a value for a service to return or the error number it failed with.
+type PosixResult = Result<u32, i32>;
+
+impl InterpreterState {
+    /// Opens the file at the path in `$a0` with the POSIX-style flags in `$a1`, creating it with
+    /// the permissions in `$a2` if necessary. Returns the new file descriptor.
+    pub fn posix_open(&mut self) -> Result<(), Exception> {
+        let path = self.path_arg(CpuRegister::Arg0)?;
+        let flags: u32 = self.registers.read(CpuRegister::Arg1);
+        let mode: u32 = self.registers.read(CpuRegister::Arg2);
+        let now = self.now();
+        let result = match self.sandbox.open_posix(&path, flags, mode, now) {
+            Ok(handle) => {
+                let fd = self.next_fd;
+                self.make_file_handle(handle);
+                Ok(fd)
+            }
+            Err(error) => Err(errno::from_io_error(&error)),
+        };
+        self.return_posix(result);
+        Ok(())
+    }
+
+    /// Moves the offset of the file with descriptor `$a0` by the signed offset in `$a1`.
+    ///
+    /// `$a2` says what the offset is relative to: 0 for the start of the file, 1 for the current
+    /// offset, and 2 for the end of the file. Returns the new offset.
+    pub fn lseek(&mut self) -> Result<(), Exception> {
+        let fd: u32 = self.registers.read(CpuRegister::Arg0);
+        let offset: i32 = self.registers.read(CpuRegister::Arg1);
+        let whence: u32 = self.registers.read(CpuRegister::Arg2);
+        let position = match whence {
+            0 => u64::try_from(offset).map(SeekFrom::Start).ok(),
+            1 => Some(SeekFrom::Current(offset as i64)),
+            2 => Some(SeekFrom::End(offset as i64)),
+            _ => None,
+        };
+        let result = match (self.files.get_mut(&fd), position) {
+            (None, _) => Err(EBADF),
+            (Some(_), None) => Err(EINVAL),
+            (Some(handle), Some(position)) => {
+                let old_offset = handle.seek(SeekFrom::Current(0));
+                match handle.seek(position) {
+                    // The offset has to fit in `$v0` without looking like an error number.
+                    Ok(offset) => i32::try_from(offset)
+                        .map(|offset| offset as u32)
+                        .map_err(|_| {
+                            // Like `lseek`, leave the offset where it was on failure.
+                            if let Ok(old_offset) = old_offset {
+                                let _ = handle.seek(SeekFrom::Start(old_offset));
+                            }
+                            EOVERFLOW
+                        }),
+                    Err(error) => Err(errno::from_io_error(&error)),
+                }
+            }
+        };
+        self.return_posix(result);
+        Ok(())
+    }
+
+    /// Writes the status of the file with descriptor `$a0` to the buffer at `$a1`.
+    ///
+    /// See [`write_stat`](Self::write_stat) for the layout of the buffer.
+    pub fn fstat(&mut self) -> Result<(), Exception> {
+        let fd: u32 = self.registers.read(CpuRegister::Arg0);
+        let buffer_address: Address = self.registers.read(CpuRegister::Arg1);
+        let result = match self.files.get(&fd).map(FileHandle::status) {
+            None => Err(EBADF),
+            Some(None) => {
+                self.write_stat(buffer_address, S_IFCHR | 0o620, 0, 0)?;
+                Ok(0)
+            }
+            Some(Some(Ok(status))) => {
+                self.write_status(buffer_address, &status)?;
+                Ok(0)
+            }
+            Some(Some(Err(error))) => Err(errno::from_io_error(&error)),
+        };
+        self.return_posix(result);
+        Ok(())
+    }
+
+    /// Writes the status of the file at the path in `$a0` to the buffer at `$a1`.
+    ///
+    /// See [`write_stat`](Self::write_stat) for the layout of the buffer.
+    pub fn stat(&mut self) -> Result<(), Exception> {
+        let path = self.path_arg(CpuRegister::Arg0)?;
+        let buffer_address: Address = self.registers.read(CpuRegister::Arg1);
+        let result = match self.sandbox.status(&path) {
+            Ok(status) => {
+                self.write_status(buffer_address, &status)?;
+                Ok(0)
+            }
+            Err(error) => Err(errno::from_io_error(&error)),
+        };
+        self.return_posix(result);
+        Ok(())
+    }
+
+    /// Deletes the file at the path in `$a0`.
+    pub fn unlink(&mut self) -> Result<(), Exception> {
+        let path = self.path_arg(CpuRegister::Arg0)?;
+        let result = self.sandbox.remove_file(&path);
+        self.return_posix_unit(result);
+        Ok(())
+    }
+
+    /// Renames the file at the path in `$a0` to the path in `$a1`, replacing any file already
+    /// there.
+    pub fn rename(&mut self) -> Result<(), Exception> {
+        let from = self.path_arg(CpuRegister::Arg0)?;
+        let to = self.path_arg(CpuRegister::Arg1)?;
+        let result = self.sandbox.rename(&from, &to);
+        self.return_posix_unit(result);
+        Ok(())
+    }
+
+    /// Creates a directory at the path in `$a0` with the permissions in `$a1`.
+    pub fn make_directory(&mut self) -> Result<(), Exception> {
+        let path = self.path_arg(CpuRegister::Arg0)?;
+        let mode: u32 = self.registers.read(CpuRegister::Arg1);
+        let result = self.sandbox.create_dir(&path, mode);
+        self.return_posix_unit(result);
+        Ok(())
+    }
+
+    /// Deletes the empty directory at the path in `$a0`.
+    pub fn remove_directory(&mut self) -> Result<(), Exception> {
+        let path = self.path_arg(CpuRegister::Arg0)?;
+        let result = self.sandbox.remove_dir(&path);
+        self.return_posix_unit(result);
+        Ok(())
+    }
+
+    /// Opens the directory at the path in `$a0` for reading its entries. Returns the new file
+    /// descriptor, which is closed like any other.
+    pub fn open_directory(&mut self) -> Result<(), Exception> {
+        let path = self.path_arg(CpuRegister::Arg0)?;
+        let result = match self.sandbox.open_directory(&path) {
+            Ok(handle) => {
+                let fd = self.next_fd;
+                self.make_file_handle(handle);
+                Ok(fd)
+            }
+            Err(error) => Err(errno::from_io_error(&error)),
+        };
+        self.return_posix(result);
+        Ok(())
+    }
+
+    /// Reads the name of the next entry of the directory with descriptor `$a0` into the buffer at
+    /// `$a1`, which holds `$a2` bytes.
+    ///
+    /// The name is nul-terminated. Returns its length without the nul byte, or 0 once every entry
+    /// has been read. Names that don't fit fail with `ENAMETOOLONG` and can be read again with a
+    /// bigger buffer.
+    pub fn read_directory(&mut self) -> Result<(), Exception> {
+        let fd: u32 = self.registers.read(CpuRegister::Arg0);
+        let buffer_address: Address = self.registers.read(CpuRegister::Arg1);
+        let requested_bytes =
+            <_ as IndexByRegister<_, u32>>::read(&self.registers, CpuRegister::Arg2) as usize;
+        self.memory
+            .prepare_slice_write(buffer_address, requested_bytes);
+        let buffer = self.memory.get_slice_mut(buffer_address)?;
+        let max_bytes = usize::min(requested_bytes, buffer.len());
+        let result = match self.files.get_mut(&fd) {
+            // The name needs room for its nul byte.
+            Some(handle) => match handle.read_entry(max_bytes.saturating_sub(1)) {
+                Ok(Some(name)) => {
+                    buffer[..name.len()].copy_from_slice(name.as_bytes());
+                    buffer[name.len()] = b'\0';
+                    Ok(name.len() as u32)
+                }
+                Ok(None) => Ok(0),
+                Err(error) => Err(errno::from_io_error(&error)),
+            },
+            None => Err(EBADF),
+        };
+        self.return_posix(result);
+        Ok(())
+    }
+
+    /// Reads the nul-terminated path whose address is in `register`.
+    fn path_arg(&self, register: CpuRegister) -> Result<PathBuf, Exception> {
+        let address: Address = self.registers.read(register);
+        let path = CStr::from_bytes_until_nul(self.memory.get_slice(address)?)
+            .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::NulNotFound))?
+            .to_str()
+            .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::InvalidUtf8))?;
+        Ok(PathBuf::from(path))
+    }
+
+    /// Writes `status` to the buffer at `address`.
+    fn write_status(&mut self, address: Address, status: &FileStatus) -> Result<(), Exception> {
+        let file_type = if status.is_directory {
+            S_IFDIR
+        } else {
+            S_IFREG
+        };
+        let size = u32::try_from(status.size).unwrap_or(u32::MAX);
+        let modified = status
+            .modified
+            .and_then(|time| time.duration_since(SystemTime::UNIX_EPOCH).ok())
+            .map_or(0, |duration| {
+                u32::try_from(duration.as_secs()).unwrap_or(u32::MAX)
+            });
+        self.write_stat(address, file_type | status.permissions, size, modified)
+    }
+
+    /// Writes a file status to the word-aligned buffer at `address`.
+    ///
+    /// The buffer holds three words: the mode (file type and permissions, as in `st_mode`), the
+    /// size in bytes (saturating at `0xffffffff`), and the time of the last modification in
+    /// seconds since the Unix epoch.
+    fn write_stat(
+        &mut self,
+        address: Address,
+        mode: u32,
+        size: u32,
+        modified: u32,
+    ) -> Result<(), Exception> {
+        self.memory.write_u32(address, mode, true)?;
+        self.memory.write_u32(address.wrapping_add(4), size, true)?;
+        self.memory
+            .write_u32(address.wrapping_add(8), modified, true)
+    }
+
+    /// Returns `result` in `$v0`, negating error numbers.
+    fn return_posix(&mut self, result: PosixResult) {
+        let value = result.unwrap_or_else(|errno| errno.wrapping_neg() as u32);
+        self.registers.write(CpuRegister::Val0, value);
+    }
+
+    /// Returns 0 in `$v0` if `result` succeeded, or the negated error number if it failed.
+    fn return_posix_unit(&mut self, result: io::Result<()>) {
+        self.return_posix(
+            result
+                .map(|()| 0)
+                .map_err(|error| errno::from_io_error(&error)),
+        );
+    }
+}
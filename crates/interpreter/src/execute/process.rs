@@ -0,0 +1,47 @@
+//! The services that inspect the process a program was started as.
+//!
+//! See [`startup`](crate::startup) for how the environment is laid out.
+
+use crate::{
+    Exception, InterpreterState, SyscallFailureKind, memory::Region, register_file::IndexByRegister,
+};
+use core::ffi::CStr;
+use seaside_constants::register::CpuRegister;
+use seaside_type_aliases::Address;
+
+impl InterpreterState {
+    /// Looks up the environment variable named by the nul-terminated string at `$a0`.
+    ///
+    /// Returns the address of its value, or 0 if it isn't set. Like C's `getenv`, this walks the
+    /// environment in memory, so changes the program made to it are seen.
+    pub fn getenv(&mut self) -> Result<(), Exception> {
+        let name_address: Address = self.registers.read(CpuRegister::Arg0);
+        let name = CStr::from_bytes_until_nul(self.memory.get_slice(name_address)?)
+            .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::NulNotFound))?
+            .to_bytes()
+            .to_vec();
+        let mut value_address: Address = 0;
+        if self.envp != 0 {
+            let mut entry_address = self.envp;
+            loop {
+                let var_address = self.memory.read_u32(entry_address, true)?;
+                if var_address == 0 {
+                    break;
+                }
+                let var = CStr::from_bytes_until_nul(self.memory.get_slice(var_address)?)
+                    .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::NulNotFound))?
+                    .to_bytes();
+                if var
+                    .strip_prefix(name.as_slice())
+                    .is_some_and(|rest| rest.starts_with(b"="))
+                {
+                    value_address = var_address + name.len() as u32 + 1;
+                    break;
+                }
+                entry_address += 4;
+            }
+        }
+        self.registers.write(CpuRegister::Val0, value_address);
+        Ok(())
+    }
+}
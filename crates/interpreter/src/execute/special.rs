@@ -1,5 +1,4 @@
 use crate::{Exception, Interpreter, InterpreterState, register_file::IndexByRegister};
-use num_traits::FromPrimitive;
 use seaside_constants::{fn_codes::SpecialFn, register::CpuRegister};
 use seaside_disassembler::fields;
 use seaside_int_utils::SignExtend;
@@ -12,7 +11,11 @@ impl Interpreter {
     /// 000000 xxxxx xxxxx xxxxx xxxxx xxxxxx
     /// opcode  $rs   $rt   $rd  shamt   fn
     /// ```
-    pub fn execute_special(&mut self, instruction: Instruction) -> Result<(), Exception> {
+    pub fn execute_special(
+        &mut self,
+        r#fn: SpecialFn,
+        instruction: Instruction,
+    ) -> Result<(), Exception> {
         use SpecialFn::*;
         let rs = fields::rs(instruction);
         let rt = fields::rt(instruction);
@@ -20,11 +23,11 @@ impl Interpreter {
         let rs_value: u32 = self.state.registers.read(rs);
         let rt_value: u32 = self.state.registers.read(rt);
         let shamt = fields::shamt(instruction);
-        let r#fn = match SpecialFn::from_u8(fields::r#fn(instruction)) {
-            Some(fn_code) => fn_code,
-            None => return Err(Exception::ReservedInstruction),
-        };
         match r#fn {
+            // MIPS32 Release 2 reuses the otherwise unused `$rs` and `shamt` fields of `srl` and
+            // `srlv` to encode rotations.
+            ShiftRightLogical if rs as u8 == 1 => self.state.rotr(rd, rt_value, shamt),
+            ShiftRightLogicalVariable if shamt == 1 => self.state.rotrv(rd, rs_value, rt_value),
             ShiftLeftLogical => self.state.sll(rd, rt_value, shamt),
             MoveConditional => self.state.movc(rt, rd, rs_value),
             ShiftRightLogical => self.state.srl(rd, rt_value, shamt),
@@ -90,6 +93,13 @@ impl InterpreterState {
         Ok(())
     }
 
+    /// Rotates `rt_value` right by `shamt` bits and stores the result in CPU register `rd`.
+    fn rotr(&mut self, rd: CpuRegister, rt_value: u32, shamt: u8) -> Result<(), Exception> {
+        self.registers
+            .write(rd, rt_value.rotate_right(shamt as u32));
+        Ok(())
+    }
+
     /// Shifts `rt_value` right by `shamt` bits (copying the most significant bit of `rt_value` to
     /// fill the space) and stores the result in CPU register `rd`.
     fn sra(&mut self, rd: CpuRegister, rt_value: u32, shamt: u8) -> Result<(), Exception> {
@@ -110,6 +120,12 @@ impl InterpreterState {
         Ok(())
     }
 
+    /// Rotates `rt_value` right by `rs_value` bits and stores the result in CPU register `rd`.
+    fn rotrv(&mut self, rd: CpuRegister, rs_value: u32, rt_value: u32) -> Result<(), Exception> {
+        self.registers.write(rd, rt_value.rotate_right(rs_value));
+        Ok(())
+    }
+
     /// Shifts `rt_value` right by `rs_value` bits (copying the most significant bit of `rt_value`
     /// to fill the space) and stores the result in CPU register `rd`.
     fn srav(&mut self, rd: CpuRegister, rs_value: u32, rt_value: u32) -> Result<(), Exception> {
@@ -1,5 +1,4 @@
 use crate::{Exception, Interpreter, InterpreterState, register_file::IndexByRegister};
-use num_traits::FromPrimitive;
 use seaside_constants::{fn_codes::Special2Fn, register::CpuRegister};
 use seaside_disassembler::fields;
 use seaside_int_utils::SignExtend;
@@ -12,17 +11,17 @@ impl Interpreter {
     /// 011100 xxxxx xxxxx xxxxx 00000 xxxxxx
     /// opcode  $rs   $rt   $rd   n/a    fn
     /// ```
-    pub fn execute_special_2(&mut self, instruction: Instruction) -> Result<(), Exception> {
+    pub fn execute_special_2(
+        &mut self,
+        r#fn: Special2Fn,
+        instruction: Instruction,
+    ) -> Result<(), Exception> {
         use Special2Fn::*;
         let rs = fields::rs(instruction);
         let rt = fields::rt(instruction);
         let rd = fields::rd(instruction);
         let rs_value: u32 = self.state.registers.read(rs);
         let rt_value: u32 = self.state.registers.read(rt);
-        let r#fn = match Special2Fn::from_u8(fields::r#fn(instruction)) {
-            Some(fn_code) => fn_code,
-            None => return Err(Exception::ReservedInstruction),
-        };
         match r#fn {
             MultiplyAdd => self.state.madd(rt_value, rs_value),
             MultiplyAddUnsigned => self.state.maddu(rt_value, rs_value),
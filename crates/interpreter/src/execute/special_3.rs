@@ -0,0 +1,128 @@
+use crate::{Exception, Interpreter, InterpreterState, register_file::IndexByRegister};
+use seaside_constants::{
+    fn_codes::{ByteShuffleFn, Special3Fn},
+    register::CpuRegister,
+};
+use seaside_disassembler::fields;
+use seaside_int_utils::SignExtend;
+use seaside_type_aliases::Instruction;
+
+impl Interpreter {
+    /// Executes `instruction`, which must follow the "special 3" instruction format:
+    ///
+    /// ```text
+    /// 011111 xxxxx xxxxx xxxxx xxxxx xxxxxx
+    /// opcode  $rs   $rt   msb   lsb    fn
+    /// ```
+    ///
+    /// The byte shuffle instructions (`seb`, `seh` and `wsbh`) share a single function code, and
+    /// are told apart by the `lsb` field instead. They're executed by
+    /// [`execute_byte_shuffle`](Self::execute_byte_shuffle).
+    pub fn execute_special_3(
+        &mut self,
+        r#fn: Special3Fn,
+        instruction: Instruction,
+    ) -> Result<(), Exception> {
+        use Special3Fn::*;
+        let rs = fields::rs(instruction);
+        let rt = fields::rt(instruction);
+        let msb = fields::rd_raw(instruction);
+        let lsb = fields::shamt(instruction);
+        let rs_value: u32 = self.state.registers.read(rs);
+        let rt_value: u32 = self.state.registers.read(rt);
+        match r#fn {
+            Extract => self.state.ext(rt, rs_value, msb, lsb),
+            Insert => self.state.ins(rt, rs_value, rt_value, msb, lsb),
+            ByteShuffle => Err(Exception::InterpreterFailure),
+        }
+    }
+
+    /// Executes one of the byte shuffle instructions, whose source is `$rt` and whose destination
+    /// is `$rd`, which occupies the same bits as `msb`.
+    pub fn execute_byte_shuffle(
+        &mut self,
+        r#fn: ByteShuffleFn,
+        instruction: Instruction,
+    ) -> Result<(), Exception> {
+        use ByteShuffleFn::*;
+        let rd = fields::rd(instruction);
+        let rt_value: u32 = self.state.registers.read(fields::rt(instruction));
+        match r#fn {
+            WordSwapBytesHalf => self.state.wsbh(rd, rt_value),
+            SignExtendByte => self.state.seb(rd, rt_value),
+            SignExtendHalf => self.state.seh(rd, rt_value),
+        }
+    }
+}
+
+impl InterpreterState {
+    /// Extracts the `size_minus_1 + 1` bits of `rs_value` starting at bit `pos`, storing them in
+    /// the least significant bits of CPU register `rt` and zeroing the rest.
+    ///
+    /// # Exceptions
+    ///
+    /// Raises a [reserved instruction][Exception::ReservedInstruction] exception if the bit field
+    /// doesn't fit within a word.
+    fn ext(
+        &mut self,
+        rt: CpuRegister,
+        rs_value: u32,
+        size_minus_1: u8,
+        pos: u8,
+    ) -> Result<(), Exception> {
+        if pos + size_minus_1 >= 32 {
+            return Err(Exception::ReservedInstruction);
+        }
+        let mask = u32::MAX >> (31 - size_minus_1);
+        self.registers.write(rt, (rs_value >> pos) & mask);
+        Ok(())
+    }
+
+    /// Replaces bits `lsb` through `msb` of `rt_value` with the least significant bits of
+    /// `rs_value`, storing the result in CPU register `rt`.
+    ///
+    /// # Exceptions
+    ///
+    /// Raises a [reserved instruction][Exception::ReservedInstruction] exception if `msb` is less
+    /// than `lsb`.
+    fn ins(
+        &mut self,
+        rt: CpuRegister,
+        rs_value: u32,
+        rt_value: u32,
+        msb: u8,
+        lsb: u8,
+    ) -> Result<(), Exception> {
+        if msb < lsb {
+            return Err(Exception::ReservedInstruction);
+        }
+        let mask = (u32::MAX >> (31 - (msb - lsb))) << lsb;
+        self.registers
+            .write(rt, (rt_value & !mask) | ((rs_value << lsb) & mask));
+        Ok(())
+    }
+
+    /// Swaps the bytes within each halfword of `rt_value`, storing the result in CPU register
+    /// `rd`.
+    fn wsbh(&mut self, rd: CpuRegister, rt_value: u32) -> Result<(), Exception> {
+        let swapped = ((rt_value & 0x00ff00ff) << 8) | ((rt_value >> 8) & 0x00ff00ff);
+        self.registers.write(rd, swapped);
+        Ok(())
+    }
+
+    /// Sign-extends the least significant byte of `rt_value`, storing the result in CPU register
+    /// `rd`.
+    fn seb(&mut self, rd: CpuRegister, rt_value: u32) -> Result<(), Exception> {
+        let value: i32 = (rt_value as u8).sign_extend();
+        self.registers.write(rd, value);
+        Ok(())
+    }
+
+    /// Sign-extends the least significant halfword of `rt_value`, storing the result in CPU
+    /// register `rd`.
+    fn seh(&mut self, rd: CpuRegister, rt_value: u32) -> Result<(), Exception> {
+        let value: i32 = (rt_value as u16).sign_extend();
+        self.registers.write(rd, value);
+        Ok(())
+    }
+}
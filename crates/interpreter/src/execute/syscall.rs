@@ -1,15 +1,18 @@
 use crate::{
     Exception, Interpreter, InterpreterState, SyscallFailureKind,
+    dialog::{InputStatus, MessageKind},
     memory::Region,
     register_file::{IndexByRegister, TryIndexByRegister},
+    spim,
 };
 use console::Term;
-use core::{ffi::CStr, time::Duration};
+use core::{ffi::CStr, str::FromStr, time::Duration};
+use minimal_logging::macros::grayln;
 use seaside_constants::register::{CpuRegister, FpuRegister};
 use seaside_type_aliases::Address;
 use std::{
-    fs::OpenOptions,
-    io::{Read, Write, stdin},
+    io::{Read, Write, stdin, stdout},
+    path::Path,
     thread::sleep,
     time::SystemTime,
 };
@@ -17,10 +20,60 @@ use std::{
 impl Interpreter {
     pub fn syscall(&mut self) -> Result<(), Exception> {
         let service_code: u32 = self.state.registers.read(CpuRegister::Val0);
-        let service = self.services.get(&service_code).ok_or_else(|| {
-            Exception::SyscallFailure(SyscallFailureKind::UnknownServiceCode(service_code))
-        })?;
-        service(&mut self.state)
+        if let Some(service) = self.custom_services.get_mut(&service_code) {
+            let result = service.call(&mut self.state);
+            if self.strace {
+                trace_opaque_service(&mut self.state, service.name(), &result);
+            }
+            return result;
+        }
+        #[cfg(feature = "lua")]
+        if let Some(scripts) = self
+            .scripts
+            .as_ref()
+            .filter(|scripts| scripts.contains(service_code))
+        {
+            let result = scripts.call(service_code, &mut self.state);
+            if self.strace {
+                let name = scripts.name(service_code).unwrap_or_default();
+                trace_opaque_service(&mut self.state, &format!("script.{name}"), &result);
+            }
+            return result;
+        }
+        let Some(&(service, service_fn)) = self.services.get(&service_code) else {
+            let exception =
+                Exception::SyscallFailure(SyscallFailureKind::UnknownServiceCode(service_code));
+            if self.strace {
+                grayln!("[strace] <unknown>(code: {service_code}) -> failed: {exception}");
+            }
+            return Err(exception);
+        };
+        if !self.strace {
+            return service_fn(&mut self.state);
+        }
+
+        let args = self.state.describe_service_args(service);
+        let result = service_fn(&mut self.state);
+        // Anything the service printed should show up before its trace.
+        let _ = self.state.flush_stdout_if_necessary();
+        match result {
+            Ok(()) => match self.state.describe_service_returns(service) {
+                Some(returns) => grayln!("[strace] {service}({args}) -> {returns}"),
+                None => grayln!("[strace] {service}({args})"),
+            },
+            Err(exception) => grayln!("[strace] {service}({args}) -> failed: {exception}"),
+        }
+        result
+    }
+}
+
+/// Logs a call to a service whose arguments and return values seaside doesn't know.
+fn trace_opaque_service(state: &mut InterpreterState, name: &str, result: &Result<(), Exception>) {
+    // Anything the service printed should show up before its trace.
+    let _ = state.flush_stdout_if_necessary();
+    match result {
+        Ok(()) => grayln!("[strace] {name}()"),
+        Err(exception) => grayln!("[strace] {name}() -> failed: {exception}"),
     }
 }
 
@@ -34,7 +87,12 @@ impl InterpreterState {
 
     pub fn print_float(&mut self) -> Result<(), Exception> {
         let x: f32 = self.registers.read(FpuRegister::F12);
-        print!("{x}");
+        if self.spim_compatible {
+            // SPIM prints floats with `printf`'s "%.8f".
+            print!("{x:.8}");
+        } else {
+            print!("{x}");
+        }
         self.stdout_pending_flush = true;
         Ok(())
     }
@@ -49,10 +107,18 @@ impl InterpreterState {
     pub fn print_string(&mut self) -> Result<(), Exception> {
         let buffer_address: Address = self.registers.read(CpuRegister::Arg0);
         let string = CStr::from_bytes_until_nul(self.memory.get_slice(buffer_address)?)
-            .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::NulNotFound))?
-            .to_str()
-            .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::InvalidUtf8))?;
-        print!("{string}");
+            .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::NulNotFound))?;
+        if self.spim_compatible {
+            // SPIM prints the bytes as they are, whether or not they're valid UTF-8.
+            stdout()
+                .write_all(string.to_bytes())
+                .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::WriteFailed))?;
+        } else {
+            let string = string
+                .to_str()
+                .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::InvalidUtf8))?;
+            print!("{string}");
+        }
         self.stdout_pending_flush = true;
         Ok(())
     }
@@ -64,10 +130,14 @@ impl InterpreterState {
         stdin()
             .read_line(&mut buffer)
             .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::StdinReadFailed))?;
-        let parsed: i32 = buffer
-            .trim()
-            .parse()
-            .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::ParseError))?;
+        let parsed: i32 = if self.spim_compatible {
+            spim::parse_int(&buffer)
+        } else {
+            buffer
+                .trim()
+                .parse()
+                .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::ParseError))?
+        };
         self.registers.write(CpuRegister::Val0, parsed);
         Ok(())
     }
@@ -79,10 +149,14 @@ impl InterpreterState {
         stdin()
             .read_line(&mut buffer)
             .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::StdinReadFailed))?;
-        let parsed: f32 = buffer
-            .trim()
-            .parse()
-            .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::ParseError))?;
+        let parsed: f32 = if self.spim_compatible {
+            spim::parse_float(&buffer) as f32
+        } else {
+            buffer
+                .trim()
+                .parse()
+                .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::ParseError))?
+        };
         self.registers.write(FpuRegister::F0, parsed);
         Ok(())
     }
@@ -94,10 +168,14 @@ impl InterpreterState {
         stdin()
             .read_line(&mut buffer)
             .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::StdinReadFailed))?;
-        let parsed: f64 = buffer
-            .trim()
-            .parse()
-            .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::ParseError))?;
+        let parsed: f64 = if self.spim_compatible {
+            spim::parse_float(&buffer)
+        } else {
+            buffer
+                .trim()
+                .parse()
+                .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::ParseError))?
+        };
         self.registers.try_write(FpuRegister::F0, parsed)
     }
 
@@ -107,11 +185,12 @@ impl InterpreterState {
         self.flush_stdout_if_necessary()
             .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::StdoutFlushFailed))?;
         let buffer_address: Address = self.registers.read(CpuRegister::Arg0);
+        let requested_bytes =
+            <_ as IndexByRegister<_, u32>>::read(&self.registers, CpuRegister::Arg1) as usize;
+        self.memory
+            .prepare_slice_write(buffer_address, requested_bytes);
         let buffer = self.memory.get_slice_mut(buffer_address)?;
-        let max_bytes = usize::min(
-            <_ as IndexByRegister<_, u32>>::read(&self.registers, CpuRegister::Arg1) as usize,
-            buffer.len(),
-        );
+        let max_bytes = usize::min(requested_bytes, buffer.len());
         if max_bytes == 0 {
             return Ok(());
         }
@@ -151,18 +230,22 @@ impl InterpreterState {
         }
 
         let address = if should_allocate {
-            let free_heap_space = self.memory.free_heap_space_mut();
-            if let Some(new_free_space) = free_heap_space.checked_sub(n_bytes) {
-                *free_heap_space = new_free_space;
-                let next_available = self.memory.next_heap_address_mut();
-                let address = *next_available;
-                *next_available += n_bytes;
-                address
-            } else {
-                0
+            match self.grow_heap(n_bytes) {
+                Some(address) => {
+                    if let Some(memcheck) = self.memory.memcheck_mut() {
+                        memcheck.allocate(address, n_bytes);
+                    }
+                    address
+                }
+                None => 0,
             }
         } else if freeable_heap_allocations {
             n_bytes = n_bytes.min(self.memory.used_heap_space());
+            // Memory handed to the heap allocator stays put, so the break can't move below it.
+            if let Some(high_water_mark) = self.heap_allocator.high_water_mark() {
+                let above = self.memory.next_heap_address() - high_water_mark;
+                n_bytes = n_bytes.min(above);
+            }
             *self.memory.free_heap_space_mut() += n_bytes;
             *self.memory.next_heap_address_mut() -= n_bytes;
             0
@@ -176,12 +259,32 @@ impl InterpreterState {
         Ok(())
     }
 
+    /// Moves the heap break up by `n_bytes`, returning the old break, or [`None`] if the heap
+    /// doesn't have that much room left.
+    pub(crate) fn grow_heap(&mut self, n_bytes: u32) -> Option<Address> {
+        let free_heap_space = self.memory.free_heap_space_mut();
+        *free_heap_space = free_heap_space.checked_sub(n_bytes)?;
+        let next_available = self.memory.next_heap_address_mut();
+        let address = *next_available;
+        *next_available += n_bytes;
+        Some(address)
+    }
+
     pub fn exit(&mut self) -> Result<(), Exception> {
         self.exit_code = Some(0);
         Ok(())
     }
 
     pub fn print_char(&mut self) -> Result<(), Exception> {
+        if self.spim_compatible {
+            // SPIM prints the low byte of `$a0` as is, like `putc`.
+            let c: u32 = self.registers.read(CpuRegister::Arg0);
+            stdout()
+                .write_all(&[c as u8])
+                .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::WriteFailed))?;
+            self.stdout_pending_flush = true;
+            return Ok(());
+        }
         let c = char::from_u32(self.registers.read(CpuRegister::Arg0))
             .ok_or(Exception::SyscallFailure(SyscallFailureKind::InvalidUtf8))?;
         print!("{c}");
@@ -212,16 +315,11 @@ impl InterpreterState {
         let flags: u32 = self.registers.read(CpuRegister::Arg1);
         // The `mode` parameter is currently ignored by both MARS and seaside.
         let _mode: u32 = self.registers.read(CpuRegister::Arg2);
-        let fd: u32 = match OpenOptions::new()
-            .read(flags == 0)
-            .write(flags & 1 != 0)
-            .create(flags & 1 != 0)
-            .append(flags & 8 != 0)
-            .open(file_name)
-        {
-            Ok(file) => {
+        let now = self.now();
+        let fd: u32 = match self.sandbox.open(Path::new(file_name), flags, now) {
+            Ok(handle) => {
                 let fd = self.next_fd;
-                self.make_file_handle(file);
+                self.make_file_handle(handle);
                 fd
             }
             Err(_) => u32::MAX,
@@ -233,11 +331,12 @@ impl InterpreterState {
     pub fn read_file(&mut self) -> Result<(), Exception> {
         let fd: u32 = self.registers.read(CpuRegister::Arg0);
         let buffer_address: Address = self.registers.read(CpuRegister::Arg1);
+        let requested_bytes =
+            <_ as IndexByRegister<_, u32>>::read(&self.registers, CpuRegister::Arg2) as usize;
+        self.memory
+            .prepare_slice_write(buffer_address, requested_bytes);
         let buffer = self.memory.get_slice_mut(buffer_address)?;
-        let max_bytes = usize::min(
-            <_ as IndexByRegister<_, u32>>::read(&self.registers, CpuRegister::Arg2) as usize,
-            buffer.len(),
-        );
+        let max_bytes = usize::min(requested_bytes, buffer.len());
         if max_bytes == 0 {
             return Ok(());
         }
@@ -259,8 +358,12 @@ impl InterpreterState {
             buffer.len(),
         );
         let buffer = &buffer[..max_bytes];
+        let now = self.now();
         let bytes_written = match self.files.get_mut(&fd) {
-            Some(handle) => handle.write(buffer).map_or(u32::MAX, |n| n as u32),
+            Some(handle) => handle.write(buffer).map_or(u32::MAX, |n| {
+                handle.touch(now);
+                n as u32
+            }),
             None => u32::MAX,
         };
         self.registers.write(CpuRegister::Val0, bytes_written);
@@ -283,9 +386,10 @@ impl InterpreterState {
     }
 
     pub fn time(&mut self) -> Result<(), Exception> {
-        let system_time: u64 = match SystemTime::UNIX_EPOCH.elapsed() {
-            Ok(duration) => duration.as_millis() as u64,
-            Err(_) => {
+        let system_time: u64 = match (&self.clock, SystemTime::UNIX_EPOCH.elapsed()) {
+            (Some(clock), _) => clock.millis(),
+            (None, Ok(duration)) => duration.as_millis() as u64,
+            (None, Err(_)) => {
                 return Err(Exception::SyscallFailure(
                     SyscallFailureKind::BeforeUnixEpoch,
                 ));
@@ -298,28 +402,38 @@ impl InterpreterState {
         Ok(())
     }
 
-    #[allow(dead_code, reason = "not yet implemented")]
     pub fn midi_out(&mut self) -> Result<(), Exception> {
-        let _pitch: u32 = self.registers.read(CpuRegister::Arg0);
-        let _millis: u32 = self.registers.read(CpuRegister::Arg1);
-        let _instrument: u32 = self.registers.read(CpuRegister::Arg2);
-        let _volume: u32 = self.registers.read(CpuRegister::Arg3);
-        todo!("generate a sound");
+        let pitch: u32 = self.registers.read(CpuRegister::Arg0);
+        let millis: i32 = self.registers.read(CpuRegister::Arg1);
+        let instrument: u32 = self.registers.read(CpuRegister::Arg2);
+        let volume: u32 = self.registers.read(CpuRegister::Arg3);
+        self.midi.play(pitch, millis, instrument, volume);
+        Ok(())
     }
 
     pub fn sleep(&mut self) -> Result<(), Exception> {
         let millis: u32 = self.registers.read(CpuRegister::Arg0);
-        sleep(Duration::from_millis(millis as u64));
+        match &mut self.clock {
+            Some(clock) => clock.sleep(millis),
+            None => sleep(Duration::from_millis(millis as u64)),
+        }
+        self.midi.wait(millis);
         Ok(())
     }
 
-    #[allow(dead_code, reason = "not yet implemented")]
     pub fn midi_out_sync(&mut self) -> Result<(), Exception> {
-        let _pitch: u32 = self.registers.read(CpuRegister::Arg0);
-        let _millis: u32 = self.registers.read(CpuRegister::Arg1);
-        let _instrument: u32 = self.registers.read(CpuRegister::Arg2);
-        let _volume: u32 = self.registers.read(CpuRegister::Arg3);
-        todo!("generate a sound");
+        let pitch: u32 = self.registers.read(CpuRegister::Arg0);
+        let millis: i32 = self.registers.read(CpuRegister::Arg1);
+        let instrument: u32 = self.registers.read(CpuRegister::Arg2);
+        let volume: u32 = self.registers.read(CpuRegister::Arg3);
+        // The note is recorded rather than played, so there's nothing to actually wait for. The
+        // virtual clock still has to wait, since the program can see the time.
+        self.midi.play_sync(pitch, millis, instrument, volume);
+        if let Some(clock) = &mut self.clock {
+            let duration = self.midi.notes().last().map_or(0, |note| note.duration);
+            clock.sleep(duration);
+        }
+        Ok(())
     }
 
     pub fn print_hex(&mut self) -> Result<(), Exception> {
@@ -401,112 +515,141 @@ impl InterpreterState {
         self.registers.try_write(FpuRegister::F0, x)
     }
 
-    #[allow(dead_code, reason = "not yet implemented")]
     pub fn confirm_dialog(&mut self) -> Result<(), Exception> {
-        let buffer_address: Address = self.registers.read(CpuRegister::Arg0);
-        let _message = CStr::from_bytes_until_nul(self.memory.get_slice(buffer_address)?)
-            .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::NulNotFound))?
-            .to_str()
-            .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::InvalidUtf8))?;
-        todo!("show the dialog and return the result");
+        let message = self.dialog_message(CpuRegister::Arg0)?;
+        let confirmation = self
+            .dialogs
+            .confirm(&message)
+            .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::StdinReadFailed))?;
+        self.registers.write(CpuRegister::Arg0, confirmation as u32);
+        Ok(())
     }
 
-    #[allow(dead_code, reason = "not yet implemented")]
     pub fn input_dialog_int(&mut self) -> Result<(), Exception> {
-        let buffer_address: Address = self.registers.read(CpuRegister::Arg0);
-        let _message = CStr::from_bytes_until_nul(self.memory.get_slice(buffer_address)?)
-            .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::NulNotFound))?
-            .to_str()
-            .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::InvalidUtf8))?;
-        todo!("show the dialog and return the result");
+        let (x, status) = self.input_dialog_value::<i32>()?;
+        self.registers.write(CpuRegister::Arg0, x);
+        self.registers.write(CpuRegister::Arg1, status as i32);
+        Ok(())
     }
 
-    #[allow(dead_code, reason = "not yet implemented")]
     pub fn input_dialog_float(&mut self) -> Result<(), Exception> {
-        let buffer_address: Address = self.registers.read(CpuRegister::Arg0);
-        let _message = CStr::from_bytes_until_nul(self.memory.get_slice(buffer_address)?)
-            .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::NulNotFound))?
-            .to_str()
-            .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::InvalidUtf8))?;
-        todo!("show the dialog and return the result");
+        let (x, status) = self.input_dialog_value::<f32>()?;
+        self.registers.write(FpuRegister::F0, x);
+        self.registers.write(CpuRegister::Arg1, status as i32);
+        Ok(())
     }
 
-    #[allow(dead_code, reason = "not yet implemented")]
     pub fn input_dialog_double(&mut self) -> Result<(), Exception> {
-        let buffer_address: Address = self.registers.read(CpuRegister::Arg0);
-        let _message = CStr::from_bytes_until_nul(self.memory.get_slice(buffer_address)?)
-            .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::NulNotFound))?
-            .to_str()
-            .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::InvalidUtf8))?;
-        todo!("show the dialog and return the result");
+        let (x, status) = self.input_dialog_value::<f64>()?;
+        self.registers.try_write(FpuRegister::F0, x)?;
+        self.registers.write(CpuRegister::Arg1, status as i32);
+        Ok(())
     }
 
-    #[allow(dead_code, reason = "not yet implemented")]
     pub fn input_dialog_string(&mut self) -> Result<(), Exception> {
-        let buffer_address: Address = self.registers.read(CpuRegister::Arg0);
-        let _message = CStr::from_bytes_until_nul(self.memory.get_slice(buffer_address)?)
-            .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::NulNotFound))?
-            .to_str()
-            .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::InvalidUtf8))?;
-        todo!("show the dialog and return the result");
+        let message = self.dialog_message(CpuRegister::Arg0)?;
+        let buffer_address: Address = self.registers.read(CpuRegister::Arg1);
+        let requested_bytes =
+            <_ as IndexByRegister<_, u32>>::read(&self.registers, CpuRegister::Arg2) as usize;
+        let input = self
+            .dialogs
+            .input(&message)
+            .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::StdinReadFailed))?;
+        let status = match input {
+            None => InputStatus::Cancelled,
+            Some(input) if input.is_empty() => InputStatus::NoData,
+            Some(input) => {
+                self.memory
+                    .prepare_slice_write(buffer_address, requested_bytes);
+                let buffer = self.memory.get_slice_mut(buffer_address)?;
+                let max_bytes = usize::min(requested_bytes, buffer.len());
+                // Like MARS, leave room for the nul byte and cut off whatever doesn't fit.
+                let len = usize::min(input.len(), max_bytes.saturating_sub(1));
+                buffer[..len].copy_from_slice(&input.as_bytes()[..len]);
+                if let Some(nul) = buffer[..max_bytes].get_mut(len) {
+                    *nul = b'\0';
+                }
+                if len < input.len() {
+                    InputStatus::TooLong
+                } else {
+                    InputStatus::Ok
+                }
+            }
+        };
+        self.registers.write(CpuRegister::Arg1, status as i32);
+        Ok(())
     }
 
-    #[allow(dead_code, reason = "not yet implemented")]
     pub fn message_dialog(&mut self) -> Result<(), Exception> {
-        let buffer_address: Address = self.registers.read(CpuRegister::Arg0);
-        let _message = CStr::from_bytes_until_nul(self.memory.get_slice(buffer_address)?)
-            .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::NulNotFound))?
-            .to_str()
-            .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::InvalidUtf8))?;
-        let _message_type: u32 = self.registers.read(CpuRegister::Arg1);
-        todo!("show the dialog");
+        let message = self.dialog_message(CpuRegister::Arg0)?;
+        let kind: u32 = self.registers.read(CpuRegister::Arg1);
+        self.show_message(MessageKind::from(kind), &message)
     }
 
-    #[allow(dead_code, reason = "not yet implemented")]
     pub fn message_dialog_int(&mut self) -> Result<(), Exception> {
-        let buffer_address: Address = self.registers.read(CpuRegister::Arg0);
-        let _message = CStr::from_bytes_until_nul(self.memory.get_slice(buffer_address)?)
-            .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::NulNotFound))?
-            .to_str()
-            .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::InvalidUtf8))?;
-        let _x: u32 = self.registers.read(CpuRegister::Arg1);
-        todo!("show the dialog");
+        let message = self.dialog_message(CpuRegister::Arg0)?;
+        let x: i32 = self.registers.read(CpuRegister::Arg1);
+        self.show_message(MessageKind::Info, &format!("{message}{x}"))
     }
 
-    #[allow(dead_code, reason = "not yet implemented")]
     pub fn message_dialog_float(&mut self) -> Result<(), Exception> {
-        let buffer_address: Address = self.registers.read(CpuRegister::Arg0);
-        let _message = CStr::from_bytes_until_nul(self.memory.get_slice(buffer_address)?)
-            .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::NulNotFound))?
-            .to_str()
-            .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::InvalidUtf8))?;
-        let _x: f32 = self.registers.read(FpuRegister::F12);
-        todo!("show the dialog");
+        let message = self.dialog_message(CpuRegister::Arg0)?;
+        let x: f32 = self.registers.read(FpuRegister::F12);
+        self.show_message(MessageKind::Info, &format!("{message}{x}"))
     }
 
-    #[allow(dead_code, reason = "not yet implemented")]
     pub fn message_dialog_double(&mut self) -> Result<(), Exception> {
-        let buffer_address: Address = self.registers.read(CpuRegister::Arg0);
-        let _message = CStr::from_bytes_until_nul(self.memory.get_slice(buffer_address)?)
-            .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::NulNotFound))?
-            .to_str()
-            .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::InvalidUtf8))?;
-        let _x: f64 = self.registers.try_read(FpuRegister::F12)?;
-        todo!("show the dialog");
+        let message = self.dialog_message(CpuRegister::Arg0)?;
+        let x: f64 = self.registers.try_read(FpuRegister::F12)?;
+        self.show_message(MessageKind::Info, &format!("{message}{x}"))
     }
 
-    #[allow(dead_code, reason = "not yet implemented")]
     pub fn message_dialog_string(&mut self) -> Result<(), Exception> {
-        let buffer_address_0: Address = self.registers.read(CpuRegister::Arg0);
-        let _message_0 = CStr::from_bytes_until_nul(self.memory.get_slice(buffer_address_0)?)
-            .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::NulNotFound))?
-            .to_str()
-            .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::InvalidUtf8))?;
-        let buffer_address_1: Address = self.registers.read(CpuRegister::Arg1);
-        let _message_1 = CStr::from_bytes_until_nul(self.memory.get_slice(buffer_address_1)?)
+        let message_0 = self.dialog_message(CpuRegister::Arg0)?;
+        let message_1 = self.dialog_message(CpuRegister::Arg1)?;
+        self.show_message(MessageKind::Info, &format!("{message_0}{message_1}"))
+    }
+
+    /// Reads the nul-terminated string pointed to by `register` and flushes stdout so it can be
+    /// shown in a dialog.
+    fn dialog_message(&mut self, register: CpuRegister) -> Result<String, Exception> {
+        let buffer_address: Address = self.registers.read(register);
+        let message = CStr::from_bytes_until_nul(self.memory.get_slice(buffer_address)?)
             .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::NulNotFound))?
             .to_str()
-            .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::InvalidUtf8))?;
-        todo!("show the dialog");
+            .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::InvalidUtf8))?
+            .to_string();
+        self.flush_stdout_if_necessary()
+            .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::StdoutFlushFailed))?;
+        Ok(message)
+    }
+
+    /// Shows the input dialog whose message `$a0` points to, then parses the input as a `T`.
+    ///
+    /// If the input is missing or can't be parsed, the default value of `T` is returned alongside
+    /// the status explaining why.
+    fn input_dialog_value<T>(&mut self) -> Result<(T, InputStatus), Exception>
+    where
+        T: Default + FromStr,
+    {
+        let message = self.dialog_message(CpuRegister::Arg0)?;
+        let input = self
+            .dialogs
+            .input(&message)
+            .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::StdinReadFailed))?;
+        Ok(match input.as_deref().map(str::trim) {
+            None => (T::default(), InputStatus::Cancelled),
+            Some("") => (T::default(), InputStatus::NoData),
+            Some(input) => match input.parse() {
+                Ok(x) => (x, InputStatus::Ok),
+                Err(_) => (T::default(), InputStatus::ParseError),
+            },
+        })
+    }
+
+    fn show_message(&mut self, kind: MessageKind, message: &str) -> Result<(), Exception> {
+        self.dialogs
+            .show(kind, message)
+            .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::WriteFailed))
     }
 }
@@ -1,20 +1,70 @@
+use crate::sandbox::{MAX_VIRTUAL_FILE_SIZE, VirtualFile};
 use std::{
-    fs::File,
-    io::{Error, ErrorKind, Read, Result, Stderr, Stdin, Stdout, Write, stderr, stdin, stdout},
+    cell::RefCell,
+    collections::VecDeque,
+    fs::{File, Metadata, OpenOptions, read_dir},
+    io::{
+        Error, ErrorKind, Read, Result, Seek, SeekFrom, Stderr, Stdin, Stdout, Write, stderr,
+        stdin, stdout,
+    },
+    path::{PathBuf, absolute},
+    rc::Rc,
+    time::SystemTime,
 };
 
+/// The bits of the open flags that select the access mode.
+pub const O_ACCMODE: u32 = 0x0003;
+/// Opens the file for writing only.
+pub const O_WRONLY: u32 = 0x0001;
+/// Makes writes append to the end of the file.
+pub const O_APPEND: u32 = 0x0008;
+/// Creates the file if it doesn't exist.
+pub const O_CREAT: u32 = 0x0100;
+/// Truncates the file to zero length.
+pub const O_TRUNC: u32 = 0x0200;
+/// Fails if [`O_CREAT`] is set and the file already exists.
+pub const O_EXCL: u32 = 0x0400;
+
 pub enum FileHandle {
     Stdin(Stdin),
     Stdout(Stdout),
     Stderr(Stderr),
-    File(File),
+    File {
+        file: File,
+        /// The absolute path the file was opened from.
+        path: PathBuf,
+        /// The flags the file was opened with.
+        flags: u32,
+    },
+    Directory {
+        /// The names of the entries that haven't been read yet.
+        entries: VecDeque<String>,
+        /// The absolute path of the directory.
+        path: PathBuf,
+    },
+    /// A file in the sandbox's virtual file system.
+    Virtual {
+        file: Rc<RefCell<VirtualFile>>,
+        /// The path of the file within the virtual file system.
+        path: PathBuf,
+        /// How the file was opened.
+        mode: OpenMode,
+        offset: u64,
+    },
 }
 
 impl Read for FileHandle {
     fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
         match self {
             Self::Stdin(stdin) => stdin.read(buf),
-            Self::File(file) => file.read(buf),
+            Self::File { file, .. } => file.read(buf),
+            Self::Virtual {
+                file, mode, offset, ..
+            } if mode.read => {
+                let n = file.borrow().read_at(*offset, buf);
+                *offset += n as u64;
+                Ok(n)
+            }
             _ => Err(Error::from(ErrorKind::PermissionDenied)),
         }
     }
@@ -25,7 +75,18 @@ impl Write for FileHandle {
         match self {
             Self::Stdout(stdout) => stdout.write(buf),
             Self::Stderr(stderr) => stderr.write(buf),
-            Self::File(file) => file.write(buf),
+            Self::File { file, .. } => file.write(buf),
+            Self::Virtual {
+                file, mode, offset, ..
+            } if mode.write => {
+                let mut file = file.borrow_mut();
+                if mode.append {
+                    *offset = file.len();
+                }
+                file.write_at(*offset, buf)?;
+                *offset += buf.len() as u64;
+                Ok(buf.len())
+            }
             _ => Err(Error::from(ErrorKind::PermissionDenied)),
         }
     }
@@ -34,13 +95,123 @@ impl Write for FileHandle {
         match self {
             Self::Stdout(stdout) => stdout.flush(),
             Self::Stderr(stderr) => stderr.flush(),
-            Self::File(file) => file.flush(),
+            Self::File { file, .. } => file.flush(),
+            Self::Virtual { .. } => Ok(()),
             _ => Err(Error::from(ErrorKind::PermissionDenied)),
         }
     }
 }
 
 impl FileHandle {
+    /// Opens the file at `path` using MARS-style `flags`.
+    ///
+    /// See [`OpenMode::from_mars_flags`] for what the flags mean.
+    pub fn open(path: PathBuf, flags: u32) -> Result<Self> {
+        let file = OpenMode::from_mars_flags(flags).options().open(&path)?;
+        Ok(Self::File {
+            file,
+            path: absolute(path)?,
+            flags,
+        })
+    }
+
+    /// Opens the file at `path` using POSIX-style `flags`, creating it with the permissions in
+    /// `mode` if necessary.
+    ///
+    /// See [`OpenMode::from_posix_flags`] for what the flags mean. Only the access mode and
+    /// [`O_APPEND`] are remembered, since they're all it takes to reopen the file.
+    pub fn open_posix(path: PathBuf, flags: u32, mode: u32) -> Result<Self> {
+        let mut options = OpenMode::from_posix_flags(flags).options();
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, mode & 0o7777);
+        #[cfg(not(unix))]
+        let _ = mode;
+        let file = options.open(&path)?;
+        Ok(Self::File {
+            file,
+            path: absolute(path)?,
+            flags: flags & (O_ACCMODE | O_APPEND),
+        })
+    }
+
+    /// Opens the directory at `path` so its entries can be read one by one, in alphabetical
+    /// order.
+    pub fn open_directory(path: PathBuf) -> Result<Self> {
+        let mut entries = read_dir(&path)?
+            .map(|entry| Ok(entry?.file_name().to_string_lossy().into_owned()))
+            .collect::<Result<Vec<String>>>()?;
+        entries.sort();
+        Ok(Self::Directory {
+            entries: entries.into(),
+            path: absolute(path)?,
+        })
+    }
+
+    /// Moves the offset of a file, returning the new offset.
+    pub fn seek(&mut self, position: SeekFrom) -> Result<u64> {
+        match self {
+            Self::File { file, .. } => file.seek(position),
+            Self::Virtual { file, offset, .. } => {
+                let new_offset = match position {
+                    SeekFrom::Start(start) => Some(start),
+                    SeekFrom::Current(delta) => offset.checked_add_signed(delta),
+                    SeekFrom::End(delta) => file.borrow().len().checked_add_signed(delta),
+                };
+                *offset = new_offset
+                    .filter(|&new_offset| new_offset <= MAX_VIRTUAL_FILE_SIZE)
+                    .ok_or_else(|| Error::from(ErrorKind::InvalidInput))?;
+                Ok(*offset)
+            }
+            Self::Directory { .. } => Err(Error::from(ErrorKind::IsADirectory)),
+            _ => Err(Error::from(ErrorKind::NotSeekable)),
+        }
+    }
+
+    /// Gets the status of a file or directory, or [`None`] for the standard streams.
+    pub fn status(&self) -> Option<Result<FileStatus>> {
+        match self {
+            Self::File { file, .. } => Some(file.metadata().map(|metadata| (&metadata).into())),
+            Self::Directory { path, .. } => {
+                Some(path.metadata().map(|metadata| (&metadata).into()))
+            }
+            Self::Virtual { file, .. } => Some(Ok(file.borrow().status())),
+            _ => None,
+        }
+    }
+
+    /// Records that a virtual file was modified at `now`. Files on the host keep track of this
+    /// themselves.
+    pub(crate) fn touch(&mut self, now: SystemTime) {
+        if let Self::Virtual { file, .. } = self {
+            file.borrow_mut().touch(now);
+        }
+    }
+
+    /// Takes the name of the next entry of a directory if it's no longer than `max_len` bytes,
+    /// returning [`None`] once every entry has been read.
+    ///
+    /// Entries that are too long are left for the next call.
+    pub fn read_entry(&mut self, max_len: usize) -> Result<Option<String>> {
+        let Self::Directory { entries, .. } = self else {
+            return Err(Error::from(ErrorKind::NotADirectory));
+        };
+        match entries.front() {
+            Some(name) if name.len() > max_len => Err(Error::from(ErrorKind::InvalidFilename)),
+            _ => Ok(entries.pop_front()),
+        }
+    }
+
+    /// Describes what this handle refers to, such as "stdout" or the path of a file.
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Stdin(_) => "stdin".to_string(),
+            Self::Stdout(_) => "stdout".to_string(),
+            Self::Stderr(_) => "stderr".to_string(),
+            Self::File { path, .. } | Self::Directory { path, .. } => path.display().to_string(),
+            Self::Virtual { path, .. } => format!("{} (in memory)", path.display()),
+        }
+    }
+
     pub fn new_stdin() -> Self {
         Self::Stdin(stdin())
     }
@@ -53,3 +224,89 @@ impl FileHandle {
         Self::Stderr(stderr())
     }
 }
+
+/// How a file is opened, independent of how the program asked for it.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct OpenMode {
+    pub read: bool,
+    pub write: bool,
+    pub append: bool,
+    pub truncate: bool,
+    pub create: bool,
+    /// Fail if the file already exists.
+    pub create_new: bool,
+}
+
+impl OpenMode {
+    /// Interprets MARS-style open flags.
+    ///
+    /// The lowest two bits select the access mode: 0 opens the file for reading, 1 for writing
+    /// (creating it if necessary) and 2 for both. Bit 3 makes writes append to the end of the file.
+    pub fn from_mars_flags(flags: u32) -> Self {
+        let write = flags & O_ACCMODE != 0;
+        Self {
+            read: flags & O_ACCMODE != O_WRONLY,
+            write,
+            append: flags & O_APPEND != 0,
+            truncate: false,
+            create: write,
+            create_new: false,
+        }
+    }
+
+    /// Interprets POSIX-style open flags, using the values Linux has on MIPS, such as [`O_CREAT`]
+    /// and [`O_TRUNC`].
+    pub fn from_posix_flags(flags: u32) -> Self {
+        Self {
+            read: flags & O_ACCMODE != O_WRONLY,
+            write: flags & O_ACCMODE != 0,
+            append: flags & O_APPEND != 0,
+            truncate: flags & O_TRUNC != 0,
+            create: flags & O_CREAT != 0,
+            create_new: flags & (O_CREAT | O_EXCL) == O_CREAT | O_EXCL,
+        }
+    }
+
+    fn options(self) -> OpenOptions {
+        let mut options = OpenOptions::new();
+        options
+            .read(self.read)
+            .write(self.write)
+            .append(self.append)
+            .truncate(self.truncate)
+            .create(self.create)
+            .create_new(self.create_new);
+        options
+    }
+}
+
+/// The parts of a file's metadata that programs can see.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FileStatus {
+    pub is_directory: bool,
+    /// The size of the file in bytes.
+    pub size: u64,
+    /// The permission bits, as in the lower bits of `st_mode`.
+    pub permissions: u32,
+    /// When the file was last modified, if known.
+    pub modified: Option<SystemTime>,
+}
+
+impl From<&Metadata> for FileStatus {
+    fn from(metadata: &Metadata) -> Self {
+        #[cfg(unix)]
+        let permissions = std::os::unix::fs::PermissionsExt::mode(&metadata.permissions()) & 0o7777;
+        #[cfg(not(unix))]
+        let permissions = if metadata.permissions().readonly() {
+            0o444
+        } else {
+            0o644
+        };
+        Self {
+            is_directory: metadata.is_dir(),
+            size: metadata.len(),
+            permissions,
+            modified: metadata.modified().ok(),
+        }
+    }
+}
@@ -0,0 +1,630 @@
+//! The floating-point unit's control registers and IEEE 754 arithmetic.
+//!
+//! Provides the struct [`Fcsr`], which models the floating-point control and status register, as
+//! well as the arithmetic coprocessor 1 performs. The host always rounds to nearest, so every
+//! result is computed that way first, then moved by at most one unit in the last place to honor
+//! the rounding mode selected in FCSR. The exact error of each operation decides which way.
+
+use crate::Exception;
+use core::{
+    cmp::Ordering,
+    ops::{BitAnd, BitOr, BitOrAssign},
+};
+use num_traits::Float;
+use seaside_constants::{ConditionCode, register::Coprocessor1ControlRegister};
+
+/// The value of FIR, which describes an FPU supporting the single, double and word formats.
+pub const FIR: u32 = (1 << 16) | (1 << 17) | (1 << 20);
+
+/// How results that can't be represented exactly are rounded.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum RoundingMode {
+    /// Round to the nearest representable value, breaking ties towards the even one.
+    #[default]
+    Nearest = 0,
+    /// Round towards zero.
+    Zero = 1,
+    /// Round towards positive infinity.
+    PositiveInfinity = 2,
+    /// Round towards negative infinity.
+    NegativeInfinity = 3,
+}
+
+/// A set of IEEE 754 exceptions, laid out like the flag, enable and cause fields of FCSR.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct FpuExceptions(u8);
+
+impl FpuExceptions {
+    pub const NONE: Self = Self(0);
+    pub const INEXACT: Self = Self(1 << 0);
+    pub const UNDERFLOW: Self = Self(1 << 1);
+    pub const OVERFLOW: Self = Self(1 << 2);
+    pub const DIVIDE_BY_ZERO: Self = Self(1 << 3);
+    pub const INVALID: Self = Self(1 << 4);
+
+    const MASK: u8 = 0x1f;
+
+    pub const fn from_bits(bits: u8) -> Self {
+        Self(bits & Self::MASK)
+    }
+
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Gets the exception raised when these are trapped, picking the most severe one.
+    pub const fn to_exception(self) -> Option<Exception> {
+        if self.contains(Self::INVALID) {
+            Some(Exception::FloatInvalid)
+        } else if self.contains(Self::DIVIDE_BY_ZERO) {
+            Some(Exception::DivideByZero)
+        } else if self.contains(Self::OVERFLOW) {
+            Some(Exception::FloatOverflow)
+        } else if self.contains(Self::UNDERFLOW) {
+            Some(Exception::FloatUnderflow)
+        } else if self.contains(Self::INEXACT) {
+            Some(Exception::FloatInexact)
+        } else {
+            None
+        }
+    }
+}
+
+impl BitAnd for FpuExceptions {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        Self(self.0 & rhs.0)
+    }
+}
+
+impl BitOr for FpuExceptions {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for FpuExceptions {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+/// The floating-point control and status register.
+///
+/// ```text
+///  31    25 24  23  22  18 17 16   12 11    7 6     2 1  0
+/// FCC7-FCC1 FS FCC0 00000  E   cause  enables  flags   RM
+/// ```
+///
+/// The flag bits are sticky: once an exception sets one, it stays set until the program clears
+/// it. The cause bits only describe the most recent floating-point operation.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Fcsr(u32);
+
+impl Fcsr {
+    const ROUNDING_MODE: u32 = 0b11;
+    const FLAGS_SHIFT: u32 = 2;
+    const ENABLES_SHIFT: u32 = 7;
+    const CAUSE_SHIFT: u32 = 12;
+    /// The cause bit for unimplemented operations, which sits just above the others.
+    const UNIMPLEMENTED: u32 = 1 << 17;
+    const FLUSH_TO_ZERO: u32 = 1 << 24;
+    /// The bits that exist; the rest always read as zero.
+    const MASK: u32 = 0xff83_ffff;
+
+    pub const fn from_bits(bits: u32) -> Self {
+        Self(bits & Self::MASK)
+    }
+
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+
+    pub const fn rounding_mode(self) -> RoundingMode {
+        match self.0 & Self::ROUNDING_MODE {
+            0 => RoundingMode::Nearest,
+            1 => RoundingMode::Zero,
+            2 => RoundingMode::PositiveInfinity,
+            _ => RoundingMode::NegativeInfinity,
+        }
+    }
+
+    /// The exceptions that have occurred since the program last cleared them.
+    pub const fn flags(self) -> FpuExceptions {
+        FpuExceptions::from_bits((self.0 >> Self::FLAGS_SHIFT) as u8)
+    }
+
+    /// The exceptions that trap instead of producing a result.
+    pub const fn enables(self) -> FpuExceptions {
+        FpuExceptions::from_bits((self.0 >> Self::ENABLES_SHIFT) as u8)
+    }
+
+    /// The exceptions raised by the most recent floating-point operation.
+    pub const fn cause(self) -> FpuExceptions {
+        FpuExceptions::from_bits((self.0 >> Self::CAUSE_SHIFT) as u8)
+    }
+
+    pub const fn condition(self, cc: ConditionCode) -> bool {
+        (self.0 >> Self::condition_bit(cc)) & 1 == 1
+    }
+
+    pub const fn set_condition(&mut self, cc: ConditionCode, value: bool) {
+        let mask = 1 << Self::condition_bit(cc);
+        if value {
+            self.0 |= mask;
+        } else {
+            self.0 &= !mask;
+        }
+    }
+
+    /// Packs every condition code into a byte, with `cc` 0 in the lowest bit.
+    pub const fn conditions(self) -> u8 {
+        (((self.0 >> 23) & 1) | ((self.0 >> 24) & 0xfe)) as u8
+    }
+
+    /// Records that a floating-point operation raised `exceptions`.
+    ///
+    /// If any of them are enabled, nothing besides the cause field is updated and the
+    /// corresponding [`Exception`] is returned. The operation shouldn't write its result in that
+    /// case. Otherwise, `exceptions` are added to the flags.
+    pub fn signal(&mut self, exceptions: FpuExceptions) -> Result<(), Exception> {
+        self.set_field(Self::CAUSE_SHIFT, 0x3f, exceptions.bits() as u32);
+        match (exceptions & self.enables()).to_exception() {
+            Some(exception) => Err(exception),
+            None => {
+                self.0 |= (exceptions.bits() as u32) << Self::FLAGS_SHIFT;
+                Ok(())
+            }
+        }
+    }
+
+    /// Reads a floating-point control register, most of which are views of parts of FCSR.
+    pub const fn read(self, register: Coprocessor1ControlRegister) -> u32 {
+        use Coprocessor1ControlRegister::*;
+        match register {
+            Implementation => FIR,
+            ConditionCodes => self.conditions() as u32,
+            Exceptions => self.0 & ((0x3f << Self::CAUSE_SHIFT) | (0x1f << Self::FLAGS_SHIFT)),
+            Enables => {
+                (self.0 & ((0x1f << Self::ENABLES_SHIFT) | Self::ROUNDING_MODE))
+                    | ((self.0 & Self::FLUSH_TO_ZERO) >> 22)
+            }
+            ControlStatus => self.0,
+        }
+    }
+
+    /// Writes `value` to a floating-point control register, updating the parts of FCSR it views.
+    ///
+    /// Writes to FIR are ignored, since it's read-only. If the write leaves an enabled exception
+    /// (or an unimplemented operation) in the cause field, the corresponding [`Exception`] is
+    /// returned, just as if an operation had raised it.
+    pub fn write(
+        &mut self,
+        register: Coprocessor1ControlRegister,
+        value: u32,
+    ) -> Result<(), Exception> {
+        use Coprocessor1ControlRegister::*;
+        match register {
+            Implementation => return Ok(()),
+            ConditionCodes => {
+                self.0 &= !0xfe80_0000;
+                self.0 |= ((value & 1) << 23) | ((value & 0xfe) << 24);
+            }
+            Exceptions => {
+                self.set_field(Self::CAUSE_SHIFT, 0x3f, value >> Self::CAUSE_SHIFT);
+                self.set_field(Self::FLAGS_SHIFT, 0x1f, value >> Self::FLAGS_SHIFT);
+            }
+            Enables => {
+                self.set_field(Self::ENABLES_SHIFT, 0x1f, value >> Self::ENABLES_SHIFT);
+                self.set_field(0, Self::ROUNDING_MODE, value);
+                self.0 &= !Self::FLUSH_TO_ZERO;
+                self.0 |= (value & 0b100) << 22;
+            }
+            ControlStatus => *self = Self::from_bits(value),
+        }
+        if self.0 & Self::UNIMPLEMENTED != 0 {
+            return Err(Exception::ReservedInstruction);
+        }
+        match (self.cause() & self.enables()).to_exception() {
+            Some(exception) => Err(exception),
+            None => Ok(()),
+        }
+    }
+
+    const fn condition_bit(cc: ConditionCode) -> u32 {
+        match cc as u32 {
+            0 => 23,
+            cc => 24 + cc,
+        }
+    }
+
+    const fn set_field(&mut self, shift: u32, mask: u32, value: u32) {
+        self.0 &= !(mask << shift);
+        self.0 |= (value & mask) << shift;
+    }
+}
+
+/// The floating-point formats coprocessor 1 computes with.
+pub(crate) trait Ieee754: Float {
+    /// Gets the smallest representable value greater than this one.
+    fn next_up(self) -> Self;
+
+    /// Gets the largest representable value less than this one.
+    fn next_down(self) -> Self {
+        -(-self).next_up()
+    }
+}
+
+macro_rules! impl_ieee754 {
+    ($($t:ty),*) => {
+        $(
+            impl Ieee754 for $t {
+                fn next_up(self) -> Self {
+                    if self.is_nan() || self == <$t>::INFINITY {
+                        self
+                    } else if self == 0.0 {
+                        <$t>::from_bits(1)
+                    } else if self > 0.0 {
+                        <$t>::from_bits(self.to_bits() + 1)
+                    } else {
+                        <$t>::from_bits(self.to_bits() - 1)
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_ieee754!(f32, f64);
+
+/// Adds `a` and `b`.
+pub(crate) fn add<T: Ieee754>(a: T, b: T, mode: RoundingMode) -> (T, FpuExceptions) {
+    let (sum, error) = two_sum(a, b);
+    finish(sum, sign(error), &[a, b], mode)
+}
+
+/// Subtracts `b` from `a`.
+pub(crate) fn sub<T: Ieee754>(a: T, b: T, mode: RoundingMode) -> (T, FpuExceptions) {
+    add(a, -b, mode)
+}
+
+/// Multiplies `a` and `b`.
+pub(crate) fn mul<T: Ieee754>(a: T, b: T, mode: RoundingMode) -> (T, FpuExceptions) {
+    let product = a * b;
+    let error = a.mul_add(b, -product);
+    finish(product, sign(error), &[a, b], mode)
+}
+
+/// Multiplies `a` and `b`, then adds `c`, rounding only once.
+pub(crate) fn mul_add<T: Ieee754>(a: T, b: T, c: T, mode: RoundingMode) -> (T, FpuExceptions) {
+    let result = a.mul_add(b, c);
+    // The exact result is `product + product_error + c`, and `product + c` is exactly
+    // `sum + sum_error`.
+    let product = a * b;
+    let product_error = a.mul_add(b, -product);
+    let (sum, sum_error) = two_sum(product, c);
+    let error = ((sum - result) + sum_error) + product_error;
+    finish(result, sign(error), &[a, b, c], mode)
+}
+
+/// Divides `a` by `b`.
+pub(crate) fn div<T: Ieee754>(a: T, b: T, mode: RoundingMode) -> (T, FpuExceptions) {
+    let quotient = a / b;
+    if b.is_zero() && a.is_finite() && !a.is_zero() {
+        return (quotient, FpuExceptions::DIVIDE_BY_ZERO);
+    }
+    // The remainder is exact, and the exact quotient is `quotient + remainder / b`.
+    let remainder = (-quotient).mul_add(b, a);
+    let error = if b.is_sign_negative() {
+        sign(remainder).reverse()
+    } else {
+        sign(remainder)
+    };
+    finish(quotient, error, &[a, b], mode)
+}
+
+/// Computes the square root of `a`.
+pub(crate) fn sqrt<T: Ieee754>(a: T, mode: RoundingMode) -> (T, FpuExceptions) {
+    let root = a.sqrt();
+    let error = (-root).mul_add(root, a);
+    finish(root, sign(error), &[a], mode)
+}
+
+/// Converts `value` to single precision.
+pub(crate) fn to_single(value: f64, mode: RoundingMode) -> (f32, FpuExceptions) {
+    let nearest = value as f32;
+    if !value.is_finite() {
+        (nearest, FpuExceptions::NONE)
+    } else if nearest.is_infinite() {
+        overflow(nearest, mode)
+    } else {
+        let error = value
+            .partial_cmp(&(nearest as f64))
+            .unwrap_or(Ordering::Equal);
+        round(nearest, error, mode)
+    }
+}
+
+/// Converts `value` to single precision.
+pub(crate) fn word_to_single(value: i32, mode: RoundingMode) -> (f32, FpuExceptions) {
+    let nearest = value as f32;
+    round(nearest, (value as i64).cmp(&(nearest as i64)), mode)
+}
+
+/// Converts `value` to a word, rounding it to an integer according to `mode`.
+///
+/// If the result doesn't fit in a word (or `value` is NaN), it's replaced with [`i32::MAX`].
+pub(crate) fn to_word<T: Ieee754>(value: T, mode: RoundingMode) -> (i32, FpuExceptions) {
+    let rounded = match mode {
+        RoundingMode::Nearest => round_ties_even(value),
+        RoundingMode::Zero => value.trunc(),
+        RoundingMode::PositiveInfinity => value.ceil(),
+        RoundingMode::NegativeInfinity => value.floor(),
+    };
+    match rounded.to_i32() {
+        Some(word) if rounded == value => (word, FpuExceptions::NONE),
+        Some(word) => (word, FpuExceptions::INEXACT),
+        None => (i32::MAX, FpuExceptions::INVALID),
+    }
+}
+
+/// Gets the exceptions raised by comparing `a` and `b` with a predicate that signals when they
+/// are unordered.
+pub(crate) fn compare_signaling<T: Ieee754>(a: T, b: T) -> FpuExceptions {
+    if a.is_nan() || b.is_nan() {
+        FpuExceptions::INVALID
+    } else {
+        FpuExceptions::NONE
+    }
+}
+
+/// Finishes an arithmetic operation, given the operation's result rounded to `nearest`, how the
+/// exact result compares to it, and the `operands` that produced it.
+fn finish<T: Ieee754>(
+    nearest: T,
+    error: Ordering,
+    operands: &[T],
+    mode: RoundingMode,
+) -> (T, FpuExceptions) {
+    if operands.iter().any(|operand| operand.is_nan()) {
+        (nearest, FpuExceptions::NONE)
+    } else if nearest.is_nan() {
+        (nearest, FpuExceptions::INVALID)
+    } else if nearest.is_infinite() {
+        if operands.iter().all(|operand| operand.is_finite()) {
+            overflow(nearest, mode)
+        } else {
+            (nearest, FpuExceptions::NONE)
+        }
+    } else {
+        round(nearest, error, mode)
+    }
+}
+
+/// Rounds a finite result according to `mode`, given the result rounded to `nearest` and how the
+/// exact result compares to it.
+fn round<T: Ieee754>(nearest: T, error: Ordering, mode: RoundingMode) -> (T, FpuExceptions) {
+    if error == Ordering::Equal {
+        return (nearest, FpuExceptions::NONE);
+    }
+    let rounded = match (mode, error) {
+        (RoundingMode::Zero, Ordering::Less) if nearest > T::zero() => nearest.next_down(),
+        (RoundingMode::Zero, Ordering::Greater) if nearest < T::zero() => nearest.next_up(),
+        (RoundingMode::PositiveInfinity, Ordering::Greater) => nearest.next_up(),
+        (RoundingMode::NegativeInfinity, Ordering::Less) => nearest.next_down(),
+        _ => nearest,
+    };
+    let mut exceptions = FpuExceptions::INEXACT;
+    if nearest.abs() < T::min_positive_value() {
+        exceptions |= FpuExceptions::UNDERFLOW;
+    }
+    if rounded.is_infinite() {
+        exceptions |= FpuExceptions::OVERFLOW;
+    }
+    (rounded, exceptions)
+}
+
+/// Produces the result of an operation that overflowed to `infinity` when rounding to nearest.
+fn overflow<T: Ieee754>(infinity: T, mode: RoundingMode) -> (T, FpuExceptions) {
+    let largest = T::max_value().copysign(infinity);
+    let result = match mode {
+        RoundingMode::Nearest => infinity,
+        RoundingMode::Zero => largest,
+        RoundingMode::PositiveInfinity if infinity < T::zero() => largest,
+        RoundingMode::NegativeInfinity if infinity > T::zero() => largest,
+        _ => infinity,
+    };
+    (result, FpuExceptions::OVERFLOW | FpuExceptions::INEXACT)
+}
+
+/// Rounds `value` to the nearest integer, breaking ties towards the even one.
+fn round_ties_even<T: Ieee754>(value: T) -> T {
+    let two = T::one() + T::one();
+    let rounded = value.round();
+    if (rounded - value).abs() == T::one() / two {
+        two * (value / two).round()
+    } else {
+        rounded
+    }
+}
+
+/// Adds `a` and `b`, returning the sum along with the exact error of the addition.
+///
+/// This is Knuth's TwoSum algorithm.
+fn two_sum<T: Ieee754>(a: T, b: T) -> (T, T) {
+    let sum = a + b;
+    let b_virtual = sum - a;
+    let error = (a - (sum - b_virtual)) + (b - b_virtual);
+    (sum, error)
+}
+
+/// Gets the sign of `value`, treating NaN as zero.
+fn sign<T: Ieee754>(value: T) -> Ordering {
+    value.partial_cmp(&T::zero()).unwrap_or(Ordering::Equal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Fcsr, FpuExceptions, RoundingMode, add, mul, to_single, to_word};
+    use crate::Exception;
+
+    const MODES: [RoundingMode; 4] = [
+        RoundingMode::Nearest,
+        RoundingMode::Zero,
+        RoundingMode::PositiveInfinity,
+        RoundingMode::NegativeInfinity,
+    ];
+
+    /// 2^-30, which is too small to change 1.0 in single precision.
+    const TINY: f64 = 1.0 / (1u64 << 30) as f64;
+
+    #[test]
+    fn rounding_mode_comes_from_the_low_bits() {
+        for (bits, mode) in MODES.into_iter().enumerate() {
+            assert_eq!(Fcsr::from_bits(bits as u32).rounding_mode(), mode);
+        }
+    }
+
+    #[test]
+    fn to_word_rounds_in_every_mode() {
+        let cases: [(f64, [i32; 4]); 6] = [
+            (2.5, [2, 2, 3, 2]),
+            (-2.5, [-2, -2, -2, -3]),
+            (3.5, [4, 3, 4, 3]),
+            (2.7, [3, 2, 3, 2]),
+            (-2.1, [-2, -2, -2, -3]),
+            (-2.7, [-3, -2, -2, -3]),
+        ];
+        for (value, expected) in cases {
+            for (mode, word) in MODES.into_iter().zip(expected) {
+                assert_eq!(
+                    to_word(value, mode),
+                    (word, FpuExceptions::INEXACT),
+                    "{value} rounded with {mode:?}",
+                );
+                assert_eq!(to_word(value as f32, mode), (word, FpuExceptions::INEXACT));
+            }
+        }
+    }
+
+    #[test]
+    fn to_word_is_exact_for_integers() {
+        for mode in MODES {
+            assert_eq!(to_word(-7.0f64, mode), (-7, FpuExceptions::NONE));
+            assert_eq!(to_word(7.0f32, mode), (7, FpuExceptions::NONE));
+        }
+    }
+
+    #[test]
+    fn to_word_is_invalid_out_of_range() {
+        for mode in MODES {
+            for value in [f64::NAN, f64::INFINITY, 3e9, -3e9] {
+                assert_eq!(to_word(value, mode), (i32::MAX, FpuExceptions::INVALID));
+            }
+            assert_eq!(
+                to_word(2_147_483_648.0f32, mode),
+                (i32::MAX, FpuExceptions::INVALID)
+            );
+        }
+    }
+
+    #[test]
+    fn to_single_rounds_in_every_mode() {
+        let above = 1.0f32 + f32::EPSILON;
+        let expected = [(1.0, -1.0), (1.0, -1.0), (above, -1.0), (1.0, -above)];
+        for (mode, (positive, negative)) in MODES.into_iter().zip(expected) {
+            assert_eq!(
+                to_single(1.0 + TINY, mode),
+                (positive, FpuExceptions::INEXACT)
+            );
+            assert_eq!(
+                to_single(-1.0 - TINY, mode),
+                (negative, FpuExceptions::INEXACT)
+            );
+            assert_eq!(to_single(1.5, mode), (1.5, FpuExceptions::NONE));
+        }
+    }
+
+    #[test]
+    fn to_single_overflows() {
+        let overflow = FpuExceptions::OVERFLOW | FpuExceptions::INEXACT;
+        let expected = [
+            (f32::INFINITY, -f32::INFINITY),
+            (f32::MAX, -f32::MAX),
+            (f32::INFINITY, -f32::MAX),
+            (f32::MAX, -f32::INFINITY),
+        ];
+        for (mode, (positive, negative)) in MODES.into_iter().zip(expected) {
+            assert_eq!(to_single(f64::MAX, mode), (positive, overflow));
+            assert_eq!(to_single(-f64::MAX, mode), (negative, overflow));
+        }
+    }
+
+    #[test]
+    fn arithmetic_rounds_in_every_mode() {
+        let tiny = TINY as f32;
+        let above = 1.0f32 + f32::EPSILON;
+        let expected = [1.0, 1.0, above, 1.0];
+        for (mode, sum) in MODES.into_iter().zip(expected) {
+            assert_eq!(add(1.0, tiny, mode), (sum, FpuExceptions::INEXACT));
+        }
+        let overflow = FpuExceptions::OVERFLOW | FpuExceptions::INEXACT;
+        assert_eq!(
+            mul(f32::MAX, 2.0, RoundingMode::Nearest),
+            (f32::INFINITY, overflow)
+        );
+        assert_eq!(mul(f32::MAX, 2.0, RoundingMode::Zero), (f32::MAX, overflow));
+    }
+
+    #[test]
+    fn signal_sets_cause_and_sticky_flags() {
+        let mut fcsr = Fcsr::default();
+        fcsr.signal(FpuExceptions::INEXACT).unwrap();
+        assert_eq!(fcsr.bits(), (1 << 12) | (1 << 2));
+        fcsr.signal(FpuExceptions::OVERFLOW | FpuExceptions::INEXACT)
+            .unwrap();
+        assert_eq!(fcsr.bits(), (1 << 14) | (1 << 12) | (1 << 4) | (1 << 2));
+        fcsr.signal(FpuExceptions::INVALID).unwrap();
+        assert_eq!(fcsr.cause(), FpuExceptions::INVALID);
+        assert_eq!(
+            fcsr.flags(),
+            FpuExceptions::INVALID | FpuExceptions::OVERFLOW | FpuExceptions::INEXACT
+        );
+        assert_eq!(fcsr.bits(), (1 << 16) | (1 << 6) | (1 << 4) | (1 << 2));
+        fcsr.signal(FpuExceptions::NONE).unwrap();
+        assert!(fcsr.cause().is_empty());
+        assert_eq!(fcsr.flags().bits(), 0b10101);
+    }
+
+    #[test]
+    fn signal_traps_enabled_exceptions() {
+        let enables = |exceptions: FpuExceptions| Fcsr::from_bits((exceptions.bits() as u32) << 7);
+        let cases = [
+            (FpuExceptions::INEXACT, Exception::FloatInexact),
+            (FpuExceptions::OVERFLOW, Exception::FloatOverflow),
+            (FpuExceptions::INVALID, Exception::FloatInvalid),
+        ];
+        for (exception, trap) in cases {
+            let mut fcsr = enables(exception);
+            let raised = exception | FpuExceptions::INEXACT;
+            assert_eq!(fcsr.signal(raised), Err(trap));
+            assert_eq!(fcsr.cause(), raised);
+            assert!(fcsr.flags().is_empty());
+        }
+        // Disabled exceptions don't trap, even alongside enabled ones that weren't raised.
+        let mut fcsr = enables(FpuExceptions::OVERFLOW);
+        assert_eq!(fcsr.signal(FpuExceptions::INEXACT), Ok(()));
+        assert_eq!(fcsr.flags(), FpuExceptions::INEXACT);
+    }
+}
@@ -0,0 +1,320 @@
+//! A `malloc`-style allocator for the heap.
+//!
+//! Provides the struct [`HeapAllocator`], which hands out blocks of heap memory that can be freed
+//! in any order. It grows the heap through the same break as `sbrk`, and keeps its bookkeeping
+//! outside of the program's memory so programs can't corrupt it.
+
+use crate::SyscallFailureKind;
+use seaside_type_aliases::Address;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::Write,
+};
+
+/// Every block is aligned to, and a multiple of, this many bytes, so any value fits in one.
+pub const ALIGNMENT: u32 = 8;
+
+/// A block handed out by the allocator that hasn't been freed yet.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Allocation {
+    /// The number of bytes the program asked for.
+    pub size: u32,
+    /// The number of bytes reserved for the block, which is at least `size`.
+    pub capacity: u32,
+    /// The address of the `syscall` instruction that allocated the block.
+    pub site: Address,
+}
+
+/// Hands out and takes back blocks of heap memory.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct HeapAllocator {
+    /// The blocks currently allocated, keyed by address.
+    allocations: BTreeMap<Address, Allocation>,
+    /// The free chunks of memory the allocator owns, keyed by address. Adjacent chunks are always
+    /// merged.
+    free: BTreeMap<Address, u32>,
+    /// The addresses of blocks that were freed and haven't been handed out again, which is how
+    /// double frees are told apart from invalid ones.
+    freed: BTreeSet<Address>,
+}
+
+impl HeapAllocator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Checks whether the allocator has never been used.
+    pub fn is_unused(&self) -> bool {
+        self.allocations.is_empty() && self.free.is_empty() && self.freed.is_empty()
+    }
+
+    /// The address just past the highest memory the allocator owns, whether allocated or free, or
+    /// [`None`] if it doesn't own any.
+    ///
+    /// The heap break must never move below this, or blocks would be handed out past it.
+    pub fn high_water_mark(&self) -> Option<Address> {
+        let allocated = self
+            .allocations
+            .last_key_value()
+            .map(|(&address, allocation)| address + allocation.capacity);
+        let free = self
+            .free
+            .last_key_value()
+            .map(|(&address, &len)| address + len);
+        allocated.max(free)
+    }
+
+    /// The blocks currently allocated, in order of address.
+    pub fn allocations(&self) -> impl Iterator<Item = (Address, &Allocation)> {
+        self.allocations
+            .iter()
+            .map(|(&address, allocation)| (address, allocation))
+    }
+
+    /// Gets the block starting at `address`, or explains why it can't be freed if it isn't
+    /// allocated.
+    pub fn allocation(&self, address: Address) -> Result<Allocation, SyscallFailureKind> {
+        match self.allocations.get(&address) {
+            Some(&allocation) => Ok(allocation),
+            None if self.freed.contains(&address) => Err(SyscallFailureKind::DoubleFree(address)),
+            None => Err(SyscallFailureKind::InvalidFree(address)),
+        }
+    }
+
+    /// The number of bytes `size` takes up once rounded up to a whole block.
+    pub fn capacity_for(size: u32) -> Option<u32> {
+        size.max(1).checked_next_multiple_of(ALIGNMENT)
+    }
+
+    /// Allocates a block of `size` bytes from the free chunks, returning its address, or [`None`]
+    /// if no chunk is big enough.
+    ///
+    /// `site` is the address of the instruction that asked for the block.
+    pub fn allocate(&mut self, size: u32, site: Address) -> Option<Address> {
+        let capacity = Self::capacity_for(size)?;
+        let (&address, &len) = self.free.iter().find(|&(_, &len)| len >= capacity)?;
+        self.free.remove(&address);
+        if len > capacity {
+            self.free.insert(address + capacity, len - capacity);
+        }
+        self.freed.remove(&address);
+        self.allocations.insert(
+            address,
+            Allocation {
+                size,
+                capacity,
+                site,
+            },
+        );
+        Some(address)
+    }
+
+    /// Gives the allocator `len` bytes of fresh memory starting at `address`.
+    pub fn add_memory(&mut self, address: Address, len: u32) {
+        self.release(address, len);
+    }
+
+    /// Frees the block starting at `address`, returning it.
+    pub fn free(&mut self, address: Address) -> Result<Allocation, SyscallFailureKind> {
+        let allocation = self.allocation(address)?;
+        self.allocations.remove(&address);
+        self.release(address, allocation.capacity);
+        self.freed.insert(address);
+        Ok(allocation)
+    }
+
+    /// Changes the size of the block at `address` to `size` if it already has room for it,
+    /// returning true if it did.
+    pub fn resize_in_place(&mut self, address: Address, size: u32) -> bool {
+        match self.allocations.get_mut(&address) {
+            Some(allocation) if allocation.capacity >= size => {
+                allocation.size = size;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Writes a line for every block that was never freed to `writer`.
+    ///
+    /// `describe` is used to describe where each block was allocated.
+    pub fn write_leak_report<W, F>(&self, writer: &mut W, describe: F) -> std::io::Result<()>
+    where
+        W: Write,
+        F: Fn(Address) -> String,
+    {
+        let total: u64 = self
+            .allocations
+            .values()
+            .map(|block| block.size as u64)
+            .sum();
+        writeln!(
+            writer,
+            "heap: {total} bytes in {} blocks still allocated at exit",
+            self.allocations.len(),
+        )?;
+        for (address, allocation) in &self.allocations {
+            writeln!(
+                writer,
+                "  {} bytes at 0x{address:08x} allocated by {} (pc: 0x{:08x})",
+                allocation.size,
+                describe(allocation.site),
+                allocation.site,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Flattens the allocator into words for saving, as read by [`HeapAllocator::from_words`].
+    pub fn to_words(&self) -> Vec<u32> {
+        let mut words = vec![self.allocations.len() as u32];
+        for (&address, allocation) in &self.allocations {
+            words.extend([
+                address,
+                allocation.size,
+                allocation.capacity,
+                allocation.site,
+            ]);
+        }
+        words.push(self.free.len() as u32);
+        for (&address, &len) in &self.free {
+            words.extend([address, len]);
+        }
+        words.push(self.freed.len() as u32);
+        words.extend(&self.freed);
+        words
+    }
+
+    /// Rebuilds an allocator from the words produced by [`HeapAllocator::to_words`], taking them
+    /// one at a time from `next`. Returns [`None`] if `next` runs out early.
+    pub fn from_words<F>(mut next: F) -> Option<Self>
+    where
+        F: FnMut() -> Option<u32>,
+    {
+        let mut allocator = Self::new();
+        for _ in 0..next()? {
+            let address = next()?;
+            let allocation = Allocation {
+                size: next()?,
+                capacity: next()?,
+                site: next()?,
+            };
+            allocator.allocations.insert(address, allocation);
+        }
+        for _ in 0..next()? {
+            let address = next()?;
+            allocator.free.insert(address, next()?);
+        }
+        for _ in 0..next()? {
+            allocator.freed.insert(next()?);
+        }
+        Some(allocator)
+    }
+
+    /// Returns `len` bytes starting at `address` to the free chunks, merging it with its
+    /// neighbours.
+    fn release(&mut self, mut address: Address, mut len: u32) {
+        let previous = self.free.range(..address).next_back();
+        if let Some((&previous, &previous_len)) =
+            previous.filter(|&(&previous, &previous_len)| previous + previous_len == address)
+        {
+            self.free.remove(&previous);
+            address = previous;
+            len += previous_len;
+        }
+        if let Some(next_len) = self.free.remove(&(address + len)) {
+            len += next_len;
+        }
+        self.free.insert(address, len);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HeapAllocator;
+    use crate::SyscallFailureKind;
+
+    const SITE: u32 = 0x0040_0000;
+
+    #[test]
+    fn allocate_uses_first_chunk_that_fits() {
+        let mut allocator = HeapAllocator::new();
+        allocator.add_memory(0x1000, 16);
+        allocator.add_memory(0x2000, 64);
+        allocator.add_memory(0x3000, 64);
+        // The first chunk is too small, so the second is split.
+        assert_eq!(allocator.allocate(20, SITE), Some(0x2000));
+        assert_eq!(allocator.allocate(8, SITE), Some(0x1000));
+        assert_eq!(allocator.allocate(40, SITE), Some(0x2018));
+        assert_eq!(allocator.allocate(64, SITE), Some(0x3000));
+        assert_eq!(allocator.allocate(1, SITE), Some(0x1008));
+        assert_eq!(allocator.allocate(1, SITE), None);
+    }
+
+    #[test]
+    fn allocate_rounds_up_to_alignment() {
+        let mut allocator = HeapAllocator::new();
+        allocator.add_memory(0x1000, 64);
+        assert_eq!(allocator.allocate(0, SITE), Some(0x1000));
+        assert_eq!(allocator.allocate(9, SITE), Some(0x1008));
+        assert_eq!(allocator.allocate(1, SITE), Some(0x1018));
+        let allocation = allocator.allocation(0x1008).unwrap();
+        assert_eq!((allocation.size, allocation.capacity), (9, 16));
+    }
+
+    #[test]
+    fn free_coalesces_neighbouring_chunks() {
+        let mut allocator = HeapAllocator::new();
+        allocator.add_memory(0x1000, 24);
+        let blocks: Vec<u32> = (0..3)
+            .map(|_| allocator.allocate(8, SITE).unwrap())
+            .collect();
+        assert_eq!(allocator.allocate(8, SITE), None);
+        // Freeing the outer blocks leaves two separate chunks, too small for 16 bytes.
+        allocator.free(blocks[0]).unwrap();
+        allocator.free(blocks[2]).unwrap();
+        assert_eq!(allocator.allocate(16, SITE), None);
+        // Freeing the middle block merges all three.
+        allocator.free(blocks[1]).unwrap();
+        assert_eq!(allocator.allocate(24, SITE), Some(0x1000));
+    }
+
+    #[test]
+    fn free_reports_double_free() {
+        let mut allocator = HeapAllocator::new();
+        allocator.add_memory(0x1000, 16);
+        let address = allocator.allocate(8, SITE).unwrap();
+        allocator.free(address).unwrap();
+        assert_eq!(
+            allocator.free(address),
+            Err(SyscallFailureKind::DoubleFree(address)),
+        );
+        // Once handed out again, the block can be freed again.
+        assert_eq!(allocator.allocate(8, SITE), Some(address));
+        assert!(allocator.free(address).is_ok());
+    }
+
+    #[test]
+    fn free_reports_pointer_never_allocated() {
+        let mut allocator = HeapAllocator::new();
+        allocator.add_memory(0x1000, 16);
+        allocator.allocate(16, SITE).unwrap();
+        for address in [0x1004, 0x2000] {
+            assert_eq!(
+                allocator.free(address),
+                Err(SyscallFailureKind::InvalidFree(address)),
+            );
+        }
+    }
+
+    #[test]
+    fn high_water_mark_covers_allocated_and_free_memory() {
+        let mut allocator = HeapAllocator::new();
+        assert_eq!(allocator.high_water_mark(), None);
+        allocator.add_memory(0x1000, 16);
+        let address = allocator.allocate(16, SITE).unwrap();
+        assert_eq!(allocator.high_water_mark(), Some(0x1010));
+        allocator.free(address).unwrap();
+        assert_eq!(allocator.high_water_mark(), Some(0x1010));
+    }
+}
@@ -0,0 +1,236 @@
+//! Reverse execution.
+//!
+//! Provides the struct [`History`], which remembers enough about each of the most recent steps
+//! taken by the interpreter to undo them.
+//!
+//! Only state owned by the interpreter is rewound: registers, the pc, memory, the heap and its
+//! allocator, which files and directories are open and how far into them the program is, random
+//! number generators, the virtual clock and the notes recorded by the MIDI out services. Text that
+//! was already printed stays printed, input that was already read won't be read again, and data
+//! written to host files isn't erased.
+//!
+//! Nothing that collects statistics or checks the program while it runs is rewound either: the
+//! profiler, memcheck, caches, pipeline model, branch predictors, stack checker and calling
+//! convention checker all keep the steps that were undone, so `seaside run` refuses to step
+//! backwards while any of them are enabled.
+//!
+//! Writes to virtual files can't be undone, so the history can't be used while the sandbox keeps
+//! files in memory.
+
+use crate::{
+    InterpreterState, clock::VirtualClock, heap_allocator::HeapAllocator, memory::JournalEntry,
+    register_file::RegisterFile, snapshot::OpenFile,
+};
+use anyhow::Result;
+use seaside_type_aliases::Address;
+use std::collections::VecDeque;
+
+/// Everything needed to undo a single step.
+#[derive(Clone, Debug)]
+pub struct StepRecord {
+    pub pc: Address,
+    pub exit_code: Option<u8>,
+    pub registers: RegisterFile,
+    /// The previous contents of memory the step overwrote, in the order they were written.
+    pub memory: Vec<JournalEntry>,
+    pub next_heap_address: Address,
+    pub free_heap_space: u32,
+    /// The time on the virtual clock in nanoseconds, if there is one.
+    pub clock: Option<u64>,
+    /// The state a system service could have changed, if the step invoked one.
+    pub syscall: Option<SyscallRecord>,
+}
+
+/// The state a system service can change besides registers and memory.
+#[derive(Clone, Debug)]
+pub struct SyscallRecord {
+    pub next_fd: u32,
+    pub files: Vec<OpenFile>,
+    /// The internal state of each random number generator, keyed by its id.
+    pub rngs: Vec<(u32, u64)>,
+    pub heap_allocator: HeapAllocator,
+    /// How many notes the MIDI out services had recorded, and the time on their clock.
+    pub midi: (usize, u64),
+}
+
+impl StepRecord {
+    /// Captures the state of `state` just before a step.
+    ///
+    /// The memory journal starts out empty; it should be filled in with the writes made during
+    /// the step. If `syscall` is true, the state system services can change is captured, too.
+    pub(crate) fn capture(state: &InterpreterState, syscall: bool) -> Self {
+        Self {
+            pc: state.pc,
+            exit_code: state.exit_code,
+            registers: state.registers.clone(),
+            memory: Vec::new(),
+            next_heap_address: *state.memory.next_heap_address(),
+            free_heap_space: *state.memory.free_heap_space(),
+            clock: state.clock.as_ref().map(VirtualClock::nanos),
+            syscall: syscall
+                .then(|| {
+                    state.open_files().ok().map(|(files, _)| SyscallRecord {
+                        next_fd: state.next_fd,
+                        files,
+                        rngs: state.rng_states(),
+                        heap_allocator: state.heap_allocator.clone(),
+                        midi: state.midi.position(),
+                    })
+                })
+                .flatten(),
+        }
+    }
+
+    /// Returns `state` to how it was just before this step.
+    pub(crate) fn undo(&self, state: &mut InterpreterState) -> Result<()> {
+        for entry in self.memory.iter().rev() {
+            state.memory.undo(entry);
+        }
+        if let Some(syscall) = &self.syscall {
+            state.reopen_files(&syscall.files, None)?;
+            state.restore_rng_states(&syscall.rngs);
+            state.next_fd = syscall.next_fd;
+            state.heap_allocator = syscall.heap_allocator.clone();
+            state.midi.rewind(syscall.midi);
+        }
+        state.pc = self.pc;
+        state.exit_code = self.exit_code;
+        state.registers = self.registers.clone();
+        *state.memory.next_heap_address_mut() = self.next_heap_address;
+        *state.memory.free_heap_space_mut() = self.free_heap_space;
+        if let (Some(clock), Some(nanos)) = (&mut state.clock, self.clock) {
+            clock.set_nanos(nanos);
+        }
+        state.last_branch = None;
+        Ok(())
+    }
+
+    /// Checks whether the step wrote to `address`.
+    pub fn wrote_to(&self, address: Address) -> bool {
+        self.memory.iter().any(|entry| entry.covers(address))
+    }
+}
+
+/// The most recent steps taken by the interpreter, oldest first.
+#[derive(Clone, Debug, Default)]
+pub struct History {
+    window: usize,
+    records: VecDeque<StepRecord>,
+}
+
+impl History {
+    /// Creates an empty history that remembers up to `window` steps.
+    pub fn new(window: u32) -> Self {
+        Self {
+            window: window as usize,
+            records: VecDeque::new(),
+        }
+    }
+
+    /// The number of steps that can currently be undone.
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Remembers `record`, forgetting the oldest step if the window is full.
+    pub fn push(&mut self, record: StepRecord) {
+        if self.window == 0 {
+            return;
+        }
+        if self.records.len() == self.window {
+            self.records.pop_front();
+        }
+        self.records.push_back(record);
+    }
+
+    /// Removes and returns the most recent step.
+    pub fn pop(&mut self) -> Option<StepRecord> {
+        self.records.pop_back()
+    }
+
+    /// Forgets every step.
+    pub fn clear(&mut self) {
+        self.records.clear();
+    }
+}
+
+/// Why [`Interpreter::reverse_continue`](crate::Interpreter::reverse_continue) stopped.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StopReason {
+    /// The pc reached a breakpoint.
+    Breakpoint(Address),
+    /// The step that was undone wrote to a watched address.
+    Watchpoint(Address),
+    /// There were no more steps to undo.
+    HistoryExhausted,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StepRecord;
+    use crate::{
+        StopReason,
+        file_handle::FileHandle,
+        snapshot::tests::{FILL_LOOP, fill, interpreter, state_of},
+    };
+    use std::{collections::HashSet, fs};
+
+    #[test]
+    fn step_back_restores_starting_state() {
+        let (mut interpreter, directory) = fill("step-back");
+        let start = state_of(&interpreter);
+        // Five steps call sbrk and set up the loop, which then takes four steps per word.
+        let steps = 5 + 16 * 4;
+        for _ in 0..steps {
+            interpreter.step().unwrap();
+        }
+        assert!(interpreter.is_finished());
+        assert_ne!(state_of(&interpreter), start);
+        for _ in 0..steps {
+            assert!(interpreter.step_back().unwrap());
+        }
+        assert!(!interpreter.step_back().unwrap());
+        assert_eq!(state_of(&interpreter), start);
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn reverse_continue_stops_at_breakpoint() {
+        let (mut interpreter, directory) = fill("reverse-continue");
+        let fill_loop = interpreter.state.pc + FILL_LOOP;
+        interpreter.run_for(30).unwrap();
+        let breakpoints = HashSet::from([fill_loop]);
+        assert_eq!(
+            interpreter
+                .reverse_continue(&breakpoints, &HashSet::new())
+                .unwrap(),
+            StopReason::Breakpoint(fill_loop),
+        );
+        assert_eq!(interpreter.state.pc, fill_loop);
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn undo_reopens_closed_directory() {
+        let (mut interpreter, directory) = interpreter("undo-close", &[0], |_| {});
+        let state = &mut interpreter.state;
+        // The directory only holds the program, so reading one entry exhausts it.
+        let mut listing = FileHandle::open_directory(directory.clone()).unwrap();
+        listing.read_entry(usize::MAX).unwrap();
+        state.files.insert(3, listing);
+        state.next_fd = 4;
+        let record = StepRecord::capture(state, true);
+        state.files.remove(&3);
+        record.undo(state).unwrap();
+        assert!(matches!(
+            state.files.get(&3),
+            Some(FileHandle::Directory { entries, .. }) if entries.is_empty(),
+        ));
+        assert_eq!(state.next_fd, 4);
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
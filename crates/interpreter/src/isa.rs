@@ -0,0 +1,39 @@
+//! Instruction set restrictions.
+//!
+//! Provides the struct [`InstructionSet`], which decides whether each instruction a program runs
+//! is part of the instruction set configured by [`IsaOptions`].
+
+use seaside_config::features::IsaOptions;
+use seaside_disassembler::mnemonic;
+use seaside_type_aliases::Instruction;
+use std::collections::HashMap;
+
+/// The instructions a program may run.
+#[derive(Clone, Debug)]
+pub struct InstructionSet {
+    options: IsaOptions,
+    /// Remembers whether each instruction seen so far is allowed, since working it out requires
+    /// disassembling it.
+    verdicts: HashMap<Instruction, bool>,
+}
+
+impl InstructionSet {
+    pub fn new(options: &IsaOptions) -> Self {
+        Self {
+            options: options.clone(),
+            verdicts: HashMap::new(),
+        }
+    }
+
+    /// Checks whether `instruction` is part of the instruction set.
+    ///
+    /// Instructions that can't be decoded are allowed, since executing them raises a reserved
+    /// instruction exception anyway.
+    pub fn allows(&mut self, instruction: Instruction) -> bool {
+        let options = &self.options;
+        *self
+            .verdicts
+            .entry(instruction)
+            .or_insert_with(|| mnemonic(instruction).is_none_or(|name| options.allows(&name)))
+    }
+}
@@ -1,21 +1,68 @@
+pub mod branch_prediction;
+pub mod calling_convention;
+pub mod clock;
+pub mod core_dump;
+pub mod decode_cache;
+pub mod dialog;
 pub mod exception;
+pub mod fpu;
+pub mod heap_allocator;
+pub mod history;
+pub mod isa;
 pub mod memory;
+pub mod midi;
+pub mod pipeline;
+pub mod profiler;
 pub mod register_file;
+pub mod sandbox;
+#[cfg(feature = "lua")]
+pub mod scripting;
+pub mod snapshot;
+pub mod spim;
+pub mod stack_check;
+pub mod startup;
+pub mod symbols;
 pub mod syscall_failure;
+pub mod system_service;
 
+mod calls;
+mod errno;
 mod execute;
 mod file_handle;
 mod rng;
+mod strace;
 
+pub use branch_prediction::{BranchOutcome, BranchPredictor, BranchPredictors};
+pub use calling_convention::ConventionChecker;
+pub use clock::VirtualClock;
+pub use core_dump::CoreDump;
+pub use decode_cache::DecodeCache;
+pub use dialog::Dialogs;
 pub use exception::Exception;
+pub use fpu::Fcsr;
+pub use heap_allocator::HeapAllocator;
+pub use history::{History, StopReason};
+pub use isa::InstructionSet;
 pub use memory::Memory;
+pub use midi::MidiRecorder;
+pub use pipeline::Pipeline;
+pub use profiler::Profiler;
 pub use register_file::RegisterFile;
+pub use sandbox::{Sandbox, VirtualFileSystem};
+#[cfg(feature = "lua")]
+pub use scripting::ScriptedServices;
+pub use snapshot::Snapshot;
+pub use stack_check::StackChecker;
+pub use startup::Startup;
+pub use symbols::SymbolTable;
 pub use syscall_failure::SyscallFailureKind;
+pub use system_service::SystemService;
 
 use anyhow::Result;
 use file_handle::FileHandle;
-use memory::regions::Region;
+use history::StepRecord;
 use minimal_logging::macros::debugln;
+use num_traits::FromPrimitive;
 use register_file::IndexByRegister;
 use rng::Rng;
 use seaside_config::{
@@ -24,24 +71,59 @@ use seaside_config::{
         Service, Services,
         services::{
             mars::{self, Mars},
+            posix::{self, Posix},
+            seaside::{self, Seaside},
             spim::{self, Spim},
         },
     },
 };
-use seaside_constants::register::CpuRegister;
-use seaside_type_aliases::Address;
+use seaside_constants::{InstructionFormat, fn_codes::SpecialFn, register::CpuRegister};
+use seaside_disassembler::fields;
+use seaside_type_aliases::{Address, Instruction};
 use std::{
-    collections::HashMap,
-    fs::File,
+    collections::{HashMap, HashSet},
     io::{Write, stdout},
     path::PathBuf,
+    time::SystemTime,
 };
 
+type ServiceFn = for<'a> fn(&'a mut InterpreterState) -> Result<(), Exception>;
+
 pub struct Interpreter {
     pub state: InterpreterState,
-    services: HashMap<u32, for<'a> fn(&'a mut InterpreterState) -> Result<(), Exception>>,
+    services: HashMap<u32, (Service, ServiceFn)>,
+    /// The services implemented by scripts, if any are configured.
+    #[cfg(feature = "lua")]
+    pub scripts: Option<ScriptedServices>,
+    /// The services registered with [`Interpreter::register_service`], which take precedence over
+    /// the ones mapped in the config.
+    custom_services: HashMap<u32, Box<dyn SystemService>>,
     pub freeable_heap_allocations: bool,
+    /// Rejects instructions outside the configured instruction set, unless every instruction is
+    /// allowed.
+    pub instruction_set: Option<InstructionSet>,
+    /// Remembers how recently executed instructions were decoded. It's always created, but can be
+    /// set to [`None`] to decode every instruction each time it's fetched.
+    pub decode_cache: Option<DecodeCache>,
     pub show_crash_handler: bool,
+    /// Write a core file when an unhandled exception is thrown.
+    pub write_core_dump: bool,
+    /// Log each system service invocation along with its arguments and return values.
+    pub strace: bool,
+    /// Collects execution statistics if enabled.
+    pub profiler: Option<Profiler>,
+    /// Estimates how many cycles the program takes on a five-stage pipeline if enabled.
+    pub pipeline: Option<Pipeline>,
+    /// Compares the accuracy of several branch predictors if enabled.
+    pub branch_predictors: Option<BranchPredictors>,
+    /// Checks how the program uses its stack if enabled.
+    pub stack_checker: Option<StackChecker>,
+    /// Checks that procedure calls follow the calling convention if enabled.
+    pub convention_checker: Option<ConventionChecker>,
+    /// Remembers the most recent steps so they can be undone if enabled.
+    pub history: Option<History>,
+    /// The labels defined in the program being run, if known.
+    pub symbols: SymbolTable,
 }
 
 pub struct InterpreterState {
@@ -53,6 +135,24 @@ pub struct InterpreterState {
     pub rngs: HashMap<u32, Rng>,
     pub stdout_pending_flush: bool,
     pub exit_code: Option<u8>,
+    /// The outcome of the conditional branch executed by the current step, if any.
+    pub last_branch: Option<BranchOutcome>,
+    /// Shows the dialogs requested by the MARS dialog services.
+    pub dialogs: Dialogs,
+    /// Records the notes played by the MIDI out services.
+    pub midi: MidiRecorder,
+    /// Decides which files the file services can reach.
+    pub sandbox: Sandbox,
+    /// Hands out the blocks allocated by the heap services.
+    pub heap_allocator: HeapAllocator,
+    /// Stands in for the wall clock in deterministic mode.
+    pub clock: Option<VirtualClock>,
+    /// The seed every random number generator is derived from in deterministic mode.
+    pub master_seed: Option<u64>,
+    /// The address of the environment laid out at startup, or 0 if there is none.
+    pub envp: Address,
+    /// Follow SPIM's rules for reading and printing.
+    pub spim_compatible: bool,
 }
 
 impl Interpreter {
@@ -63,8 +163,15 @@ impl Interpreter {
         data: Option<PathBuf>,
         ktext: Option<PathBuf>,
         kdata: Option<PathBuf>,
-        argv: Vec<String>,
+        startup: Startup,
     ) -> Result<Self> {
+        #[cfg(not(feature = "lua"))]
+        if !config.features.scripts.is_empty() {
+            return Err(
+                anyhow::Error::new(seaside_error::EngineError::InvalidConfig)
+                    .context("binding services to scripts requires seaside's `lua` feature"),
+            );
+        }
         let memory = Memory::init(config, text, r#extern, data, ktext, kdata)?;
         let pc = memory.initial_pc();
         let services = Self::init_services(
@@ -76,6 +183,13 @@ impl Interpreter {
         files.insert(0, FileHandle::new_stdin());
         files.insert(1, FileHandle::new_stdout());
         files.insert(2, FileHandle::new_stderr());
+        let deterministic = &config.features.deterministic;
+        let clock = deterministic
+            .enabled
+            .then(|| VirtualClock::new(deterministic.nanos_per_instruction));
+        let now = clock
+            .as_ref()
+            .map_or_else(SystemTime::now, VirtualClock::time);
         let mut interpreter = Self {
             state: InterpreterState {
                 memory,
@@ -86,19 +200,86 @@ impl Interpreter {
                 rngs: HashMap::new(),
                 stdout_pending_flush: false,
                 exit_code: None,
+                last_branch: None,
+                dialogs: Dialogs::interactive(),
+                midi: MidiRecorder::new(),
+                sandbox: Sandbox::init(&config.features.sandbox, now)?,
+                heap_allocator: HeapAllocator::new(),
+                clock,
+                master_seed: deterministic.enabled.then_some(deterministic.seed),
+                envp: 0,
+                spim_compatible: config.features.spim.enabled,
             },
             services,
+            #[cfg(feature = "lua")]
+            scripts: ScriptedServices::init(&config.features.scripts)?,
+            custom_services: HashMap::new(),
             freeable_heap_allocations: config.features.freeable_heap_allocations,
+            instruction_set: (!config.features.isa.allows_everything())
+                .then(|| InstructionSet::new(&config.features.isa)),
+            decode_cache: Some(DecodeCache::new()),
             show_crash_handler: config.features.show_crash_handler,
+            write_core_dump: config.features.write_core_dump,
+            strace: false,
+            profiler: None,
+            pipeline: config
+                .features
+                .pipeline
+                .enabled
+                .then(|| Pipeline::new(&config.features.pipeline)),
+            branch_predictors: config
+                .features
+                .branch_prediction
+                .enabled
+                .then(|| BranchPredictors::new(&config.features.branch_prediction)),
+            stack_checker: None,
+            convention_checker: config
+                .features
+                .check_calling_convention
+                .then(|| ConventionChecker::new(pc)),
+            history: config
+                .features
+                .history
+                .enabled
+                .then(|| History::new(config.features.history.window)),
+            symbols: SymbolTable::new(),
         };
         interpreter
             .state
-            .init_argv(argv, config.memory_map.segments.runtime_data.range.limit)
-            .map(|_| interpreter)
+            .init_process(startup, config.memory_map.segments.runtime_data.range.limit)?;
+        let stack_checks = &config.features.stack_checks;
+        if stack_checks.enabled || stack_checks.alignment != 0 {
+            let sp: Address = interpreter.state.registers.read(CpuRegister::StackPtr);
+            interpreter.stack_checker = Some(StackChecker::new(stack_checks, sp));
+        }
+        Ok(interpreter)
     }
 
     pub fn run(&mut self) -> Result<(), Exception> {
-        while !self.state.memory.pc_past_end(self.state.pc) && self.state.exit_code.is_none() {
+        self.run_steps(None).map(|_| ())
+    }
+
+    /// Runs like [`Interpreter::run`], but pauses after `max_steps` instructions have been
+    /// executed.
+    ///
+    /// Returns true if the program was paused before it finished.
+    pub fn run_for(&mut self, max_steps: u64) -> Result<bool, Exception> {
+        self.run_steps(Some(max_steps))
+    }
+
+    /// Checks whether the program has exited or dropped off the bottom of its text segment.
+    pub fn is_finished(&self) -> bool {
+        self.state.memory.pc_past_end(self.state.pc) || self.state.exit_code.is_some()
+    }
+
+    fn run_steps(&mut self, max_steps: Option<u64>) -> Result<bool, Exception> {
+        let mut steps: u64 = 0;
+        while !self.is_finished() {
+            if max_steps.is_some_and(|max_steps| steps >= max_steps) {
+                let _ = self.state.flush_stdout_if_necessary();
+                return Ok(true);
+            }
+            steps += 1;
             if let Err(exception) = self.step() {
                 let _ = self.state.flush_stdout_if_necessary();
                 match self.state.memory.get_exception_handler() {
@@ -110,22 +291,159 @@ impl Interpreter {
             };
         }
         let _ = self.state.flush_stdout_if_necessary();
+        Ok(false)
+    }
+
+    /// Captures the current state of the program.
+    pub fn snapshot(&self) -> Result<Snapshot> {
+        Snapshot::capture(&self.state)
+    }
+
+    /// Captures the state of the program after it was ended by `exception`.
+    pub fn core_dump(&self, exception: Exception) -> Result<CoreDump> {
+        CoreDump::capture(&self.state, exception)
+    }
+
+    /// Returns the program to the state captured in `snapshot`.
+    ///
+    /// This also forgets the undo history, since it no longer leads anywhere.
+    pub fn restore(&mut self, snapshot: &Snapshot) -> Result<()> {
+        snapshot.apply(&mut self.state)?;
+        if let Some(history) = &mut self.history {
+            history.clear();
+        }
         Ok(())
     }
 
+    /// Undoes the most recent step.
+    ///
+    /// Returns false if there was nothing to undo, either because the history is disabled or
+    /// because every step in it has already been undone.
+    ///
+    /// The profiler, memcheck, caches, pipeline model, branch predictors and checkers aren't
+    /// rewound, so they keep counting the undone steps.
+    pub fn step_back(&mut self) -> Result<bool> {
+        Ok(self.undo_step()?.is_some())
+    }
+
+    /// Steps backwards until the pc reaches one of `breakpoints` or a step that wrote to one of
+    /// `watchpoints` is undone.
+    ///
+    /// At least one step is undone, so calling this while stopped at a breakpoint goes back to
+    /// the previous time it was hit.
+    pub fn reverse_continue(
+        &mut self,
+        breakpoints: &HashSet<Address>,
+        watchpoints: &HashSet<Address>,
+    ) -> Result<StopReason> {
+        while let Some(record) = self.undo_step()? {
+            if let Some(&address) = watchpoints
+                .iter()
+                .find(|&&address| record.wrote_to(address))
+            {
+                return Ok(StopReason::Watchpoint(address));
+            }
+            if breakpoints.contains(&self.state.pc) {
+                return Ok(StopReason::Breakpoint(self.state.pc));
+            }
+        }
+        Ok(StopReason::HistoryExhausted)
+    }
+
+    fn undo_step(&mut self) -> Result<Option<StepRecord>> {
+        let Some(record) = self.history.as_mut().and_then(History::pop) else {
+            return Ok(None);
+        };
+        record.undo(&mut self.state)?;
+        Ok(Some(record))
+    }
+
     pub fn step(&mut self) -> Result<(), Exception> {
-        let instruction = self.state.memory.get_instruction(self.state.pc)?;
+        let pc = self.state.pc;
+        let decoded = self.fetch_decoded(pc)?;
+        let instruction = decoded.instruction;
+        if let Some(memcheck) = self.state.memory.memcheck_mut() {
+            memcheck.set_pc(pc);
+        }
+        let record = self
+            .history
+            .is_some()
+            .then(|| StepRecord::capture(&self.state, is_syscall(instruction)));
+        if record.is_some() {
+            self.state.memory.start_journal();
+        }
         self.state.pc += 4;
-        self.execute(instruction)
+        let result = self
+            .execute_decoded(decoded)
+            .map_err(|exception| self.state.diagnose_stack_overflow(exception));
+        if let (Some(history), Some(mut record)) = (&mut self.history, record) {
+            record.memory = self.state.memory.take_journal();
+            history.push(record);
+        }
+        if let Some(clock) = &mut self.state.clock {
+            clock.tick();
+        }
+        if let Some(profiler) = &mut self.profiler {
+            profiler.record(pc, instruction, self.state.pc);
+        }
+        if let Some(pipeline) = &mut self.pipeline {
+            pipeline.record(pc, instruction, self.state.pc);
+        }
+        if let Some(stack_checker) = &mut self.stack_checker {
+            let sp: Address = self.state.registers.read(CpuRegister::StackPtr);
+            stack_checker.record(pc, instruction, self.state.pc, sp);
+        }
+        if let Some(convention_checker) = &mut self.convention_checker {
+            convention_checker.record(pc, instruction, self.state.pc, &self.state.registers);
+        }
+        if let (Some(predictors), Some(outcome)) =
+            (&mut self.branch_predictors, self.state.last_branch.take())
+        {
+            predictors.record(pc, outcome);
+        }
+        result
+    }
+
+    /// Starts collecting execution statistics, treating the current pc as the program's entry
+    /// point.
+    pub fn enable_profiler(&mut self) {
+        self.profiler = Some(Profiler::new(self.state.pc));
+    }
+
+    /// Starts checking the program's loads and stores for misuse of the heap and stack.
+    ///
+    /// Everything already on the stack above `$sp` (such as the program's arguments) is treated
+    /// as initialized.
+    pub fn enable_memcheck(&mut self) {
+        let sp: Address = self.state.registers.read(CpuRegister::StackPtr);
+        self.state.memory.enable_memcheck(sp);
+    }
+
+    /// Binds `service` to service `code`, returning the custom service it replaced, if any.
+    ///
+    /// Custom services take precedence over the services and scripts mapped in the config, so
+    /// they can both add new codes and override existing ones.
+    pub fn register_service<S>(&mut self, code: u32, service: S) -> Option<Box<dyn SystemService>>
+    where
+        S: SystemService + 'static,
+    {
+        self.custom_services.insert(code, Box::new(service))
+    }
+
+    /// Removes the custom service bound to service `code`, returning it.
+    ///
+    /// Calls to `code` go back to whatever the config maps it to.
+    pub fn unregister_service(&mut self, code: u32) -> Option<Box<dyn SystemService>> {
+        self.custom_services.remove(&code)
     }
 
     fn init_services(
         services: &Services,
         freeable_heap_allocations: bool,
-    ) -> Result<HashMap<u32, for<'a> fn(&'a mut InterpreterState) -> Result<(), Exception>>> {
+    ) -> Result<HashMap<u32, (Service, ServiceFn)>> {
         let mut service_fns = HashMap::new();
         for (&code, &service) in services.iter() {
-            let r#fn = match service {
+            let r#fn: ServiceFn = match service {
                 Service::Spim(Spim::Print(spim::Print::Int)) => InterpreterState::print_int,
                 Service::Mars(Mars::Print(mars::Print::Uint)) => InterpreterState::print_uint,
                 Service::Mars(Mars::Print(mars::Print::Bin)) => InterpreterState::print_bin,
@@ -198,8 +516,35 @@ impl Interpreter {
                 Service::Mars(Mars::Random(mars::Random::RandDouble)) => {
                     InterpreterState::rand_double
                 }
+                Service::Posix(Posix::File(posix::File::Open)) => InterpreterState::posix_open,
+                Service::Posix(Posix::File(posix::File::Lseek)) => InterpreterState::lseek,
+                Service::Posix(Posix::File(posix::File::Fstat)) => InterpreterState::fstat,
+                Service::Posix(Posix::File(posix::File::Stat)) => InterpreterState::stat,
+                Service::Posix(Posix::File(posix::File::Unlink)) => InterpreterState::unlink,
+                Service::Posix(Posix::File(posix::File::Rename)) => InterpreterState::rename,
+                Service::Posix(Posix::Directory(posix::Directory::Make)) => {
+                    InterpreterState::make_directory
+                }
+                Service::Posix(Posix::Directory(posix::Directory::Remove)) => {
+                    InterpreterState::remove_directory
+                }
+                Service::Posix(Posix::Directory(posix::Directory::Open)) => {
+                    InterpreterState::open_directory
+                }
+                Service::Posix(Posix::Directory(posix::Directory::Read)) => {
+                    InterpreterState::read_directory
+                }
+                Service::Seaside(Seaside::Heap(seaside::Heap::Malloc)) => InterpreterState::malloc,
+                Service::Seaside(Seaside::Heap(seaside::Heap::Calloc)) => InterpreterState::calloc,
+                Service::Seaside(Seaside::Heap(seaside::Heap::Realloc)) => {
+                    InterpreterState::realloc
+                }
+                Service::Seaside(Seaside::Heap(seaside::Heap::Free)) => InterpreterState::free,
+                Service::Seaside(Seaside::Process(seaside::Process::Getenv)) => {
+                    InterpreterState::getenv
+                }
             };
-            service_fns.insert(code, r#fn);
+            service_fns.insert(code, (service, r#fn));
         }
         Ok(service_fns)
     }
@@ -223,49 +568,17 @@ impl InterpreterState {
         );
     }
 
-    pub fn init_argv(&mut self, argv: Vec<String>, stack_base: Address) -> Result<()> {
-        let argc = argv.len() as u32;
-        if argc == 0 {
-            return Ok(());
-        }
-
-        let mut current: Address = stack_base;
-        let mut arg_addresses: Vec<Address> = Vec::with_capacity(argv.len());
-        for arg in &argv {
-            current -= 1;
-            for byte in arg.bytes().rev() {
-                self.memory.write_u8(current, byte)?;
-                current -= 1;
-            }
-            arg_addresses.push(current + 1);
-        }
-        let mut stack_frame_base: Address = self.registers.read(CpuRegister::StackPtr);
-        if current < stack_frame_base {
-            stack_frame_base = current - (current % 4) - 4;
-        }
-        stack_frame_base -= 4;
-        for &arg_address in arg_addresses.iter().rev() {
-            self.memory.write_u32(stack_frame_base, arg_address, true)?;
-            stack_frame_base -= 4;
-        }
-        self.memory.write_u32(stack_frame_base, argc, true)?;
-        self.registers
-            .write(CpuRegister::StackPtr, stack_frame_base);
-        self.registers.write(CpuRegister::Arg0, argc);
-        self.registers
-            .write(CpuRegister::Arg1, stack_frame_base + 4);
-        Ok(())
-    }
-
-    pub fn make_file_handle(&mut self, file: File) -> &mut FileHandle {
+    pub fn make_file_handle(&mut self, handle: FileHandle) -> &mut FileHandle {
         let fd: u32 = self.next_fd;
-        self.files.insert(fd, FileHandle::File(file));
+        self.files.insert(fd, handle);
         self.next_fd += 1;
         self.files.get_mut(&fd).unwrap()
     }
 
     pub fn close_file_handle(&mut self, fd: u32) -> bool {
-        if let Some(FileHandle::File(_)) = self.files.get_mut(&fd) {
+        if let Some(FileHandle::File { .. } | FileHandle::Directory { .. }) =
+            self.files.get_mut(&fd)
+        {
             self.files.remove(&fd);
             true
         } else {
@@ -273,8 +586,20 @@ impl InterpreterState {
         }
     }
 
+    /// The current time, which comes from the virtual clock in deterministic mode.
+    pub fn now(&self) -> SystemTime {
+        self.clock
+            .as_ref()
+            .map_or_else(SystemTime::now, VirtualClock::time)
+    }
+
+    /// Creates generator `id` with a random seed, or one derived from the master seed in
+    /// deterministic mode.
     pub fn make_rng(&mut self, id: u32) -> &mut Rng {
-        let seed: u64 = rand::random();
+        let seed: u64 = match self.master_seed {
+            Some(master_seed) => Rng::derive_seed(master_seed, id),
+            None => rand::random(),
+        };
         self.set_rng_seed(id, seed);
         self.rngs.get_mut(&id).unwrap()
     }
@@ -291,3 +616,10 @@ impl InterpreterState {
         Ok(())
     }
 }
+
+/// Checks whether `instruction` invokes a system service.
+fn is_syscall(instruction: Instruction) -> bool {
+    fields::opcode(instruction)
+        .is_some_and(|opcode| InstructionFormat::from(opcode) == InstructionFormat::Special)
+        && SpecialFn::from_u8(fields::r#fn(instruction)) == Some(SpecialFn::SystemCall)
+}
@@ -0,0 +1,227 @@
+use crate::rng::Rng;
+use core::fmt::{Display, Formatter, Result as FmtResult};
+use minimal_logging::macros::grayln;
+use seaside_config::features::cache::{CacheConfig, ReplacementPolicy, WritePolicy};
+use seaside_type_aliases::Address;
+
+/// Whether an access to a [`Cache`] reads or writes data.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AccessKind {
+    Read,
+    Write,
+}
+
+/// Counts how a [`Cache`] has been used.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct CacheStats {
+    pub read_hits: u64,
+    pub read_misses: u64,
+    pub write_hits: u64,
+    pub write_misses: u64,
+    /// The number of dirty blocks written back to memory upon eviction.
+    pub write_backs: u64,
+    /// The number of stores passed straight through to memory.
+    pub write_throughs: u64,
+}
+
+impl CacheStats {
+    pub const fn hits(&self) -> u64 {
+        self.read_hits + self.write_hits
+    }
+
+    pub const fn misses(&self) -> u64 {
+        self.read_misses + self.write_misses
+    }
+
+    pub const fn accesses(&self) -> u64 {
+        self.hits() + self.misses()
+    }
+
+    pub fn hit_rate(&self) -> f64 {
+        match self.accesses() {
+            0 => 0.0,
+            accesses => self.hits() as f64 / accesses as f64,
+        }
+    }
+}
+
+impl Display for CacheStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        writeln!(
+            f,
+            "{} accesses, {} hits, {} misses ({:.2}% hit rate)",
+            self.accesses(),
+            self.hits(),
+            self.misses(),
+            self.hit_rate() * 100.0,
+        )?;
+        writeln!(
+            f,
+            "  reads: {} hits, {} misses",
+            self.read_hits, self.read_misses,
+        )?;
+        writeln!(
+            f,
+            "  writes: {} hits, {} misses",
+            self.write_hits, self.write_misses,
+        )?;
+        write!(
+            f,
+            "  write-backs: {}, write-throughs: {}",
+            self.write_backs, self.write_throughs,
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Block {
+    valid: bool,
+    dirty: bool,
+    tag: u32,
+    /// When this block was brought into the cache.
+    loaded_at: u64,
+    /// When this block was last accessed.
+    used_at: u64,
+}
+
+/// Simulates a set-associative cache.
+///
+/// Only the tags are tracked; the data itself always lives in [`Memory`](super::Memory).
+pub struct Cache {
+    name: &'static str,
+    config: CacheConfig,
+    /// Every block in the cache, grouped into sets of [`CacheConfig::associativity`] blocks.
+    blocks: Vec<Block>,
+    offset_bits: u32,
+    index_mask: u32,
+    /// Increases with every access. Used to order blocks for replacement.
+    clock: u64,
+    rng: Rng,
+    log_accesses: bool,
+    stats: CacheStats,
+}
+
+impl Cache {
+    /// Creates an empty cache. `seed` seeds the generator used for random replacement.
+    pub fn new(name: &'static str, config: CacheConfig, log_accesses: bool, seed: u64) -> Self {
+        let n_blocks = (config.size / config.block_size) as usize;
+        Self {
+            name,
+            config,
+            blocks: vec![Block::default(); n_blocks],
+            offset_bits: config.block_size.trailing_zeros(),
+            index_mask: config.n_sets() - 1,
+            clock: 0,
+            rng: Rng::new(seed),
+            log_accesses,
+            stats: CacheStats::default(),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn stats(&self) -> &CacheStats {
+        &self.stats
+    }
+
+    pub fn config(&self) -> &CacheConfig {
+        &self.config
+    }
+
+    /// Simulates accessing `size` bytes starting at `address`.
+    ///
+    /// Each block touched by the access is counted separately.
+    pub fn access(&mut self, address: Address, size: u32, kind: AccessKind) {
+        let first_block = address >> self.offset_bits;
+        let last_block = address.wrapping_add(size.saturating_sub(1)) >> self.offset_bits;
+        for block_address in first_block..=last_block {
+            let hit = self.access_block(block_address, kind);
+            if self.log_accesses {
+                grayln!(
+                    "[{}] {} 0x{:08x}: {}",
+                    self.name,
+                    match kind {
+                        AccessKind::Read => "read",
+                        AccessKind::Write => "write",
+                    },
+                    block_address << self.offset_bits,
+                    if hit { "hit" } else { "miss" },
+                );
+            }
+        }
+    }
+
+    /// Accesses the block numbered `block_address`, returning whether it was a hit.
+    fn access_block(&mut self, block_address: u32, kind: AccessKind) -> bool {
+        self.clock += 1;
+        let index = block_address & self.index_mask;
+        let tag = block_address >> self.index_mask.count_ones();
+        let associativity = self.config.associativity as usize;
+        let set_start = index as usize * associativity;
+        let set = &mut self.blocks[set_start..set_start + associativity];
+        let write_through = self.config.write_policy == WritePolicy::WriteThrough;
+
+        if let Some(block) = set.iter_mut().find(|block| block.valid && block.tag == tag) {
+            block.used_at = self.clock;
+            match kind {
+                AccessKind::Read => self.stats.read_hits += 1,
+                AccessKind::Write if write_through => {
+                    self.stats.write_hits += 1;
+                    self.stats.write_throughs += 1;
+                }
+                AccessKind::Write => {
+                    self.stats.write_hits += 1;
+                    block.dirty = true;
+                }
+            }
+            return true;
+        }
+
+        match kind {
+            AccessKind::Read => self.stats.read_misses += 1,
+            AccessKind::Write if write_through => {
+                // Write-through caches don't allocate blocks on store misses.
+                self.stats.write_misses += 1;
+                self.stats.write_throughs += 1;
+                return false;
+            }
+            AccessKind::Write => self.stats.write_misses += 1,
+        }
+        let victim = match set.iter().position(|block| !block.valid) {
+            Some(empty) => empty,
+            None => match self.config.replacement {
+                ReplacementPolicy::Lru => Self::oldest(set, |block| block.used_at),
+                ReplacementPolicy::Fifo => Self::oldest(set, |block| block.loaded_at),
+                ReplacementPolicy::Random => self
+                    .rng
+                    .next_u32_from_range(associativity as u64)
+                    .unwrap_or_default() as usize,
+            },
+        };
+        let block = &mut set[victim];
+        if block.valid && block.dirty {
+            self.stats.write_backs += 1;
+        }
+        *block = Block {
+            valid: true,
+            dirty: kind == AccessKind::Write,
+            tag,
+            loaded_at: self.clock,
+            used_at: self.clock,
+        };
+        false
+    }
+
+    /// Finds the index of the block in `set` with the smallest timestamp.
+    fn oldest<F>(set: &[Block], timestamp: F) -> usize
+    where
+        F: Fn(&Block) -> u64,
+    {
+        set.iter()
+            .enumerate()
+            .min_by_key(|(_, block)| timestamp(block))
+            .map_or(0, |(i, _)| i)
+    }
+}
@@ -1,8 +1,9 @@
 use crate::{
-    memory::{DataRegion, Region},
     Exception,
+    memory::{DataRegion, Region},
 };
 use seaside_type_aliases::Address;
+use std::ops::Range;
 
 pub struct DataMemory {
     r#extern: DataRegion,
@@ -129,10 +130,72 @@ impl DataMemory {
         }
     }
 
+    /// Replaces the contents of the kdata region with `bytes`.
+    pub fn load_kdata(&mut self, bytes: Vec<u8>) {
+        self.kdata.populate(bytes);
+    }
+
+    pub fn heap_addresses(&self) -> &Range<Address> {
+        &self.heap.addresses
+    }
+
+    pub fn stack_addresses(&self) -> &Range<Address> {
+        &self.stack.addresses
+    }
+
     pub fn used_heap_space(&self) -> u32 {
         self.heap.addresses.len() as u32 - self.free_heap_space
     }
 
+    /// Copies the contents of each data region, keyed by the address it starts at.
+    pub fn dump_regions(&self) -> Vec<(Address, Vec<u8>)> {
+        self.regions()
+            .into_iter()
+            .filter(|region| !region.addresses.is_empty())
+            .map(|region| (region.addresses.start, region.bytes().to_vec()))
+            .collect()
+    }
+
+    /// The size in bytes of the data region starting at `start`, or [`None`] if there is no such
+    /// region.
+    pub fn region_len(&self, start: Address) -> Option<usize> {
+        self.regions()
+            .into_iter()
+            .find(|region| region.addresses.start == start && !region.addresses.is_empty())
+            .map(|region| region.bytes().len())
+    }
+
+    /// Overwrites the data region starting at `start` with `bytes`, returning false if there is
+    /// no such region or `bytes` is the wrong size.
+    pub fn restore_region(&mut self, start: Address, bytes: &[u8]) -> bool {
+        self.regions_mut()
+            .into_iter()
+            .find(|region| region.addresses.start == start && !region.addresses.is_empty())
+            .is_some_and(|region| region.restore(bytes))
+    }
+
+    fn regions(&self) -> [&DataRegion; 6] {
+        [
+            &self.r#extern,
+            &self.data,
+            &self.heap,
+            &self.stack,
+            &self.kdata,
+            &self.mmio,
+        ]
+    }
+
+    fn regions_mut(&mut self) -> [&mut DataRegion; 6] {
+        [
+            &mut self.r#extern,
+            &mut self.data,
+            &mut self.heap,
+            &mut self.stack,
+            &mut self.kdata,
+            &mut self.mmio,
+        ]
+    }
+
     fn region_containing(&self, address: Address) -> Option<&DataRegion> {
         if self.r#extern.contains(address) {
             Some(&self.r#extern)
@@ -1,4 +1,5 @@
-use super::{DataMemory, DataRegion, InstructionMemory, Memory, TextRegion};
+use super::{Cache, DataMemory, DataRegion, InstructionMemory, Memory, TextRegion};
+use crate::rng::Rng;
 use anyhow::Result;
 use seaside_config::{
     Config,
@@ -32,10 +33,32 @@ impl Memory {
             init_data_region(&segments.kdata, kdata)?,
             init_data_region(&segments.mmio, None)?,
         );
+        let cache_options = &config.features.cache;
+        // In deterministic mode, the caches' generators are derived from the master seed like the
+        // program's are, using ids from the top of the range so they're unlikely to be shared.
+        let deterministic = &config.features.deterministic;
+        let cache_seed = |id| {
+            if deterministic.enabled {
+                Rng::derive_seed(deterministic.seed, id)
+            } else {
+                rand::random()
+            }
+        };
+        let new_cache =
+            |name, cache, id| Cache::new(name, cache, cache_options.log_accesses, cache_seed(id));
         Ok(Self {
             instruction_memory,
             data_memory,
             endian: config.endian,
+            instruction_cache: cache_options
+                .instruction
+                .map(|cache| new_cache("icache", cache, u32::MAX)),
+            data_cache: cache_options
+                .data
+                .map(|cache| new_cache("dcache", cache, u32::MAX - 1)),
+            journal: None,
+            memcheck: None,
+            text_writes: Vec::new(),
         })
     }
 }
@@ -1,5 +1,6 @@
 use super::{Region, TextRegion};
 use crate::Exception;
+use seaside_int_utils::{ByteStream, Endian};
 use seaside_type_aliases::Address;
 
 pub struct InstructionMemory {
@@ -126,7 +127,65 @@ impl InstructionMemory {
         self.text.addresses.start
     }
 
+    /// Copies the contents of each text region, keyed by the address it starts at.
+    ///
+    /// Instructions are stored as little-endian bytes.
+    pub fn dump_regions(&self) -> Vec<(Address, Vec<u8>)> {
+        [&self.text, &self.ktext]
+            .into_iter()
+            .filter(|region| !region.addresses.is_empty())
+            .map(|region| {
+                let bytes = region
+                    .instructions()
+                    .iter()
+                    .flat_map(|instruction| instruction.to_le_bytes())
+                    .collect();
+                (region.addresses.start, bytes)
+            })
+            .collect()
+    }
+
+    /// The size in bytes of the text region starting at `start` as produced by
+    /// [`InstructionMemory::dump_regions`], or [`None`] if there is no such region.
+    pub fn region_len(&self, start: Address) -> Option<usize> {
+        [&self.text, &self.ktext]
+            .into_iter()
+            .find(|region| region.addresses.start == start && !region.addresses.is_empty())
+            .map(|region| region.instructions().len() * 4)
+    }
+
+    /// Overwrites the text region starting at `start` with `bytes` as produced by
+    /// [`InstructionMemory::dump_regions`], returning false if there is no such region or `bytes`
+    /// is the wrong size.
+    pub fn restore_region(&mut self, start: Address, bytes: &[u8]) -> bool {
+        let instructions: Vec<u32> = ByteStream::<'_, u32>::new(bytes, Endian::Little).collect();
+        [&mut self.text, &mut self.ktext]
+            .into_iter()
+            .find(|region| region.addresses.start == start && !region.addresses.is_empty())
+            .is_some_and(|region| bytes.len() % 4 == 0 && region.restore(&instructions))
+    }
+
+    /// Replaces the contents of the ktext region with `instructions`, making `exception_handler`
+    /// the address exceptions are handled at.
+    pub fn load_ktext(&mut self, instructions: &[u32], exception_handler: Address) {
+        let bytes = instructions
+            .iter()
+            .flat_map(|instruction| instruction.to_le_bytes())
+            .collect();
+        self.ktext.populate(bytes, Endian::Little);
+        self.exception_handler = Some(exception_handler);
+    }
+
     pub fn pc_past_end(&self, pc: Address) -> bool {
+        // Within a text region, only that region's instructions count. Otherwise, a program that
+        // drops off the bottom of the text segment would run on into empty memory whenever there
+        // is a kernel.
+        if let Some(region) = [&self.text, &self.ktext]
+            .into_iter()
+            .find(|region| region.contains(pc))
+        {
+            return region.end_pc.is_none_or(|end_pc| pc >= end_pc);
+        }
         if let Some(text_end_pc) = self.text.end_pc {
             if pc < text_end_pc {
                 return false;
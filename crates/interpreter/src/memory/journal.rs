@@ -0,0 +1,45 @@
+use seaside_type_aliases::Address;
+
+/// The contents of memory just before a write, which is enough to undo it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum JournalEntry {
+    U8(Address, u8),
+    U16(Address, u16),
+    U32(Address, u32),
+    U64(Address, u64),
+    Bytes(Address, Vec<u8>),
+}
+
+impl JournalEntry {
+    pub fn address(&self) -> Address {
+        match self {
+            Self::U8(address, _)
+            | Self::U16(address, _)
+            | Self::U32(address, _)
+            | Self::U64(address, _)
+            | Self::Bytes(address, _) => *address,
+        }
+    }
+
+    /// The number of bytes overwritten.
+    pub fn len(&self) -> u32 {
+        match self {
+            Self::U8(..) => 1,
+            Self::U16(..) => 2,
+            Self::U32(..) => 4,
+            Self::U64(..) => 8,
+            Self::Bytes(_, bytes) => bytes.len() as u32,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Checks whether the write this entry records touched `address`.
+    pub fn covers(&self, address: Address) -> bool {
+        address
+            .checked_sub(self.address())
+            .is_some_and(|offset| offset < self.len())
+    }
+}
@@ -0,0 +1,224 @@
+use crate::symbols::SymbolTable;
+use core::fmt::{Display, Formatter, Result as FmtResult};
+use seaside_type_aliases::Address;
+use std::{collections::HashMap, io::Write, ops::Range};
+
+/// A way a program can misuse memory.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum MemCheckErrorKind {
+    /// Read heap or stack memory that was never written.
+    UninitializedRead,
+    /// Accessed heap memory that `sbrk` never handed out.
+    PastHeapBreak,
+    /// Accessed heap memory that was released by a negative `sbrk`.
+    ReleasedHeap,
+}
+
+impl Display for MemCheckErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::UninitializedRead => write!(f, "read of uninitialized memory"),
+            Self::PastHeapBreak => write!(f, "access past the heap break"),
+            Self::ReleasedHeap => write!(f, "access to released heap memory"),
+        }
+    }
+}
+
+/// A misuse of memory found by [`MemCheck`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MemCheckError {
+    pub kind: MemCheckErrorKind,
+    /// The address of the instruction that misused memory.
+    pub pc: Address,
+    /// The first address accessed the first time this error occurred.
+    pub address: Address,
+    /// The number of bytes accessed the first time this error occurred.
+    pub size: u32,
+    /// How many times the instruction made this error.
+    pub occurrences: u64,
+}
+
+/// Tracks a single bit for every byte in a range of addresses.
+struct Shadow {
+    addresses: Range<Address>,
+    bits: Vec<u64>,
+}
+
+impl Shadow {
+    fn new(addresses: &Range<Address>) -> Self {
+        Self {
+            addresses: addresses.clone(),
+            bits: vec![0; addresses.len().div_ceil(64)],
+        }
+    }
+
+    fn contains(&self, address: Address) -> bool {
+        self.addresses.contains(&address)
+    }
+
+    /// Sets the bits of the bytes in `addresses` that fall within this shadow to `value`.
+    fn set(&mut self, addresses: Range<Address>, value: bool) {
+        for offset in self.offsets(addresses) {
+            let (word, bit) = (offset as usize / 64, offset % 64);
+            if value {
+                self.bits[word] |= 1 << bit;
+            } else {
+                self.bits[word] &= !(1 << bit);
+            }
+        }
+    }
+
+    /// Checks whether the bits of every byte in `addresses` that falls within this shadow are set.
+    fn all_set(&self, addresses: Range<Address>) -> bool {
+        self.offsets(addresses)
+            .all(|offset| self.bits[offset as usize / 64] & (1 << (offset % 64)) != 0)
+    }
+
+    /// Gets the offsets into this shadow of the bytes in `addresses` that fall within it.
+    fn offsets(&self, addresses: Range<Address>) -> Range<u32> {
+        let start = addresses.start.max(self.addresses.start);
+        let end = addresses.end.min(self.addresses.end).max(start);
+        start - self.addresses.start..end - self.addresses.start
+    }
+}
+
+/// Shadows the heap and stack to catch reads of uninitialized memory and accesses to heap memory
+/// the program doesn't own.
+///
+/// Other regions are loaded from the assembled program or deliberately zero-filled, so they aren't
+/// checked.
+pub struct MemCheck {
+    /// The address of the instruction currently being executed.
+    pc: Address,
+    heap: Shadow,
+    stack: Shadow,
+    /// The end of the highest heap allocation `sbrk` has ever handed out.
+    heap_high_water: Address,
+    errors: Vec<MemCheckError>,
+    /// Maps each instruction and kind of error it made to the error's index in `errors`.
+    error_indices: HashMap<(Address, MemCheckErrorKind), usize>,
+}
+
+impl MemCheck {
+    pub fn new(heap: &Range<Address>, stack: &Range<Address>, heap_break: Address) -> Self {
+        Self {
+            pc: 0,
+            heap: Shadow::new(heap),
+            stack: Shadow::new(stack),
+            heap_high_water: heap_break,
+            errors: Vec::new(),
+            error_indices: HashMap::new(),
+        }
+    }
+
+    /// Attributes any errors found from now on to the instruction at `pc`.
+    pub fn set_pc(&mut self, pc: Address) {
+        self.pc = pc;
+    }
+
+    /// The errors found so far, in the order they first occurred.
+    pub fn errors(&self) -> &[MemCheckError] {
+        &self.errors
+    }
+
+    /// Marks `len` bytes starting at `address` as initialized.
+    pub fn mark_initialized(&mut self, address: Address, len: u32) {
+        let addresses = address..address.saturating_add(len);
+        self.heap.set(addresses.clone(), true);
+        self.stack.set(addresses, true);
+    }
+
+    /// Notes that `sbrk` handed out `len` bytes of fresh heap memory starting at `address`.
+    pub fn allocate(&mut self, address: Address, len: u32) {
+        let end = address.saturating_add(len);
+        self.heap.set(address..end, false);
+        self.heap_high_water = self.heap_high_water.max(end);
+    }
+
+    /// Checks a read of `size` bytes starting at `address`.
+    pub fn check_read(&mut self, address: Address, size: u32, heap_break: Address) {
+        let addresses = address..address.saturating_add(size);
+        if self.heap.contains(address) {
+            if self.check_heap(addresses.clone(), heap_break) && !self.heap.all_set(addresses) {
+                self.report(MemCheckErrorKind::UninitializedRead, address, size);
+            }
+        } else if self.stack.contains(address) && !self.stack.all_set(addresses) {
+            self.report(MemCheckErrorKind::UninitializedRead, address, size);
+        }
+    }
+
+    /// Checks a write of `size` bytes starting at `address`, then marks those bytes as
+    /// initialized.
+    pub fn check_write(&mut self, address: Address, size: u32, heap_break: Address) {
+        let addresses = address..address.saturating_add(size);
+        if self.heap.contains(address) {
+            self.check_heap(addresses.clone(), heap_break);
+        }
+        self.heap.set(addresses.clone(), true);
+        self.stack.set(addresses, true);
+    }
+
+    /// Writes every error found so far to `writer`.
+    ///
+    /// `symbols` is used to describe the instructions at fault in terms of the labels enclosing
+    /// them.
+    pub fn write_report<W>(&self, writer: &mut W, symbols: &SymbolTable) -> std::io::Result<()>
+    where
+        W: Write,
+    {
+        let total: u64 = self.errors.iter().map(|error| error.occurrences).sum();
+        writeln!(
+            writer,
+            "memcheck: {total} errors from {} instructions",
+            self.errors.len(),
+        )?;
+        for error in &self.errors {
+            write!(
+                writer,
+                "  {}: {} bytes at 0x{:08x} by {} (pc: 0x{:08x})",
+                error.kind,
+                error.size,
+                error.address,
+                symbols.describe(error.pc),
+                error.pc,
+            )?;
+            if error.occurrences > 1 {
+                write!(writer, ", {} times", error.occurrences)?;
+            }
+            writeln!(writer)?;
+        }
+        Ok(())
+    }
+
+    /// Reports any part of `addresses` that lies past `heap_break`, returning true if there was
+    /// none.
+    fn check_heap(&mut self, addresses: Range<Address>, heap_break: Address) -> bool {
+        if addresses.end <= heap_break {
+            return true;
+        }
+        let kind = if addresses.start.max(heap_break) < self.heap_high_water {
+            MemCheckErrorKind::ReleasedHeap
+        } else {
+            MemCheckErrorKind::PastHeapBreak
+        };
+        self.report(kind, addresses.start, addresses.len() as u32);
+        false
+    }
+
+    fn report(&mut self, kind: MemCheckErrorKind, address: Address, size: u32) {
+        match self.error_indices.get(&(self.pc, kind)) {
+            Some(&index) => self.errors[index].occurrences += 1,
+            None => {
+                self.error_indices
+                    .insert((self.pc, kind), self.errors.len());
+                self.errors.push(MemCheckError {
+                    kind,
+                    pc: self.pc,
+                    address,
+                    size,
+                    occurrences: 1,
+                });
+            }
+        }
+    }
+}
@@ -1,20 +1,37 @@
+pub mod cache;
 pub mod data_memory;
 pub mod init;
 pub mod instruction_memory;
+pub mod journal;
+pub mod memcheck;
 pub mod regions;
 
+pub use cache::{AccessKind, Cache, CacheStats};
 pub use data_memory::DataMemory;
 pub use instruction_memory::InstructionMemory;
+pub use journal::JournalEntry;
+pub use memcheck::{MemCheck, MemCheckError, MemCheckErrorKind};
 pub use regions::{DataRegion, Region, TextRegion};
 
 use crate::Exception;
+use core::cell::{Ref, RefCell};
 use seaside_int_utils::Endian;
 use seaside_type_aliases::{Address, Instruction};
+use std::ops::Range;
 
 pub struct Memory {
     instruction_memory: InstructionMemory,
     data_memory: DataMemory,
     endian: Endian,
+    instruction_cache: Option<Cache>,
+    data_cache: Option<Cache>,
+    /// The previous contents of memory overwritten since the journal was started, if it was.
+    journal: Option<Vec<JournalEntry>>,
+    // Loads only need shared access to memory, but they still update the memory checker.
+    memcheck: Option<RefCell<MemCheck>>,
+    /// The parts of instruction memory that may have been overwritten since
+    /// [`Memory::take_text_writes`] was last called, as pairs of addresses and lengths.
+    text_writes: Vec<(Address, u32)>,
 }
 
 impl Region for Memory {
@@ -26,24 +43,26 @@ impl Region for Memory {
         self.instruction_memory
             .read_u8(address)
             .or(self.data_memory.read_u8(address))
+            .inspect(|_| self.check_read(address, 1))
     }
 
     fn read_u16(&self, address: Address, assert_aligned: bool) -> Result<u16, Exception> {
         self.instruction_memory
             .read_u16(address, assert_aligned)
             .or(self.data_memory.read_u16(address, assert_aligned))
+            .inspect(|_| self.check_read(address, 2))
     }
 
     fn read_u32(&self, address: Address, assert_aligned: bool) -> Result<u32, Exception> {
-        self.instruction_memory
-            .read_u32(address, assert_aligned)
-            .or(self.data_memory.read_u32(address, assert_aligned))
+        self.read_u32_raw(address, assert_aligned)
+            .inspect(|_| self.check_read(address, 4))
     }
 
     fn read_u64(&self, address: Address, assert_aligned: bool) -> Result<u64, Exception> {
         self.instruction_memory
             .read_u64(address, assert_aligned)
             .or(self.data_memory.read_u64(address, assert_aligned))
+            .inspect(|_| self.check_read(address, 8))
     }
 
     fn get_slice(&self, address: Address) -> Result<&[u8], Exception> {
@@ -61,9 +80,12 @@ impl Region for Memory {
     }
 
     fn write_u8(&mut self, address: Address, value: u8) -> Result<(), Exception> {
+        self.journal_write(address, 1);
+        self.record_text_write(address, 1);
         self.instruction_memory
             .write_u8(address, value)
             .or(self.data_memory.write_u8(address, value))
+            .inspect(|_| self.check_write(address, 1))
     }
 
     fn write_u16(
@@ -72,9 +94,12 @@ impl Region for Memory {
         value: u16,
         assert_aligned: bool,
     ) -> Result<(), Exception> {
+        self.journal_write(address, 2);
+        self.record_text_write(address, 2);
         self.instruction_memory
             .write_u16(address, value, assert_aligned)
             .or(self.data_memory.write_u16(address, value, assert_aligned))
+            .inspect(|_| self.check_write(address, 2))
     }
 
     fn write_u32(
@@ -83,9 +108,8 @@ impl Region for Memory {
         value: u32,
         assert_aligned: bool,
     ) -> Result<(), Exception> {
-        self.instruction_memory
-            .write_u32(address, value, assert_aligned)
-            .or(self.data_memory.write_u32(address, value, assert_aligned))
+        self.write_u32_raw(address, value, assert_aligned)
+            .inspect(|_| self.check_write(address, 4))
     }
 
     fn write_u64(
@@ -94,9 +118,12 @@ impl Region for Memory {
         value: u64,
         assert_aligned: bool,
     ) -> Result<(), Exception> {
+        self.journal_write(address, 8);
+        self.record_text_write(address, 8);
         self.instruction_memory
             .write_u64(address, value, assert_aligned)
             .or(self.data_memory.write_u64(address, value, assert_aligned))
+            .inspect(|_| self.check_write(address, 8))
     }
 }
 
@@ -105,10 +132,222 @@ impl Memory {
         self.endian
     }
 
+    /// Reads a word like [`Region::read_u32`], but without telling the memory checker.
+    ///
+    /// This is for instructions that only use some of the word's bytes. They should pass those
+    /// bytes to [`Memory::check_read`] themselves.
+    pub fn read_u32_raw(&self, address: Address, assert_aligned: bool) -> Result<u32, Exception> {
+        self.instruction_memory
+            .read_u32(address, assert_aligned)
+            .or(self.data_memory.read_u32(address, assert_aligned))
+    }
+
+    /// Writes a word like [`Region::write_u32`], but without telling the memory checker.
+    ///
+    /// This is for instructions that only change some of the word's bytes. They should pass those
+    /// bytes to [`Memory::check_write`] themselves.
+    pub fn write_u32_raw(
+        &mut self,
+        address: Address,
+        value: u32,
+        assert_aligned: bool,
+    ) -> Result<(), Exception> {
+        self.journal_write(address, 4);
+        self.record_text_write(address, 4);
+        self.instruction_memory
+            .write_u32(address, value, assert_aligned)
+            .or(self.data_memory.write_u32(address, value, assert_aligned))
+    }
+
     pub fn get_instruction(&self, pc: Address) -> Result<Instruction, Exception> {
         self.instruction_memory.read_u32(pc, true)
     }
 
+    /// Gets the instruction at `pc` like [`Memory::get_instruction`], but also simulates fetching
+    /// it through the instruction cache.
+    pub fn fetch_instruction(&mut self, pc: Address) -> Result<Instruction, Exception> {
+        let instruction = self.get_instruction(pc)?;
+        self.simulate_fetch(pc);
+        Ok(instruction)
+    }
+
+    /// Simulates fetching the instruction at `pc` through the instruction cache without reading
+    /// it, for callers that already know what it is.
+    pub fn simulate_fetch(&mut self, pc: Address) {
+        if let Some(cache) = &mut self.instruction_cache {
+            cache.access(pc, 4, AccessKind::Read);
+        }
+    }
+
+    pub fn instruction_cache(&self) -> Option<&Cache> {
+        self.instruction_cache.as_ref()
+    }
+
+    pub fn data_cache(&self) -> Option<&Cache> {
+        self.data_cache.as_ref()
+    }
+
+    /// Simulates a load or store of `size` bytes at `address` through the data cache.
+    ///
+    /// Only load and store instructions call this, so memory touched by system services or
+    /// scripts doesn't count towards the cache's statistics.
+    pub fn simulate_data_access(&mut self, address: Address, size: u32, kind: AccessKind) {
+        if let Some(cache) = &mut self.data_cache {
+            cache.access(address, size, kind);
+        }
+    }
+
+    /// Starts recording the previous contents of memory before each write.
+    pub fn start_journal(&mut self) {
+        self.journal = Some(Vec::new());
+    }
+
+    /// Stops recording writes, returning everything recorded since [`Memory::start_journal`] was
+    /// called.
+    pub fn take_journal(&mut self) -> Vec<JournalEntry> {
+        self.journal.take().unwrap_or_default()
+    }
+
+    /// Prepares for up to `len` bytes starting at `address` to be overwritten.
+    ///
+    /// The bytes are recorded if a journal is being kept, and marked as initialized if the memory
+    /// checker is enabled. This must be called before writing to memory through
+    /// [`Region::get_slice_mut`], since those writes can't be tracked automatically.
+    pub fn prepare_slice_write(&mut self, address: Address, len: usize) {
+        if !self.data_memory.contains(address) {
+            self.record_text_write(address, len.try_into().unwrap_or(u32::MAX));
+        }
+        if let Some(memcheck) = &mut self.memcheck {
+            memcheck
+                .get_mut()
+                .mark_initialized(address, len.try_into().unwrap_or(u32::MAX));
+        }
+        let Some(journal) = &mut self.journal else {
+            return;
+        };
+        if let Ok(slice) = self.data_memory.get_slice(address) {
+            let len = usize::min(len, slice.len());
+            journal.push(JournalEntry::Bytes(address, slice[..len].to_vec()));
+        }
+    }
+
+    /// Restores the contents of memory recorded in `entry`.
+    ///
+    /// This bypasses the caches and isn't recorded in the journal.
+    pub fn undo(&mut self, entry: &JournalEntry) {
+        self.record_text_write(entry.address(), entry.len());
+        let text = &mut self.instruction_memory;
+        let data = &mut self.data_memory;
+        let _ = match *entry {
+            JournalEntry::U8(address, old) => text
+                .write_u8(address, old)
+                .or_else(|_| data.write_u8(address, old)),
+            JournalEntry::U16(address, old) => text
+                .write_u16(address, old, false)
+                .or_else(|_| data.write_u16(address, old, false)),
+            JournalEntry::U32(address, old) => text
+                .write_u32(address, old, false)
+                .or_else(|_| data.write_u32(address, old, false)),
+            JournalEntry::U64(address, old) => text
+                .write_u64(address, old, false)
+                .or_else(|_| data.write_u64(address, old, false)),
+            JournalEntry::Bytes(address, ref old) => data
+                .get_slice_mut(address)
+                .map(|slice| slice[..old.len()].copy_from_slice(old)),
+        };
+    }
+
+    /// Takes the parts of instruction memory that may have been overwritten since this was last
+    /// called, as pairs of addresses and lengths.
+    ///
+    /// Anything derived from the instructions in those parts of memory is out of date.
+    pub fn take_text_writes(&mut self) -> Vec<(Address, u32)> {
+        core::mem::take(&mut self.text_writes)
+    }
+
+    fn record_text_write(&mut self, address: Address, len: u32) {
+        if self.instruction_memory.contains(address) {
+            self.text_writes.push((address, len));
+        }
+    }
+
+    /// Starts checking loads and stores to the heap and stack for misuse.
+    ///
+    /// Only memory written from now on counts as initialized, except for the stack at and above
+    /// `sp`, which holds anything placed there before the program started (e.g., its arguments).
+    pub fn enable_memcheck(&mut self, sp: Address) {
+        let stack = self.data_memory.stack_addresses();
+        let mut memcheck = MemCheck::new(
+            self.data_memory.heap_addresses(),
+            stack,
+            self.data_memory.next_heap_address,
+        );
+        memcheck.mark_initialized(sp, stack.end.saturating_sub(sp));
+        self.memcheck = Some(RefCell::new(memcheck));
+    }
+
+    pub fn memcheck(&self) -> Option<Ref<'_, MemCheck>> {
+        self.memcheck.as_ref().map(RefCell::borrow)
+    }
+
+    pub fn memcheck_mut(&mut self) -> Option<&mut MemCheck> {
+        self.memcheck.as_mut().map(RefCell::get_mut)
+    }
+
+    /// Tells the memory checker, if it's enabled, that `size` bytes were loaded from `address`.
+    pub fn check_read(&self, address: Address, size: u32) {
+        if let Some(memcheck) = &self.memcheck {
+            memcheck
+                .borrow_mut()
+                .check_read(address, size, self.data_memory.next_heap_address);
+        }
+    }
+
+    /// Tells the memory checker, if it's enabled, that `size` bytes were stored at `address`.
+    pub fn check_write(&self, address: Address, size: u32) {
+        if let Some(memcheck) = &self.memcheck {
+            memcheck
+                .borrow_mut()
+                .check_write(address, size, self.data_memory.next_heap_address);
+        }
+    }
+
+    fn journal_write(&mut self, address: Address, size: u32) {
+        let Some(journal) = &mut self.journal else {
+            return;
+        };
+        let text = &self.instruction_memory;
+        let data = &self.data_memory;
+        let entry = match size {
+            1 => text
+                .read_u8(address)
+                .or_else(|_| data.read_u8(address))
+                .map(|old| JournalEntry::U8(address, old)),
+            2 => text
+                .read_u16(address, false)
+                .or_else(|_| data.read_u16(address, false))
+                .map(|old| JournalEntry::U16(address, old)),
+            4 => text
+                .read_u32(address, false)
+                .or_else(|_| data.read_u32(address, false))
+                .map(|old| JournalEntry::U32(address, old)),
+            _ => text
+                .read_u64(address, false)
+                .or_else(|_| data.read_u64(address, false))
+                .map(|old| JournalEntry::U64(address, old)),
+        };
+        if let Ok(entry) = entry {
+            journal.push(entry);
+        }
+    }
+
+    /// Replaces the contents of the ktext and kdata segments, making `exception_handler` the
+    /// address exceptions are handled at.
+    pub fn load_kernel(&mut self, ktext: &[u32], kdata: Vec<u8>, exception_handler: Address) {
+        self.instruction_memory.load_ktext(ktext, exception_handler);
+        self.data_memory.load_kdata(kdata);
+    }
+
     pub fn get_exception_handler(&self) -> Option<Address> {
         self.instruction_memory.exception_handler
     }
@@ -121,6 +360,33 @@ impl Memory {
         self.instruction_memory.pc_past_end(pc)
     }
 
+    /// Copies the contents of every region, keyed by the address it starts at.
+    pub fn dump_regions(&self) -> Vec<(Address, Vec<u8>)> {
+        let mut regions = self.instruction_memory.dump_regions();
+        regions.extend(self.data_memory.dump_regions());
+        regions
+    }
+
+    /// The size in bytes of the region starting at `start` as produced by
+    /// [`Memory::dump_regions`], or [`None`] if there is no such region.
+    pub fn region_len(&self, start: Address) -> Option<usize> {
+        self.instruction_memory
+            .region_len(start)
+            .or_else(|| self.data_memory.region_len(start))
+    }
+
+    /// Overwrites the region starting at `start` with `bytes` as produced by
+    /// [`Memory::dump_regions`], returning false if there is no such region or `bytes` is the
+    /// wrong size.
+    pub fn restore_region(&mut self, start: Address, bytes: &[u8]) -> bool {
+        if self.instruction_memory.restore_region(start, bytes) {
+            self.record_text_write(start, bytes.len().try_into().unwrap_or(u32::MAX));
+            true
+        } else {
+            self.data_memory.restore_region(start, bytes)
+        }
+    }
+
     pub fn free_heap_space(&self) -> &u32 {
         &self.data_memory.free_heap_space
     }
@@ -133,6 +399,10 @@ impl Memory {
         self.data_memory.used_heap_space()
     }
 
+    pub fn stack_addresses(&self) -> &Range<Address> {
+        self.data_memory.stack_addresses()
+    }
+
     pub fn next_heap_address(&self) -> &Address {
         &self.data_memory.next_heap_address
     }
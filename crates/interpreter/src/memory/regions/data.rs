@@ -152,6 +152,19 @@ impl DataRegion {
         }
     }
 
+    pub fn bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Overwrites every byte in the region, returning false if `bytes` doesn't exactly fill it.
+    pub fn restore(&mut self, bytes: &[u8]) -> bool {
+        if bytes.len() != self.data.len() {
+            return false;
+        }
+        self.data.copy_from_slice(bytes);
+        true
+    }
+
     fn calculate_index(&self, address: Address, alignment: u32) -> Option<usize> {
         if is_aligned(address, alignment) && self.contains(address) {
             Some(self.calculate_index_unchecked(address))
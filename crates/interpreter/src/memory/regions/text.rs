@@ -99,6 +99,20 @@ impl TextRegion {
         self.end_pc = Some((self.num_instructions << 2) as u32 + self.addresses.start);
     }
 
+    pub fn instructions(&self) -> &[u32] {
+        &self.instructions
+    }
+
+    /// Overwrites every instruction in the region, returning false if `instructions` doesn't
+    /// exactly fill it.
+    pub fn restore(&mut self, instructions: &[u32]) -> bool {
+        if instructions.len() != self.instructions.len() {
+            return false;
+        }
+        self.instructions.copy_from_slice(instructions);
+        true
+    }
+
     fn calculate_index(&self, address: Address, assert_aligned: bool) -> Option<usize> {
         if (!assert_aligned || is_aligned(address, 4)) && self.contains(address) {
             let index = (address - self.addresses.start) >> 2;
@@ -0,0 +1,315 @@
+//! Recording for the MIDI out services.
+//!
+//! Provides the struct [`MidiRecorder`], which keeps track of the notes a program plays instead of
+//! playing them, so they can be written to a Standard MIDI File or synthesized to a WAV file once
+//! the program finishes.
+
+use std::{
+    collections::HashMap,
+    f64::consts::TAU,
+    fs::File,
+    io::{BufWriter, Result, Write},
+    path::Path,
+};
+
+/// The sample rate of synthesized WAV files, in hertz.
+const SAMPLE_RATE: u32 = 44_100;
+
+/// How long synthesized notes take to fade in and out, in milliseconds.
+const FADE_MILLIS: f64 = 10.0;
+
+/// The longest a single note is synthesized for, in milliseconds.
+const MAX_NOTE_MILLIS: u64 = 60_000;
+
+/// The longest a synthesized WAV file can be, in milliseconds. Anything later is cut off.
+const MAX_WAV_MILLIS: u64 = 600_000;
+
+/// The number of samples synthesized before they're written out.
+const CHUNK_SAMPLES: usize = 4096;
+
+/// A note played by the program.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Note {
+    /// When the note started, in milliseconds on the virtual clock.
+    pub start: u64,
+    /// The pitch of the note, where 60 is middle C.
+    pub pitch: u8,
+    /// How long the note lasts, in milliseconds.
+    pub duration: u32,
+    /// The General MIDI instrument (program) that plays the note.
+    pub instrument: u8,
+    pub volume: u8,
+}
+
+impl Note {
+    /// Creates a note from the arguments of a MIDI out service, replacing out-of-range arguments
+    /// with MARS's defaults.
+    pub fn new(start: u64, pitch: u32, duration: i32, instrument: u32, volume: u32) -> Self {
+        Self {
+            start,
+            pitch: u8::try_from(pitch)
+                .ok()
+                .filter(|&pitch| pitch < 128)
+                .unwrap_or(60),
+            duration: u32::try_from(duration).unwrap_or(1000),
+            instrument: u8::try_from(instrument)
+                .ok()
+                .filter(|&instrument| instrument < 128)
+                .unwrap_or(0),
+            volume: u8::try_from(volume)
+                .ok()
+                .filter(|&volume| volume < 128)
+                .unwrap_or(100),
+        }
+    }
+
+    /// When the note ends, in milliseconds on the virtual clock.
+    pub fn end(&self) -> u64 {
+        self.start + self.duration as u64
+    }
+
+    /// The frequency of the note in hertz, assuming A4 is tuned to 440 Hz.
+    pub fn frequency(&self) -> f64 {
+        440.0 * 2f64.powf((self.pitch as f64 - 69.0) / 12.0)
+    }
+}
+
+/// Records the notes played by the program against a virtual clock.
+///
+/// The clock only moves when the program waits, either by playing a note synchronously or by
+/// sleeping, so recordings don't depend on how fast the program runs.
+#[derive(Clone, Debug, Default)]
+pub struct MidiRecorder {
+    notes: Vec<Note>,
+    /// The current time in milliseconds.
+    clock: u64,
+}
+
+impl MidiRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The notes played so far, in the order they were played.
+    pub fn notes(&self) -> &[Note] {
+        &self.notes
+    }
+
+    /// Starts playing a note without waiting for it to finish.
+    pub fn play(&mut self, pitch: u32, duration: i32, instrument: u32, volume: u32) {
+        self.notes
+            .push(Note::new(self.clock, pitch, duration, instrument, volume));
+    }
+
+    /// Plays a note and waits for it to finish.
+    pub fn play_sync(&mut self, pitch: u32, duration: i32, instrument: u32, volume: u32) {
+        self.play(pitch, duration, instrument, volume);
+        self.clock = self.notes.last().map_or(self.clock, Note::end);
+    }
+
+    /// Moves the clock forward by `millis` milliseconds.
+    pub fn wait(&mut self, millis: u32) {
+        self.clock += millis as u64;
+    }
+
+    /// The number of notes played so far and the current time, which [`MidiRecorder::rewind`] can
+    /// return to.
+    pub fn position(&self) -> (usize, u64) {
+        (self.notes.len(), self.clock)
+    }
+
+    /// Forgets the notes played since `position` was taken and moves the clock back to it.
+    pub fn rewind(&mut self, (n_notes, clock): (usize, u64)) {
+        self.notes.truncate(n_notes);
+        self.clock = clock;
+    }
+
+    /// Writes the notes to `path`, synthesizing them if the file ends in `.wav` and writing a
+    /// Standard MIDI File otherwise.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        if path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("wav"))
+        {
+            self.write_wav(&mut writer)?;
+        } else {
+            self.write_smf(&mut writer)?;
+        }
+        writer.flush()
+    }
+
+    /// Writes the notes as a format 0 Standard MIDI File.
+    ///
+    /// Each tick lasts one millisecond. Every instrument gets its own channel, skipping the
+    /// percussion channel, until there are no channels left and they start being shared.
+    pub fn write_smf<W>(&self, writer: &mut W) -> Result<()>
+    where
+        W: Write,
+    {
+        // Events are sorted by time, then so that notes end before new ones start.
+        let mut events: Vec<(u64, u8, [u8; 3])> = Vec::with_capacity(self.notes.len() * 3);
+        let mut channels: HashMap<u8, u8> = HashMap::new();
+        for note in &self.notes {
+            let slot = if channels.len() < 15 {
+                channels.len() as u8
+            } else {
+                note.instrument % 15
+            };
+            // Channel 9 is reserved for percussion.
+            let channel = if slot < 9 { slot } else { slot + 1 };
+            let channel = *channels.entry(note.instrument).or_insert(channel);
+            events.push((note.start, 1, [0xc0 | channel, note.instrument, 0]));
+            events.push((note.start, 2, [0x90 | channel, note.pitch, note.volume]));
+            events.push((note.end(), 0, [0x80 | channel, note.pitch, 0]));
+        }
+        events.sort_by_key(|&(time, order, _)| (time, order));
+
+        // One tick per millisecond: 1000 ticks per quarter note at 1,000,000 us per quarter note.
+        let mut track: Vec<u8> = vec![0x00, 0xff, 0x51, 0x03, 0x0f, 0x42, 0x40];
+        let mut last_time = 0;
+        for (time, _, event) in events {
+            write_variable_length(&mut track, (time - last_time) as u32);
+            // Program changes only take one data byte.
+            let len = if event[0] & 0xf0 == 0xc0 { 2 } else { 3 };
+            track.extend_from_slice(&event[..len]);
+            last_time = time;
+        }
+        track.extend_from_slice(&[0x00, 0xff, 0x2f, 0x00]);
+
+        writer.write_all(b"MThd")?;
+        writer.write_all(&6u32.to_be_bytes())?;
+        writer.write_all(&0u16.to_be_bytes())?;
+        writer.write_all(&1u16.to_be_bytes())?;
+        writer.write_all(&1000u16.to_be_bytes())?;
+        writer.write_all(b"MTrk")?;
+        writer.write_all(&(track.len() as u32).to_be_bytes())?;
+        writer.write_all(&track)
+    }
+
+    /// Synthesizes the notes into a 16-bit mono WAV file.
+    ///
+    /// Every instrument is rendered as a sine wave, since the point is to hear the melody rather
+    /// than to imitate the instruments. Notes are cut short after [`MAX_NOTE_MILLIS`], and the
+    /// file after [`MAX_WAV_MILLIS`].
+    pub fn write_wav<W>(&self, writer: &mut W) -> Result<()>
+    where
+        W: Write,
+    {
+        let millis_to_samples =
+            |millis: u64| (millis.min(MAX_WAV_MILLIS) * SAMPLE_RATE as u64 / 1000) as usize;
+        let rendered_end = |note: &Note| {
+            note.start
+                .saturating_add((note.duration as u64).min(MAX_NOTE_MILLIS))
+        };
+        // The first sample and number of samples of each note, in order of when they start.
+        let mut notes: Vec<(usize, usize, &Note)> = self
+            .notes
+            .iter()
+            .map(|note| {
+                let start = millis_to_samples(note.start);
+                (start, millis_to_samples(rendered_end(note)) - start, note)
+            })
+            .filter(|&(_, len, _)| len > 0)
+            .collect();
+        notes.sort_by_key(|&(start, ..)| start);
+        let total = notes
+            .iter()
+            .map(|&(start, len, _)| start + len)
+            .max()
+            .unwrap_or(0);
+
+        let data_len = (total * 2) as u32;
+        writer.write_all(b"RIFF")?;
+        writer.write_all(&(36 + data_len).to_le_bytes())?;
+        writer.write_all(b"WAVEfmt ")?;
+        writer.write_all(&16u32.to_le_bytes())?;
+        writer.write_all(&1u16.to_le_bytes())?; // PCM
+        writer.write_all(&1u16.to_le_bytes())?; // mono
+        writer.write_all(&SAMPLE_RATE.to_le_bytes())?;
+        writer.write_all(&(SAMPLE_RATE * 2).to_le_bytes())?;
+        writer.write_all(&2u16.to_le_bytes())?;
+        writer.write_all(&16u16.to_le_bytes())?;
+        writer.write_all(b"data")?;
+        writer.write_all(&data_len.to_le_bytes())?;
+
+        // Samples are mixed a chunk at a time, from the notes that overlap the chunk.
+        let mut mix = vec![0.0f64; CHUNK_SAMPLES];
+        let mut bytes: Vec<u8> = Vec::with_capacity(CHUNK_SAMPLES * 2);
+        let mut active: Vec<(usize, usize, &Note)> = Vec::new();
+        let mut next = 0;
+        for chunk_start in (0..total).step_by(CHUNK_SAMPLES) {
+            let chunk_end = (chunk_start + CHUNK_SAMPLES).min(total);
+            while let Some(&note) = notes.get(next).filter(|&&(start, ..)| start < chunk_end) {
+                active.push(note);
+                next += 1;
+            }
+            active.retain(|&(start, len, _)| start + len > chunk_start);
+            let mix = &mut mix[..chunk_end - chunk_start];
+            mix.fill(0.0);
+            for &(start, len, note) in &active {
+                let fade = (FADE_MILLIS * SAMPLE_RATE as f64 / 1000.0).min(len as f64 / 2.0);
+                let amplitude = 0.25 * note.volume as f64 / 127.0;
+                let step = TAU * note.frequency() / SAMPLE_RATE as f64;
+                for position in start.max(chunk_start)..(start + len).min(chunk_end) {
+                    let i = position - start;
+                    let envelope = (i as f64 / fade).min((len - i) as f64 / fade).min(1.0);
+                    mix[position - chunk_start] += amplitude * envelope * (step * i as f64).sin();
+                }
+            }
+            bytes.clear();
+            for &sample in mix.iter() {
+                let sample = (sample.clamp(-1.0, 1.0) * i16::MAX as f64) as i16;
+                bytes.extend_from_slice(&sample.to_le_bytes());
+            }
+            writer.write_all(&bytes)?;
+        }
+        Ok(())
+    }
+}
+
+/// Appends `value` to `bytes` as a MIDI variable-length quantity.
+fn write_variable_length(bytes: &mut Vec<u8>, value: u32) {
+    let mut groups = vec![(value & 0x7f) as u8];
+    let mut value = value >> 7;
+    while value != 0 {
+        groups.push((value & 0x7f) as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.extend(groups.iter().rev());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MAX_NOTE_MILLIS, MidiRecorder, SAMPLE_RATE};
+
+    /// Reads the length of the sample data from a WAV header.
+    fn data_len(wav: &[u8]) -> usize {
+        u32::from_le_bytes(wav[40..44].try_into().unwrap()) as usize
+    }
+
+    #[test]
+    fn write_wav_cuts_long_notes_short() {
+        let mut recorder = MidiRecorder::new();
+        recorder.play(60, i32::MAX, 0, 100);
+        let mut wav = Vec::new();
+        recorder.write_wav(&mut wav).unwrap();
+        let samples = (MAX_NOTE_MILLIS * SAMPLE_RATE as u64 / 1000) as usize;
+        assert_eq!(data_len(&wav), samples * 2);
+        assert_eq!(wav.len(), 44 + samples * 2);
+    }
+
+    #[test]
+    fn write_wav_mixes_overlapping_notes() {
+        let mut recorder = MidiRecorder::new();
+        recorder.play(60, 100, 0, 100);
+        recorder.wait(50);
+        recorder.play_sync(64, 100, 0, 100);
+        let mut wav = Vec::new();
+        recorder.write_wav(&mut wav).unwrap();
+        let samples = (150 * SAMPLE_RATE / 1000) as usize;
+        assert_eq!(data_len(&wav), samples * 2);
+        assert_eq!(wav.len(), 44 + samples * 2);
+        assert!(wav[44..].iter().any(|&byte| byte != 0));
+    }
+}
@@ -0,0 +1,584 @@
+//! Pipeline timing model.
+//!
+//! Provides the struct [`Pipeline`], which replays the instructions executed by the interpreter
+//! through a classic five-stage (IF/ID/EX/MEM/WB) pipeline to estimate how many cycles they would
+//! take.
+//!
+//! The model assumes every stage takes a single cycle, that branches are predicted not taken, and
+//! that instructions on the wrong path are flushed without side effects. Only the instructions
+//! that were actually executed are replayed, so flushed instructions show up as branch penalties
+//! rather than as rows of their own.
+
+use num_traits::FromPrimitive;
+use seaside_config::features::pipeline::{BranchResolution, PipelineOptions};
+use seaside_constants::{
+    Opcode,
+    fn_codes::{
+        Coprocessor0Fn, Coprocessor1Fn, Coprocessor1RegisterImmediateFn, Coprocessor1XFn,
+        RegisterImmediateFn, Special2Fn, Special3Fn, SpecialFn,
+    },
+};
+use seaside_disassembler::{disassemble, fields};
+use seaside_type_aliases::{Address, Instruction};
+use std::io::Write;
+
+/// How many instructions to include in the pipeline diagram.
+const MAX_DIAGRAM_ROWS: usize = 32;
+
+/// The index of the first floating-point register in [`Pipeline::ready_at`].
+pub(crate) const FPU_BASE: u8 = 32;
+pub(crate) const HI: u8 = 64;
+pub(crate) const LO: u8 = 65;
+/// The condition flags set by `c.cond` and read by `bc1t`, `bc1f` and `movt`/`movf`, along with
+/// the rest of FCSR as seen by `cfc1` and `ctc1`.
+const FCC: u8 = 66;
+const N_RESOURCES: usize = 67;
+
+const RETURN_ADDR: u8 = 31;
+
+/// Counts what happened to the instructions replayed through a [`Pipeline`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct PipelineStats {
+    pub instructions: u64,
+    /// The cycle in which the last instruction left the pipeline.
+    pub cycles: u64,
+    /// Cycles lost waiting on the result of a load.
+    pub load_use_stalls: u64,
+    /// Cycles lost waiting on the result of any other instruction.
+    pub data_stalls: u64,
+    /// Cycles lost flushing instructions fetched after a taken branch or jump.
+    pub branch_penalties: u64,
+    /// The number of branches and jumps that changed the flow of execution.
+    pub taken_branches: u64,
+    /// The number of operands that had to be forwarded because they weren't written back in time.
+    pub forwarded_operands: u64,
+}
+
+impl PipelineStats {
+    /// The average number of cycles per instruction.
+    pub fn cpi(&self) -> f64 {
+        match self.instructions {
+            0 => 0.0,
+            instructions => self.cycles as f64 / instructions as f64,
+        }
+    }
+}
+
+/// Replays executed instructions through a five-stage pipeline.
+#[derive(Clone, Debug)]
+pub struct Pipeline {
+    forwarding: bool,
+    branch_resolution: BranchResolution,
+    /// When the previous instruction entered each stage.
+    previous: Option<Timing>,
+    /// The earliest cycle in which the next instruction can be fetched.
+    redirect: u64,
+    /// When the latest value of each register becomes available.
+    ready_at: [Ready; N_RESOURCES],
+    stats: PipelineStats,
+    /// The first few instructions replayed, if a diagram was requested.
+    diagram: Option<Vec<DiagramRow>>,
+}
+
+/// The cycles in which an instruction entered each stage of the pipeline.
+#[derive(Clone, Copy, Debug)]
+struct Timing {
+    fetch: u64,
+    decode: u64,
+    execute: u64,
+    memory: u64,
+    write_back: u64,
+}
+
+/// When a register's latest value becomes available to later instructions.
+#[derive(Clone, Copy, Debug, Default)]
+struct Ready {
+    /// The first cycle in which the value can be forwarded to the stage that needs it.
+    forwarded: u64,
+    /// The first cycle in which the value can be read from the register file.
+    written_back: u64,
+    /// Whether the value is produced by a load.
+    from_load: bool,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct DiagramRow {
+    pc: Address,
+    instruction: Instruction,
+    timing: Timing,
+}
+
+/// How an instruction affects the flow of the pipeline.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+enum Kind {
+    #[default]
+    Other,
+    /// Produces its result in MEM rather than EX.
+    Load,
+    /// Changes the pc unconditionally in ID.
+    Jump,
+    /// Changes the pc depending on its operands, which are examined in the stage chosen by
+    /// [`BranchResolution`].
+    Branch,
+}
+
+/// The registers an instruction reads and writes.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct Operands {
+    kind: Kind,
+    reads: [Option<u8>; 4],
+    writes: [Option<u8>; 2],
+}
+
+impl Pipeline {
+    pub fn new(options: &PipelineOptions) -> Self {
+        Self {
+            forwarding: options.forwarding,
+            branch_resolution: options.branch_resolution,
+            previous: None,
+            redirect: 0,
+            ready_at: [Ready::default(); N_RESOURCES],
+            stats: PipelineStats::default(),
+            diagram: options.diagram.then(Vec::new),
+        }
+    }
+
+    pub fn stats(&self) -> &PipelineStats {
+        &self.stats
+    }
+
+    /// Replays `instruction`, which was executed at `pc`, after which execution continued at
+    /// `next_pc`.
+    pub fn record(&mut self, pc: Address, instruction: Instruction, next_pc: Address) {
+        let operands = Operands::of(instruction);
+        let previous = self.previous.unwrap_or(Timing {
+            fetch: 0,
+            decode: 1,
+            execute: 0,
+            memory: 0,
+            write_back: 0,
+        });
+
+        let fetch_base = previous.decode;
+        let fetch = fetch_base.max(self.redirect);
+        self.stats.branch_penalties += fetch - fetch_base;
+
+        // Without forwarding, every operand is read from the register file in ID. With it,
+        // operands are needed at the start of EX, except by branches resolved in ID.
+        let reads_in_decode = !self.forwarding
+            || (operands.kind == Kind::Branch
+                && self.branch_resolution == BranchResolution::Decode);
+        let (needed, from_load) = operands
+            .reads()
+            .map(|register| self.ready_at[register as usize])
+            .fold((0, false), |(needed, from_load), ready| {
+                let ready_cycle = if self.forwarding {
+                    ready.forwarded
+                } else {
+                    ready.written_back
+                };
+                if ready_cycle > needed {
+                    (ready_cycle, ready.from_load)
+                } else {
+                    (needed, from_load)
+                }
+            });
+
+        let decode_base = (fetch + 1).max(previous.execute);
+        let decode = if reads_in_decode {
+            decode_base.max(needed)
+        } else {
+            decode_base
+        };
+        let execute_base = (decode + 1).max(previous.memory);
+        let execute = if reads_in_decode {
+            execute_base
+        } else {
+            execute_base.max(needed)
+        };
+        let stalls = (decode - decode_base) + (execute - execute_base);
+        if from_load {
+            self.stats.load_use_stalls += stalls;
+        } else {
+            self.stats.data_stalls += stalls;
+        }
+        if self.forwarding {
+            let forwarded = operands
+                .reads()
+                .filter(|&register| self.ready_at[register as usize].written_back > decode)
+                .count();
+            self.stats.forwarded_operands += forwarded as u64;
+        }
+        let memory = (execute + 1).max(previous.write_back);
+        let write_back = memory + 1;
+        let timing = Timing {
+            fetch,
+            decode,
+            execute,
+            memory,
+            write_back,
+        };
+
+        for register in operands.writes() {
+            self.ready_at[register as usize] = Ready {
+                forwarded: if operands.kind == Kind::Load {
+                    memory + 1
+                } else {
+                    execute + 1
+                },
+                written_back: write_back,
+                from_load: operands.kind == Kind::Load,
+            };
+        }
+        if next_pc != pc.wrapping_add(4) {
+            let resolved_in = match (operands.kind, self.branch_resolution) {
+                (Kind::Jump, _) | (Kind::Branch, BranchResolution::Decode) => Some(decode),
+                (Kind::Branch, BranchResolution::Execute) => Some(execute),
+                _ => None,
+            };
+            if let Some(cycle) = resolved_in {
+                self.redirect = cycle + 1;
+                self.stats.taken_branches += 1;
+            }
+        }
+
+        self.stats.instructions += 1;
+        self.stats.cycles = write_back;
+        self.previous = Some(timing);
+        if let Some(diagram) = &mut self.diagram {
+            if diagram.len() < MAX_DIAGRAM_ROWS {
+                diagram.push(DiagramRow {
+                    pc,
+                    instruction,
+                    timing,
+                });
+            }
+        }
+    }
+
+    /// Writes the cycle count, CPI and a breakdown of stalls to `writer`, followed by the
+    /// pipeline diagram if one was requested.
+    pub fn write_report<W>(&self, writer: &mut W) -> std::io::Result<()>
+    where
+        W: Write,
+    {
+        let stats = &self.stats;
+        writeln!(
+            writer,
+            "pipeline: {} instructions in {} cycles (CPI {:.3})",
+            stats.instructions,
+            stats.cycles,
+            stats.cpi(),
+        )?;
+        writeln!(
+            writer,
+            "  forwarding {}, branches resolved in {}",
+            if self.forwarding {
+                "enabled"
+            } else {
+                "disabled"
+            },
+            match self.branch_resolution {
+                BranchResolution::Decode => "ID",
+                BranchResolution::Execute => "EX",
+            },
+        )?;
+        writeln!(
+            writer,
+            "  data hazard stalls: {} load-use, {} other",
+            stats.load_use_stalls, stats.data_stalls,
+        )?;
+        writeln!(
+            writer,
+            "  branch penalties: {} cycles over {} taken branches and jumps",
+            stats.branch_penalties, stats.taken_branches,
+        )?;
+        write!(writer, "  forwarded operands: {}", stats.forwarded_operands)?;
+        if let Some(diagram) = &self.diagram {
+            writeln!(writer)?;
+            self.write_diagram(writer, diagram)?;
+        }
+        writeln!(writer)
+    }
+
+    /// Writes a table with a row per instruction and a column per cycle, showing which stage each
+    /// instruction occupied in each cycle.
+    ///
+    /// A stage name marks the cycle an instruction entered that stage, and a dash marks a cycle it
+    /// spent stalled there.
+    fn write_diagram<W>(&self, writer: &mut W, diagram: &[DiagramRow]) -> std::io::Result<()>
+    where
+        W: Write,
+    {
+        let last_cycle = diagram
+            .iter()
+            .map(|row| row.timing.write_back)
+            .max()
+            .unwrap_or_default();
+        write!(writer, "\n  {:<32}", "cycle")?;
+        for cycle in 1..=last_cycle {
+            write!(writer, "{cycle:>4}")?;
+        }
+        for row in diagram {
+            let text = disassemble(row.instruction).unwrap_or_else(|| String::from("<unknown>"));
+            write!(writer, "\n  {:08x} {text:<23}", row.pc)?;
+            let Timing {
+                fetch,
+                decode,
+                execute,
+                memory,
+                write_back,
+            } = row.timing;
+            for cycle in 1..=last_cycle {
+                let cell = match cycle {
+                    _ if cycle < fetch => "",
+                    _ if cycle == fetch => "IF",
+                    _ if cycle == decode => "ID",
+                    _ if cycle == execute => "EX",
+                    _ if cycle == memory => "MEM",
+                    _ if cycle == write_back => "WB",
+                    _ if cycle < write_back => "-",
+                    _ => "",
+                };
+                write!(writer, "{cell:>4}")?;
+            }
+        }
+        if self.stats.instructions > diagram.len() as u64 {
+            write!(
+                writer,
+                "\n  ({} more instructions not shown)",
+                self.stats.instructions - diagram.len() as u64,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Operands {
+    /// Determines which registers `instruction` reads and writes.
+    ///
+    /// System calls are assumed to read `$v0` and `$a0`-`$a2` and to write `$v0`.
+    pub(crate) fn of(instruction: Instruction) -> Self {
+        use Opcode::*;
+        let rs = fields::rs_raw(instruction);
+        let rt = fields::rt_raw(instruction);
+        let rd = fields::rd_raw(instruction);
+        let operands = Self::default();
+        let Some(opcode) = fields::opcode(instruction) else {
+            return operands;
+        };
+        match opcode {
+            Special => Self::of_special(instruction),
+            RegisterImmediate => {
+                use RegisterImmediateFn::*;
+                match RegisterImmediateFn::from_u8(rt) {
+                    Some(BranchLessThanZero | BranchGreaterEqualZero) => {
+                        operands.kind(Kind::Branch).read(rs)
+                    }
+                    Some(BranchLessThanZeroAndLink | BranchGreaterEqualZeroAndLink) => {
+                        operands.kind(Kind::Branch).read(rs).write(RETURN_ADDR)
+                    }
+                    Some(_) => operands.read(rs),
+                    None => operands,
+                }
+            }
+            Jump => operands.kind(Kind::Jump),
+            JumpAndLink => operands.kind(Kind::Jump).write(RETURN_ADDR),
+            BranchEqual | BranchNotEqual | BranchEqualLikely | BranchNotEqualLikely => {
+                operands.kind(Kind::Branch).read(rs).read(rt)
+            }
+            BranchLessEqualZero
+            | BranchGreaterThanZero
+            | BranchLessEqualZeroLikely
+            | BranchGreaterThanZeroLikely => operands.kind(Kind::Branch).read(rs),
+            AddImmediate
+            | AddImmediateUnsigned
+            | SetLessThanImmediate
+            | SetLessThanImmediateUnsigned
+            | AndImmediate
+            | OrImmediate
+            | XorImmediate => operands.read(rs).write(rt),
+            LoadUpperImmediate => operands.write(rt),
+            Coprocessor0 => match Coprocessor0Fn::from_u8(rs) {
+                Some(Coprocessor0Fn::MoveFromCoprocessor0) => operands.write(rt),
+                Some(Coprocessor0Fn::MoveToCoprocessor0) => operands.read(rt),
+                Some(Coprocessor0Fn::ErrorReturn) => operands.kind(Kind::Jump),
+                None => operands,
+            },
+            Coprocessor1 => Self::of_coprocessor_1(instruction),
+            Coprocessor1X => Self::of_coprocessor_1x(instruction),
+            Special2 => {
+                use Special2Fn::*;
+                match Special2Fn::from_u8(fields::r#fn(instruction)) {
+                    Some(
+                        MultiplyAdd
+                        | MultiplyAddUnsigned
+                        | MultiplySubtract
+                        | MultiplySubtractUnsigned,
+                    ) => operands
+                        .read(rs)
+                        .read(rt)
+                        .read(HI)
+                        .read(LO)
+                        .write(HI)
+                        .write(LO),
+                    Some(Multiply) => operands.read(rs).read(rt).write(rd),
+                    Some(CountLeadingZeroes | CountLeadingOnes) => operands.read(rs).write(rd),
+                    None => operands,
+                }
+            }
+            Special3 => match Special3Fn::from_u8(fields::r#fn(instruction)) {
+                Some(Special3Fn::Extract) => operands.read(rs).write(rt),
+                Some(Special3Fn::Insert) => operands.read(rs).read(rt).write(rt),
+                Some(Special3Fn::ByteShuffle) => operands.read(rt).write(rd),
+                None => operands,
+            },
+            LoadByte | LoadHalf | LoadWord | LoadByteUnsigned | LoadHalfUnsigned | LoadLinked => {
+                operands.kind(Kind::Load).read(rs).write(rt)
+            }
+            LoadWordLeft | LoadWordRight => operands.kind(Kind::Load).read(rs).read(rt).write(rt),
+            LoadWordCoprocessor1 | LoadDoubleCoprocessor1 => {
+                operands.kind(Kind::Load).read(rs).write(FPU_BASE + rt)
+            }
+            StoreByte | StoreHalf | StoreWordLeft | StoreWord | StoreWordRight => {
+                operands.read(rs).read(rt)
+            }
+            StoreConditional => operands.kind(Kind::Load).read(rs).read(rt).write(rt),
+            StoreWordCoprocessor1 | StoreDoubleCoprocessor1 => {
+                operands.read(rs).read(FPU_BASE + rt)
+            }
+        }
+    }
+
+    fn of_special(instruction: Instruction) -> Self {
+        use SpecialFn::*;
+        let rs = fields::rs_raw(instruction);
+        let rt = fields::rt_raw(instruction);
+        let rd = fields::rd_raw(instruction);
+        let operands = Self::default();
+        let Some(r#fn) = SpecialFn::from_u8(fields::r#fn(instruction)) else {
+            return operands;
+        };
+        match r#fn {
+            ShiftLeftLogical | ShiftRightLogical | ShiftRightArithmetic => {
+                operands.read(rt).write(rd)
+            }
+            MoveConditional => operands.read(rs).read(FCC).read(rd).write(rd),
+            MoveZero | MoveNotZero => operands.read(rs).read(rt).read(rd).write(rd),
+            JumpRegister => operands.kind(Kind::Branch).read(rs),
+            JumpAndLinkRegister => operands.kind(Kind::Branch).read(rs).write(rd),
+            SystemCall => operands.read(2).read(4).read(5).read(6).write(2),
+            Break => operands,
+            MoveFromHigh => operands.read(HI).write(rd),
+            MoveToHigh => operands.read(rs).write(HI),
+            MoveFromLow => operands.read(LO).write(rd),
+            MoveToLow => operands.read(rs).write(LO),
+            Multiply | MultiplyUnsigned | Divide | DivideUnsigned => {
+                operands.read(rs).read(rt).write(HI).write(LO)
+            }
+            TrapGreaterEqual
+            | TrapGreaterEqualUnsigned
+            | TrapLessThan
+            | TrapLessThanUnsigned
+            | TrapEqual
+            | TrapNotEqual => operands.read(rs).read(rt),
+            _ => operands.read(rs).read(rt).write(rd),
+        }
+    }
+
+    fn of_coprocessor_1(instruction: Instruction) -> Self {
+        use Coprocessor1Fn::*;
+        let rt = fields::rt_raw(instruction);
+        let fs = FPU_BASE + fields::rd_raw(instruction);
+        let ft = FPU_BASE + rt;
+        let fd = FPU_BASE + fields::shamt(instruction);
+        let operands = Self::default();
+        match Coprocessor1RegisterImmediateFn::from_u8(fields::fmt(instruction)) {
+            Some(Coprocessor1RegisterImmediateFn::MoveFromCoprocessor1) => {
+                return operands.read(fs).write(rt);
+            }
+            Some(Coprocessor1RegisterImmediateFn::MoveToCoprocessor1) => {
+                return operands.read(rt).write(fs);
+            }
+            Some(Coprocessor1RegisterImmediateFn::ControlFromCoprocessor1) => {
+                return operands.read(FCC).write(rt);
+            }
+            Some(Coprocessor1RegisterImmediateFn::ControlToCoprocessor1) => {
+                return operands.read(rt).write(FCC);
+            }
+            Some(Coprocessor1RegisterImmediateFn::BranchCoprocessor1Flag) => {
+                return operands.kind(Kind::Branch).read(FCC);
+            }
+            None => {}
+        }
+        let Some(r#fn) = Coprocessor1Fn::from_u8(fields::r#fn(instruction)) else {
+            return operands;
+        };
+        match r#fn {
+            Add | Subtract | Multiply | Divide => operands.read(fs).read(ft).write(fd),
+            MoveConditional => operands.read(fs).read(FCC).read(fd).write(fd),
+            MoveZero | MoveNotZero => operands.read(fs).read(rt).read(fd).write(fd),
+            CompareEqual | CompareLessThan | CompareLessEqual => {
+                operands.read(fs).read(ft).write(FCC)
+            }
+            _ => operands.read(fs).write(fd),
+        }
+    }
+
+    fn of_coprocessor_1x(instruction: Instruction) -> Self {
+        use Coprocessor1XFn::*;
+        let base = fields::rs_raw(instruction);
+        let index = fields::rt_raw(instruction);
+        let fr = FPU_BASE + base;
+        let ft = FPU_BASE + index;
+        let fs = FPU_BASE + fields::rd_raw(instruction);
+        let fd = FPU_BASE + fields::shamt(instruction);
+        let operands = Self::default();
+        let Some(r#fn) = Coprocessor1XFn::from_u8(fields::r#fn(instruction)) else {
+            return operands;
+        };
+        match r#fn {
+            LoadWordIndexed | LoadDoubleIndexed => {
+                operands.kind(Kind::Load).read(base).read(index).write(fd)
+            }
+            StoreWordIndexed | StoreDoubleIndexed => operands.read(base).read(index).read(fs),
+            PrefetchIndexed => operands.read(base).read(index),
+            _ => operands.read(fr).read(fs).read(ft).write(fd),
+        }
+    }
+
+    const fn kind(mut self, kind: Kind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Adds `register` to the registers read, ignoring `$zero`.
+    fn read(mut self, register: u8) -> Self {
+        if register == 0 {
+            return self;
+        }
+        if let Some(slot) = self.reads.iter_mut().find(|slot| slot.is_none()) {
+            *slot = Some(register);
+        }
+        self
+    }
+
+    /// Adds `register` to the registers written, ignoring `$zero`.
+    fn write(mut self, register: u8) -> Self {
+        if register == 0 {
+            return self;
+        }
+        if let Some(slot) = self.writes.iter_mut().find(|slot| slot.is_none()) {
+            *slot = Some(register);
+        }
+        self
+    }
+
+    pub(crate) fn reads(&self) -> impl Iterator<Item = u8> {
+        self.reads.into_iter().flatten()
+    }
+
+    pub(crate) fn writes(&self) -> impl Iterator<Item = u8> {
+        self.writes.into_iter().flatten()
+    }
+}
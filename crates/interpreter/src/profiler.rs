@@ -0,0 +1,317 @@
+//! Execution statistics.
+//!
+//! Provides the struct [`Profiler`], which records every instruction the interpreter executes and
+//! summarizes them once the program finishes.
+
+use crate::{Memory, symbols::SymbolTable};
+use num_traits::FromPrimitive;
+use seaside_constants::{
+    InstructionFormat, Opcode,
+    fn_codes::{Coprocessor1RegisterImmediateFn, Coprocessor1XFn, RegisterImmediateFn, SpecialFn},
+    register::CpuRegister,
+};
+use seaside_disassembler::{fields, mnemonic};
+use seaside_type_aliases::{Address, Instruction};
+use std::{collections::HashMap, io::Write};
+
+/// How many rows to show in each table of the report.
+const MAX_ROWS: usize = 10;
+
+/// How many times a branch was taken and not taken.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct BranchCounts {
+    pub taken: u64,
+    pub not_taken: u64,
+}
+
+/// Records the instructions executed by the interpreter.
+#[derive(Clone, Debug, Default)]
+pub struct Profiler {
+    /// How many times the instruction at each address was executed.
+    instruction_counts: HashMap<Address, u64>,
+    /// How often each branch was taken, keyed by the address of the branch.
+    branch_counts: HashMap<Address, BranchCounts>,
+    /// The number of load instructions executed.
+    loads: u64,
+    /// The number of store instructions executed.
+    stores: u64,
+    /// The entry point of each procedure in the current call chain, starting from the program's
+    /// entry point.
+    call_stack: Vec<Address>,
+    /// How many instructions were executed with each call chain.
+    folded_stacks: HashMap<Vec<Address>, u64>,
+}
+
+impl Profiler {
+    pub fn new(entry_point: Address) -> Self {
+        Self {
+            call_stack: vec![entry_point],
+            ..Default::default()
+        }
+    }
+
+    pub fn total_instructions(&self) -> u64 {
+        self.instruction_counts.values().sum()
+    }
+
+    /// Records that `instruction` was executed at `pc`, after which execution continued at
+    /// `next_pc`.
+    pub fn record(&mut self, pc: Address, instruction: Instruction, next_pc: Address) {
+        use Opcode::*;
+        *self.instruction_counts.entry(pc).or_default() += 1;
+        match self.folded_stacks.get_mut(self.call_stack.as_slice()) {
+            Some(count) => *count += 1,
+            None => {
+                self.folded_stacks.insert(self.call_stack.clone(), 1);
+            }
+        }
+
+        let Some(opcode) = fields::opcode(instruction) else {
+            return;
+        };
+        match opcode {
+            Special => match SpecialFn::from_u8(fields::r#fn(instruction)) {
+                Some(SpecialFn::JumpAndLinkRegister) => self.call_stack.push(next_pc),
+                Some(SpecialFn::JumpRegister)
+                    if fields::rs(instruction) == CpuRegister::ReturnAddr
+                        && self.call_stack.len() > 1 =>
+                {
+                    self.call_stack.pop();
+                }
+                _ => {}
+            },
+            RegisterImmediate => {
+                use RegisterImmediateFn::*;
+                match RegisterImmediateFn::from_u8(fields::rt_raw(instruction)) {
+                    Some(BranchLessThanZero | BranchGreaterEqualZero) => {
+                        self.record_branch(pc, next_pc);
+                    }
+                    Some(BranchLessThanZeroAndLink | BranchGreaterEqualZeroAndLink) => {
+                        if self.record_branch(pc, next_pc) {
+                            self.call_stack.push(next_pc);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            JumpAndLink => self.call_stack.push(next_pc),
+            BranchEqual
+            | BranchNotEqual
+            | BranchLessEqualZero
+            | BranchGreaterThanZero
+            | BranchEqualLikely
+            | BranchNotEqualLikely
+            | BranchLessEqualZeroLikely
+            | BranchGreaterThanZeroLikely => {
+                self.record_branch(pc, next_pc);
+            }
+            Coprocessor1
+                if fields::fmt(instruction)
+                    == Coprocessor1RegisterImmediateFn::BranchCoprocessor1Flag as u8 =>
+            {
+                self.record_branch(pc, next_pc);
+            }
+            Coprocessor1X => match Coprocessor1XFn::from_u8(fields::r#fn(instruction)) {
+                Some(Coprocessor1XFn::LoadWordIndexed | Coprocessor1XFn::LoadDoubleIndexed) => {
+                    self.loads += 1;
+                }
+                Some(Coprocessor1XFn::StoreWordIndexed | Coprocessor1XFn::StoreDoubleIndexed) => {
+                    self.stores += 1;
+                }
+                _ => {}
+            },
+            LoadByte
+            | LoadHalf
+            | LoadWordLeft
+            | LoadWord
+            | LoadByteUnsigned
+            | LoadHalfUnsigned
+            | LoadWordRight
+            | LoadLinked
+            | LoadWordCoprocessor1
+            | LoadDoubleCoprocessor1 => {
+                self.loads += 1;
+            }
+            StoreByte
+            | StoreHalf
+            | StoreWordLeft
+            | StoreWord
+            | StoreWordRight
+            | StoreConditional
+            | StoreWordCoprocessor1
+            | StoreDoubleCoprocessor1 => {
+                self.stores += 1;
+            }
+            _ => {}
+        }
+    }
+
+    /// Records whether the branch at `pc` was taken, returning true if it was.
+    fn record_branch(&mut self, pc: Address, next_pc: Address) -> bool {
+        let counts = self.branch_counts.entry(pc).or_default();
+        let taken = next_pc != pc.wrapping_add(4);
+        if taken {
+            counts.taken += 1;
+        } else {
+            counts.not_taken += 1;
+        }
+        taken
+    }
+
+    /// Writes a human-readable summary of the recorded statistics to `writer`.
+    ///
+    /// `memory` is used to look up the instructions that were executed, and `symbols` is used to
+    /// attribute them to the labels enclosing them.
+    pub fn write_report<W>(
+        &self,
+        writer: &mut W,
+        memory: &Memory,
+        symbols: &SymbolTable,
+    ) -> std::io::Result<()>
+    where
+        W: Write,
+    {
+        let total = self.total_instructions();
+        let mut by_label: HashMap<&str, u64> = HashMap::new();
+        let mut by_format: HashMap<InstructionFormat, u64> = HashMap::new();
+        let mut by_operation: HashMap<String, u64> = HashMap::new();
+        for (&address, &count) in &self.instruction_counts {
+            let label = symbols
+                .enclosing(address)
+                .map_or("<unlabeled>", |(label, _)| label);
+            *by_label.entry(label).or_default() += count;
+            let Ok(instruction) = memory.get_instruction(address) else {
+                continue;
+            };
+            if let Some(opcode) = fields::opcode(instruction) {
+                *by_format
+                    .entry(InstructionFormat::from(opcode))
+                    .or_default() += count;
+            }
+            let operation = mnemonic(instruction).unwrap_or_else(|| String::from("<unknown>"));
+            *by_operation.entry(operation).or_default() += count;
+        }
+
+        writeln!(writer, "executed {total} instructions")?;
+        writeln!(writer, "loads: {}, stores: {}", self.loads, self.stores)?;
+
+        writeln!(writer, "\ntop functions:")?;
+        writeln!(
+            writer,
+            "  {:<24} {:>12} {:>8}",
+            "label", "instructions", "share"
+        )?;
+        for (label, count) in sorted_by_count(by_label).into_iter().take(MAX_ROWS) {
+            writeln!(
+                writer,
+                "  {label:<24} {count:>12} {:>7.2}%",
+                percentage(count, total),
+            )?;
+        }
+
+        writeln!(writer, "\ninstruction mix by format:")?;
+        for (format, count) in sorted_by_count(by_format) {
+            writeln!(
+                writer,
+                "  {:<24} {count:>12} {:>7.2}%",
+                format!("{format:?}"),
+                percentage(count, total),
+            )?;
+        }
+
+        writeln!(writer, "\ninstruction mix by operation:")?;
+        for (operation, count) in sorted_by_count(by_operation).into_iter().take(MAX_ROWS) {
+            writeln!(
+                writer,
+                "  {operation:<24} {count:>12} {:>7.2}%",
+                percentage(count, total),
+            )?;
+        }
+
+        let (taken, not_taken) = self
+            .branch_counts
+            .values()
+            .fold((0, 0), |(taken, not_taken), counts| {
+                (taken + counts.taken, not_taken + counts.not_taken)
+            });
+        writeln!(
+            writer,
+            "\nbranches: {taken} taken, {not_taken} not taken ({:.2}% taken)",
+            percentage(taken, taken + not_taken),
+        )?;
+        let mut branches: Vec<(Address, BranchCounts)> = self
+            .branch_counts
+            .iter()
+            .map(|(&pc, &counts)| (pc, counts))
+            .collect();
+        branches.sort_by(|(pc_a, a), (pc_b, b)| {
+            (b.taken + b.not_taken)
+                .cmp(&(a.taken + a.not_taken))
+                .then(pc_a.cmp(pc_b))
+        });
+        writeln!(
+            writer,
+            "  {:<24} {:>12} {:>12} {:>8}",
+            "branch", "taken", "not taken", "taken %"
+        )?;
+        for (pc, counts) in branches.into_iter().take(MAX_ROWS) {
+            writeln!(
+                writer,
+                "  {:<24} {:>12} {:>12} {:>7.2}%",
+                symbols.describe(pc),
+                counts.taken,
+                counts.not_taken,
+                percentage(counts.taken, counts.taken + counts.not_taken),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Writes the recorded call chains to `writer` in the folded-stack format understood by most
+    /// flamegraph tools.
+    ///
+    /// Each line lists the procedures in a call chain from outermost to innermost, separated by
+    /// semicolons, followed by a space and the number of instructions executed in it.
+    pub fn write_folded_stacks<W>(
+        &self,
+        writer: &mut W,
+        symbols: &SymbolTable,
+    ) -> std::io::Result<()>
+    where
+        W: Write,
+    {
+        let mut lines: Vec<(String, u64)> = self
+            .folded_stacks
+            .iter()
+            .map(|(stack, &count)| {
+                let frames: Vec<String> = stack
+                    .iter()
+                    .map(|&address| symbols.describe(address))
+                    .collect();
+                (frames.join(";"), count)
+            })
+            .collect();
+        lines.sort();
+        for (stack, count) in lines {
+            writeln!(writer, "{stack} {count}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Gets `part` as a percentage of `whole`, or 0 if `whole` is 0.
+pub(crate) fn percentage(part: u64, whole: u64) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 * 100.0 / whole as f64
+    }
+}
+
+/// Sorts `counts` from most to least common, breaking ties by key so the order doesn't depend on
+/// the map's iteration order.
+fn sorted_by_count<K: Ord>(counts: HashMap<K, u64>) -> Vec<(K, u64)> {
+    let mut counts: Vec<(K, u64)> = counts.into_iter().collect();
+    counts.sort_by(|(key_a, a), (key_b, b)| b.cmp(a).then(key_a.cmp(key_b)));
+    counts
+}
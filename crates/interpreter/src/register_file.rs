@@ -1,4 +1,4 @@
-use crate::Exception;
+use crate::{Exception, fpu::Fcsr};
 use core::{
     fmt::{Display, Formatter, Result as FmtResult},
     iter::zip,
@@ -12,13 +12,13 @@ use seaside_constants::{
 use seaside_type_aliases::Address;
 use strum::IntoEnumIterator;
 
-#[derive(Default)]
+#[derive(Clone, Default)]
 pub struct RegisterFile {
     cpu: [u32; 32],
     pub hi: u32,
     pub lo: u32,
     fpu: [f32; 32],
-    fpu_flags: u8,
+    pub fcsr: Fcsr,
     pub vaddr: Address,
     pub status: u32,
     pub cause: u32,
@@ -132,16 +132,15 @@ impl TryIndexByRegister<FpuRegister, i64> for RegisterFile {
 }
 
 impl RegisterFile {
+    /// The number of words needed to hold every register.
+    pub const N_WORDS: usize = 71;
+
     pub fn read_fpu_flag(&self, cc: ConditionCode) -> bool {
-        (self.fpu_flags >> cc as u8) & 1 == 1
+        self.fcsr.condition(cc)
     }
 
     pub fn write_fpu_flag(&mut self, cc: ConditionCode, value: bool) {
-        let index = cc as u8;
-        let mask = 1 << index;
-        let value = if value { mask } else { 0 };
-        self.fpu_flags &= !mask;
-        self.fpu_flags |= value;
+        self.fcsr.set_condition(cc, value);
     }
 
     pub fn init(register_defaults: &RegisterDefaults) -> Self {
@@ -165,6 +164,42 @@ impl RegisterFile {
         register_file.epc = register_defaults.coprocessor_0[3];
         register_file
     }
+
+    /// Packs every register into a flat array of words, in the order CPU, `hi`, `lo`, FPU, FCSR,
+    /// `vaddr`, `status`, `cause` and `epc`.
+    pub fn to_words(&self) -> [u32; Self::N_WORDS] {
+        let mut words = [0; Self::N_WORDS];
+        words[..32].copy_from_slice(&self.cpu);
+        words[32] = self.hi;
+        words[33] = self.lo;
+        for (word, value) in zip(&mut words[34..66], self.fpu) {
+            *word = value.to_bits();
+        }
+        words[66] = self.fcsr.bits();
+        words[67] = self.vaddr;
+        words[68] = self.status;
+        words[69] = self.cause;
+        words[70] = self.epc;
+        words
+    }
+
+    /// Unpacks registers from an array produced by [`RegisterFile::to_words`].
+    pub fn from_words(words: &[u32; Self::N_WORDS]) -> Self {
+        let mut register_file = Self::default();
+        register_file.cpu.copy_from_slice(&words[..32]);
+        register_file.cpu[0] = 0;
+        register_file.hi = words[32];
+        register_file.lo = words[33];
+        for (value, &word) in zip(&mut register_file.fpu, &words[34..66]) {
+            *value = f32::from_bits(word);
+        }
+        register_file.fcsr = Fcsr::from_bits(words[66]);
+        register_file.vaddr = words[67];
+        register_file.status = words[68];
+        register_file.cause = words[69];
+        register_file.epc = words[70];
+        register_file
+    }
 }
 
 impl Display for RegisterFile {
@@ -177,7 +212,7 @@ impl Display for RegisterFile {
             f,
             "┠┈┈┈┈┈┈┈┈┈┈┈┈┈┈┈┈┈┈┈┈ Flags ┈┈┈┈┈┈┈┈┈┈┈┈┈┈┈┈┈┈┈┈┨\n┃    "
         )?;
-        write_fpu_flags(self.fpu_flags, f)?;
+        write_fpu_flags(self.fcsr.conditions(), f)?;
         writeln!(
             f,
             "    ┃\n┗━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━┛"
@@ -13,6 +13,28 @@ impl Rng {
         random
     }
 
+    /// Derives the seed of generator `id` from `master_seed`.
+    ///
+    /// This is a SplitMix64 step, so neighbouring ids get unrelated seeds.
+    pub fn derive_seed(master_seed: u64, id: u32) -> u64 {
+        let mut z = master_seed.wrapping_add((id as u64 + 1).wrapping_mul(0x9e3779b97f4a7c15));
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Recreates a generator from the internal state reported by [`Rng::state`].
+    pub fn from_state(state: u64) -> Self {
+        Self {
+            seed: state & Self::MAX_SEED,
+        }
+    }
+
+    /// Gets the internal state of the generator, which differs from the seed it was created with.
+    pub fn state(&self) -> u64 {
+        self.seed
+    }
+
     pub fn set_seed(&mut self, seed: u64) {
         self.seed = (seed ^ Self::MAGIC) & Self::MAX_SEED;
     }
@@ -0,0 +1,838 @@
+//! Sandboxing for the file services.
+//!
+//! Provides the enum [`Sandbox`], which decides where the paths programs give to file services
+//! lead, and the struct [`VirtualFileSystem`], which lets programs use files without touching the
+//! host at all.
+
+use crate::file_handle::{FileHandle, FileStatus, OpenMode};
+use anyhow::{Context, Result, bail};
+use seaside_config::features::{SandboxMode, SandboxOptions};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, VecDeque},
+    ffi::OsStr,
+    fs,
+    io::{self, Error, ErrorKind},
+    path::{Component, Path, PathBuf, absolute},
+    rc::Rc,
+    time::SystemTime,
+};
+
+/// The size of a block in a tar archive.
+const TAR_BLOCK: usize = 512;
+
+/// The largest a file in a [`VirtualFileSystem`] can grow, and so the furthest its offset can be
+/// moved.
+pub const MAX_VIRTUAL_FILE_SIZE: u64 = 0x400_0000; // 64 MiB
+
+/// Where the paths given to file services lead.
+#[derive(Debug, Default)]
+pub enum Sandbox {
+    /// Paths refer to the host file system as is.
+    #[default]
+    Off,
+    /// Paths are relative to this absolute directory and can't leave it.
+    Jail(PathBuf),
+    /// Paths refer to a file system that only exists in memory.
+    Memory(VirtualFileSystem),
+}
+
+impl Sandbox {
+    /// Sets up the sandbox described by `options`, loading the virtual file system's initial
+    /// contents if necessary.
+    ///
+    /// Loaded files are given `now` as their modification time.
+    pub fn init(options: &SandboxOptions, now: SystemTime) -> Result<Self> {
+        Ok(match (options.mode, &options.root) {
+            (SandboxMode::Off, _) => Self::Off,
+            (SandboxMode::Jail, Some(root)) => {
+                let root = absolute(root)?;
+                if !root.is_dir() {
+                    bail!("sandbox root {} is not a directory", root.display());
+                }
+                // Paths are checked against the root once their symbolic links are resolved, so
+                // the root has to be resolved too.
+                Self::Jail(root.canonicalize()?)
+            }
+            // The config validation makes sure a jail has a root, so this never happens.
+            (SandboxMode::Jail, None) => bail!("jail sandbox is missing a root"),
+            (SandboxMode::Memory, root) => {
+                let mut vfs = VirtualFileSystem::new();
+                if let Some(root) = root {
+                    vfs.load(root, now).with_context(|| {
+                        format!("failed to load sandbox contents from {}", root.display())
+                    })?;
+                }
+                Self::Memory(vfs)
+            }
+        })
+    }
+
+    /// The virtual file system, if the sandbox keeps files in memory.
+    pub fn vfs(&self) -> Option<&VirtualFileSystem> {
+        match self {
+            Self::Memory(vfs) => Some(vfs),
+            _ => None,
+        }
+    }
+
+    /// Opens the file at `path` using MARS-style `flags`.
+    ///
+    /// Virtual files that are created or truncated are given `now` as their modification time.
+    pub(crate) fn open(
+        &mut self,
+        path: &Path,
+        flags: u32,
+        now: SystemTime,
+    ) -> io::Result<FileHandle> {
+        match self {
+            Self::Off => FileHandle::open(path.to_path_buf(), flags),
+            Self::Jail(root) => FileHandle::open(jailed(root, path)?, flags),
+            Self::Memory(vfs) => vfs.open(path, OpenMode::from_mars_flags(flags), 0o644, now),
+        }
+    }
+
+    /// Opens the file at `path` using POSIX-style `flags`, creating it with the permissions in
+    /// `mode` if necessary.
+    ///
+    /// Virtual files that are created or truncated are given `now` as their modification time.
+    pub(crate) fn open_posix(
+        &mut self,
+        path: &Path,
+        flags: u32,
+        mode: u32,
+        now: SystemTime,
+    ) -> io::Result<FileHandle> {
+        match self {
+            Self::Off => FileHandle::open_posix(path.to_path_buf(), flags, mode),
+            Self::Jail(root) => FileHandle::open_posix(jailed(root, path)?, flags, mode),
+            Self::Memory(vfs) => vfs.open(path, OpenMode::from_posix_flags(flags), mode, now),
+        }
+    }
+
+    /// Opens the directory at `path` so its entries can be read.
+    pub(crate) fn open_directory(&self, path: &Path) -> io::Result<FileHandle> {
+        match self {
+            Self::Off => FileHandle::open_directory(path.to_path_buf()),
+            Self::Jail(root) => FileHandle::open_directory(jailed(root, path)?),
+            Self::Memory(vfs) => vfs.open_directory(path),
+        }
+    }
+
+    /// Gets the status of the file or directory at `path`.
+    pub(crate) fn status(&self, path: &Path) -> io::Result<FileStatus> {
+        let metadata = match self {
+            Self::Off => fs::metadata(path)?,
+            Self::Jail(root) => fs::metadata(jailed(root, path)?)?,
+            Self::Memory(vfs) => return vfs.status(path),
+        };
+        Ok((&metadata).into())
+    }
+
+    /// Deletes the file at `path`.
+    pub(crate) fn remove_file(&mut self, path: &Path) -> io::Result<()> {
+        match self {
+            Self::Off => fs::remove_file(path),
+            Self::Jail(root) => fs::remove_file(jailed(root, path)?),
+            Self::Memory(vfs) => vfs.remove(path, false),
+        }
+    }
+
+    /// Renames the file or directory at `from` to `to`.
+    pub(crate) fn rename(&mut self, from: &Path, to: &Path) -> io::Result<()> {
+        match self {
+            Self::Off => fs::rename(from, to),
+            Self::Jail(root) => fs::rename(jailed(root, from)?, jailed(root, to)?),
+            Self::Memory(vfs) => vfs.rename(from, to),
+        }
+    }
+
+    /// Creates a directory at `path` with the permissions in `mode`.
+    pub(crate) fn create_dir(&mut self, path: &Path, mode: u32) -> io::Result<()> {
+        let path = match self {
+            Self::Off => path.to_path_buf(),
+            Self::Jail(root) => jailed(root, path)?,
+            Self::Memory(vfs) => return vfs.create_dir(path, mode),
+        };
+        let mut builder = fs::DirBuilder::new();
+        #[cfg(unix)]
+        std::os::unix::fs::DirBuilderExt::mode(&mut builder, mode & 0o7777);
+        #[cfg(not(unix))]
+        let _ = mode;
+        builder.create(path)
+    }
+
+    /// Deletes the empty directory at `path`.
+    pub(crate) fn remove_dir(&mut self, path: &Path) -> io::Result<()> {
+        match self {
+            Self::Off => fs::remove_dir(path),
+            Self::Jail(root) => fs::remove_dir(jailed(root, path)?),
+            Self::Memory(vfs) => vfs.remove(path, true),
+        }
+    }
+}
+
+/// Resolves `path` against the jail `root`, which must be canonical.
+///
+/// Absolute paths and paths containing `..` are refused. Symbolic links are resolved, and paths
+/// that would lead outside of the jail through one are refused as well. A path that doesn't exist
+/// yet is allowed as long as its parent does, so files can be created.
+fn jailed(root: &Path, path: &Path) -> io::Result<PathBuf> {
+    let joined = root.join(normalize(path)?);
+    let resolved = match joined.canonicalize() {
+        Ok(resolved) => resolved,
+        Err(error) if error.kind() == ErrorKind::NotFound => {
+            // A dangling symbolic link would be followed when the file is created.
+            if fs::symlink_metadata(&joined).is_ok() {
+                return Err(Error::from(ErrorKind::PermissionDenied));
+            }
+            match (joined.parent(), joined.file_name()) {
+                (Some(parent), Some(name)) => parent.canonicalize()?.join(name),
+                _ => return Err(error),
+            }
+        }
+        Err(error) => return Err(error),
+    };
+    if resolved.starts_with(root) {
+        Ok(resolved)
+    } else {
+        Err(Error::from(ErrorKind::PermissionDenied))
+    }
+}
+
+/// Strips `.` components from the relative path `path`, refusing absolute paths and paths
+/// containing `..`.
+fn normalize(path: &Path) -> io::Result<PathBuf> {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(name) => normalized.push(name),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => {
+                return Err(Error::from(ErrorKind::PermissionDenied));
+            }
+        }
+    }
+    Ok(normalized)
+}
+
+/// The contents of a file in a [`VirtualFileSystem`].
+#[derive(Clone, Debug)]
+pub struct VirtualFile {
+    data: Vec<u8>,
+    permissions: u32,
+    modified: SystemTime,
+}
+
+impl VirtualFile {
+    pub(crate) fn new(data: Vec<u8>, permissions: u32, modified: SystemTime) -> Self {
+        Self {
+            data,
+            permissions: permissions & 0o7777,
+            modified,
+        }
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// The size of the file in bytes.
+    pub fn len(&self) -> u64 {
+        self.data.len() as u64
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Copies the bytes starting at `offset` into `buf`, returning how many were copied.
+    pub(crate) fn read_at(&self, offset: u64, buf: &mut [u8]) -> usize {
+        let start = usize::try_from(offset).map_or(self.data.len(), |offset| {
+            usize::min(offset, self.data.len())
+        });
+        let n = usize::min(buf.len(), self.data.len() - start);
+        buf[..n].copy_from_slice(&self.data[start..start + n]);
+        n
+    }
+
+    /// Writes `buf` at `offset`, filling any gap past the end of the file with zeroes.
+    ///
+    /// Writes that would make the file larger than [`MAX_VIRTUAL_FILE_SIZE`] fail without changing
+    /// it.
+    pub(crate) fn write_at(&mut self, offset: u64, buf: &[u8]) -> io::Result<()> {
+        let end = offset
+            .checked_add(buf.len() as u64)
+            .filter(|&end| end <= MAX_VIRTUAL_FILE_SIZE)
+            .ok_or_else(|| Error::from(ErrorKind::StorageFull))?;
+        // Both fit in a `usize`, since they're no larger than `MAX_VIRTUAL_FILE_SIZE`.
+        let (start, end) = (offset as usize, end as usize);
+        if self.data.len() < end {
+            self.data.resize(end, 0);
+        }
+        self.data[start..end].copy_from_slice(buf);
+        Ok(())
+    }
+
+    /// Sets the time the file was last modified.
+    ///
+    /// Writes don't do this themselves, since they don't know what time it is.
+    pub(crate) fn touch(&mut self, now: SystemTime) {
+        self.modified = now;
+    }
+
+    pub(crate) fn status(&self) -> FileStatus {
+        FileStatus {
+            is_directory: false,
+            size: self.len(),
+            permissions: self.permissions,
+            modified: Some(self.modified),
+        }
+    }
+}
+
+/// A node in a [`VirtualFileSystem`].
+#[derive(Clone, Debug)]
+pub(crate) enum Node {
+    File(Rc<RefCell<VirtualFile>>),
+    Directory { permissions: u32 },
+}
+
+/// A file system that only exists in memory.
+///
+/// Files that are still open keep their contents even after being deleted or replaced, like they
+/// would on a Unix file system.
+#[derive(Clone, Debug)]
+pub struct VirtualFileSystem {
+    /// Every file and directory, keyed by its normalized path. The root directory has an empty
+    /// path.
+    nodes: BTreeMap<PathBuf, Node>,
+}
+
+impl Default for VirtualFileSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VirtualFileSystem {
+    /// Creates a virtual file system containing nothing but its root directory.
+    pub fn new() -> Self {
+        let mut nodes = BTreeMap::new();
+        nodes.insert(PathBuf::new(), Node::Directory { permissions: 0o755 });
+        Self { nodes }
+    }
+
+    /// Copies the directory or tar archive at `source` into the file system, giving each file
+    /// `now` as its modification time.
+    pub fn load(&mut self, source: &Path, now: SystemTime) -> io::Result<()> {
+        if source.is_dir() {
+            self.load_directory(source, Path::new(""), now)
+        } else {
+            self.load_tar(&fs::read(source)?, now)
+        }
+    }
+
+    /// The paths of every file and directory, in alphabetical order. The root directory isn't
+    /// included.
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.nodes.keys().map(PathBuf::as_path).skip(1)
+    }
+
+    /// Every file and directory along with its path, in alphabetical order. The root directory
+    /// isn't included.
+    pub(crate) fn nodes(&self) -> impl Iterator<Item = (&Path, &Node)> {
+        self.nodes
+            .iter()
+            .map(|(path, node)| (path.as_path(), node))
+            .skip(1)
+    }
+
+    /// Replaces every file and directory besides the root directory with `nodes`.
+    pub(crate) fn replace_nodes(&mut self, nodes: impl IntoIterator<Item = (PathBuf, Node)>) {
+        self.nodes.retain(|path, _| path.as_os_str().is_empty());
+        self.nodes.extend(nodes);
+    }
+
+    /// Gets the contents of the file at `path`, or [`None`] if there is no such file.
+    pub fn read(&self, path: &Path) -> Option<Vec<u8>> {
+        match self.nodes.get(&normalize(path).ok()?)? {
+            Node::File(file) => Some(file.borrow().data().to_vec()),
+            Node::Directory { .. } => None,
+        }
+    }
+
+    /// Writes every file and directory into the host directory `destination`, creating it if
+    /// necessary.
+    pub fn export(&self, destination: &Path) -> io::Result<()> {
+        fs::create_dir_all(destination)?;
+        for (path, node) in self.nodes.iter().skip(1) {
+            let host_path = destination.join(path);
+            match node {
+                Node::File(file) => fs::write(host_path, file.borrow().data())?,
+                Node::Directory { .. } => fs::create_dir_all(host_path)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn load_directory(&mut self, source: &Path, prefix: &Path, now: SystemTime) -> io::Result<()> {
+        for entry in fs::read_dir(source)? {
+            let entry = entry?;
+            let path = prefix.join(entry.file_name());
+            let metadata = entry.metadata()?;
+            let permissions = FileStatus::from(&metadata).permissions;
+            if metadata.is_dir() {
+                self.nodes
+                    .insert(path.clone(), Node::Directory { permissions });
+                self.load_directory(&entry.path(), &path, now)?;
+            } else {
+                let file = VirtualFile::new(fs::read(entry.path())?, permissions, now);
+                self.nodes
+                    .insert(path, Node::File(Rc::new(RefCell::new(file))));
+            }
+        }
+        Ok(())
+    }
+
+    /// Loads the regular files and directories from a ustar archive.
+    fn load_tar(&mut self, archive: &[u8], now: SystemTime) -> io::Result<()> {
+        let invalid = || Error::new(ErrorKind::InvalidData, "malformed tar archive");
+        let mut offset = 0;
+        while let Some(header) = archive.get(offset..offset + TAR_BLOCK) {
+            // The archive ends with blocks of zeroes.
+            if header.iter().all(|&byte| byte == 0) {
+                break;
+            }
+            let field = |range: core::ops::Range<usize>| {
+                let bytes = &header[range];
+                let len = bytes
+                    .iter()
+                    .position(|&byte| byte == 0)
+                    .unwrap_or(bytes.len());
+                String::from_utf8_lossy(&bytes[..len]).into_owned()
+            };
+            let octal = |range| u64::from_str_radix(field(range).trim(), 8).map_err(|_| invalid());
+            let mut name = field(0..100);
+            if header[257..262] == *b"ustar" && header[345] != 0 {
+                name = format!("{}/{name}", field(345..500));
+            }
+            let permissions = octal(100..108)? as u32;
+            let size = octal(124..136)? as usize;
+            let data_start = offset + TAR_BLOCK;
+            let data = archive
+                .get(data_start..data_start + size)
+                .ok_or_else(invalid)?;
+            let path = normalize(Path::new(&name))
+                .map_err(|_| Error::new(ErrorKind::InvalidData, format!("unsafe path {name}")))?;
+            match header[156] {
+                b'0' | b'\0' => {
+                    self.create_parents(&path);
+                    let file = VirtualFile::new(data.to_vec(), permissions, now);
+                    self.nodes
+                        .insert(path, Node::File(Rc::new(RefCell::new(file))));
+                }
+                b'5' => {
+                    self.create_parents(&path);
+                    self.nodes.insert(path, Node::Directory { permissions });
+                }
+                // Links, devices and the like have no place in the sandbox.
+                _ => {}
+            }
+            offset = data_start + size.div_ceil(TAR_BLOCK) * TAR_BLOCK;
+        }
+        Ok(())
+    }
+
+    /// Creates any missing directories above `path`, since archives don't always list them.
+    fn create_parents(&mut self, path: &Path) {
+        for ancestor in path.ancestors().skip(1) {
+            self.nodes
+                .entry(ancestor.to_path_buf())
+                .or_insert(Node::Directory { permissions: 0o755 });
+        }
+    }
+
+    /// Checks that the directory that would contain `path` exists.
+    fn check_parent(&self, path: &Path) -> io::Result<()> {
+        match path.parent().and_then(|parent| self.nodes.get(parent)) {
+            Some(Node::Directory { .. }) => Ok(()),
+            Some(Node::File(_)) => Err(Error::from(ErrorKind::NotADirectory)),
+            None => Err(Error::from(ErrorKind::NotFound)),
+        }
+    }
+
+    fn open(
+        &mut self,
+        path: &Path,
+        mode: OpenMode,
+        permissions: u32,
+        now: SystemTime,
+    ) -> io::Result<FileHandle> {
+        let path = normalize(path)?;
+        let file = match self.nodes.get(&path) {
+            Some(Node::Directory { .. }) => return Err(Error::from(ErrorKind::IsADirectory)),
+            Some(Node::File(_)) if mode.create_new => {
+                return Err(Error::from(ErrorKind::AlreadyExists));
+            }
+            Some(Node::File(file)) => {
+                if mode.truncate {
+                    let mut file = file.borrow_mut();
+                    file.data.clear();
+                    file.touch(now);
+                }
+                Rc::clone(file)
+            }
+            None if mode.create || mode.create_new => {
+                self.check_parent(&path)?;
+                let file = VirtualFile::new(Vec::new(), permissions, now);
+                let file = Rc::new(RefCell::new(file));
+                self.nodes
+                    .insert(path.clone(), Node::File(Rc::clone(&file)));
+                file
+            }
+            None => return Err(Error::from(ErrorKind::NotFound)),
+        };
+        Ok(FileHandle::Virtual {
+            file,
+            path,
+            mode,
+            offset: 0,
+        })
+    }
+
+    fn open_directory(&self, path: &Path) -> io::Result<FileHandle> {
+        let path = normalize(path)?;
+        match self.nodes.get(&path) {
+            Some(Node::Directory { .. }) => {}
+            Some(Node::File(_)) => return Err(Error::from(ErrorKind::NotADirectory)),
+            None => return Err(Error::from(ErrorKind::NotFound)),
+        }
+        let entries: VecDeque<String> = self
+            .children(&path)
+            .map(|child| child.to_string_lossy().into_owned())
+            .collect();
+        Ok(FileHandle::Directory { entries, path })
+    }
+
+    /// The names of the entries directly inside the directory at `path`, in alphabetical order.
+    fn children<'a>(&'a self, path: &'a Path) -> impl Iterator<Item = &'a OsStr> {
+        self.nodes
+            .keys()
+            .filter(move |child| child.parent() == Some(path))
+            .filter_map(|child| child.file_name())
+    }
+
+    fn status(&self, path: &Path) -> io::Result<FileStatus> {
+        match self.nodes.get(&normalize(path)?) {
+            Some(Node::File(file)) => Ok(file.borrow().status()),
+            Some(&Node::Directory { permissions }) => Ok(FileStatus {
+                is_directory: true,
+                size: 0,
+                permissions,
+                modified: None,
+            }),
+            None => Err(Error::from(ErrorKind::NotFound)),
+        }
+    }
+
+    /// Removes the file at `path`, or the empty directory if `directory` is set.
+    fn remove(&mut self, path: &Path, directory: bool) -> io::Result<()> {
+        let path = normalize(path)?;
+        match (self.nodes.get(&path), directory) {
+            (None, _) => return Err(Error::from(ErrorKind::NotFound)),
+            (Some(Node::File(_)), true) => return Err(Error::from(ErrorKind::NotADirectory)),
+            (Some(Node::Directory { .. }), false) => {
+                return Err(Error::from(ErrorKind::IsADirectory));
+            }
+            (Some(Node::Directory { .. }), true) if path.as_os_str().is_empty() => {
+                return Err(Error::from(ErrorKind::PermissionDenied));
+            }
+            (Some(Node::Directory { .. }), true) if self.children(&path).next().is_some() => {
+                return Err(Error::from(ErrorKind::DirectoryNotEmpty));
+            }
+            _ => {}
+        }
+        self.nodes.remove(&path);
+        Ok(())
+    }
+
+    fn rename(&mut self, from: &Path, to: &Path) -> io::Result<()> {
+        let from = normalize(from)?;
+        let to = normalize(to)?;
+        // A directory can't be moved inside itself.
+        if from.as_os_str().is_empty() || (to.starts_with(&from) && to != from) {
+            return Err(Error::from(ErrorKind::InvalidInput));
+        }
+        if !self.nodes.contains_key(&from) {
+            return Err(Error::from(ErrorKind::NotFound));
+        }
+        self.check_parent(&to)?;
+        match (&self.nodes[&from], self.nodes.get(&to)) {
+            (Node::File(_), Some(Node::Directory { .. })) => {
+                return Err(Error::from(ErrorKind::IsADirectory));
+            }
+            (Node::Directory { .. }, Some(Node::File(_))) => {
+                return Err(Error::from(ErrorKind::NotADirectory));
+            }
+            (Node::Directory { .. }, Some(Node::Directory { .. }))
+                if self.children(&to).next().is_some() =>
+            {
+                return Err(Error::from(ErrorKind::DirectoryNotEmpty));
+            }
+            _ => {}
+        }
+        // Moving a directory moves everything inside it too.
+        let moved: Vec<PathBuf> = self
+            .nodes
+            .keys()
+            .filter(|path| path.starts_with(&from))
+            .cloned()
+            .collect();
+        for old_path in moved {
+            let node = self.nodes.remove(&old_path).unwrap();
+            let rest = old_path.strip_prefix(&from).unwrap();
+            let new_path = if rest.as_os_str().is_empty() {
+                to.clone()
+            } else {
+                to.join(rest)
+            };
+            self.nodes.insert(new_path, node);
+        }
+        Ok(())
+    }
+
+    fn create_dir(&mut self, path: &Path, mode: u32) -> io::Result<()> {
+        let path = normalize(path)?;
+        if self.nodes.contains_key(&path) {
+            return Err(Error::from(ErrorKind::AlreadyExists));
+        }
+        self.check_parent(&path)?;
+        self.nodes.insert(
+            path,
+            Node::Directory {
+                permissions: mode & 0o7777,
+            },
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        MAX_VIRTUAL_FILE_SIZE, Node, TAR_BLOCK, VirtualFile, VirtualFileSystem, jailed, normalize,
+    };
+    use std::{
+        cell::RefCell,
+        fs,
+        io::ErrorKind,
+        path::{Path, PathBuf},
+        rc::Rc,
+        time::SystemTime,
+    };
+
+    /// Creates an empty directory for a test to use as a jail, returning its canonical path.
+    fn jail(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("seaside-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        root.canonicalize().unwrap()
+    }
+
+    /// Builds a ustar header for an entry named `name`.
+    fn tar_header(name: &str, kind: u8, size: usize) -> [u8; TAR_BLOCK] {
+        let mut header = [0; TAR_BLOCK];
+        header[..name.len()].copy_from_slice(name.as_bytes());
+        header[100..107].copy_from_slice(b"0000644");
+        header[124..135].copy_from_slice(format!("{size:011o}").as_bytes());
+        header[156] = kind;
+        header[257..263].copy_from_slice(b"ustar\0");
+        header
+    }
+
+    fn add_file(vfs: &mut VirtualFileSystem, path: &str, data: &[u8]) {
+        vfs.nodes.insert(
+            PathBuf::from(path),
+            Node::File(Rc::new(RefCell::new(VirtualFile::new(
+                data.to_vec(),
+                0o644,
+                SystemTime::UNIX_EPOCH,
+            )))),
+        );
+    }
+
+    #[test]
+    fn normalize_strips_current_directory() {
+        assert_eq!(
+            normalize(Path::new("./a/./b")).unwrap(),
+            PathBuf::from("a/b"),
+        );
+        assert_eq!(normalize(Path::new(".")).unwrap(), PathBuf::new());
+    }
+
+    #[test]
+    fn normalize_refuses_parent_and_absolute_paths() {
+        for path in ["..", "a/../b", "a/..", "/etc/passwd"] {
+            let error = normalize(Path::new(path)).unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::PermissionDenied, "{path}");
+        }
+    }
+
+    #[test]
+    fn jailed_stays_inside_root() {
+        let root = jail("jailed");
+        fs::create_dir(root.join("dir")).unwrap();
+        assert_eq!(jailed(&root, Path::new("dir")).unwrap(), root.join("dir"),);
+        // Files that don't exist yet can still be created.
+        assert_eq!(
+            jailed(&root, Path::new("dir/new.txt")).unwrap(),
+            root.join("dir/new.txt"),
+        );
+        for path in ["..", "dir/../..", "/etc/passwd"] {
+            let error = jailed(&root, Path::new(path)).unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::PermissionDenied, "{path}");
+        }
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn jailed_refuses_symbolic_links_out_of_root() {
+        use std::os::unix::fs::symlink;
+
+        let root = jail("symlinks");
+        let outside = jail("symlinks-outside");
+        fs::write(outside.join("secret"), b"secret").unwrap();
+        fs::write(root.join("file"), b"file").unwrap();
+        symlink(&outside, root.join("escape")).unwrap();
+        symlink(outside.join("missing"), root.join("dangling")).unwrap();
+        symlink(root.join("file"), root.join("inside")).unwrap();
+        for path in ["escape", "escape/secret", "escape/new", "dangling"] {
+            let error = jailed(&root, Path::new(path)).unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::PermissionDenied, "{path}");
+        }
+        assert_eq!(
+            jailed(&root, Path::new("inside")).unwrap(),
+            root.join("file"),
+        );
+        fs::remove_dir_all(&root).unwrap();
+        fs::remove_dir_all(&outside).unwrap();
+    }
+
+    #[test]
+    fn write_at_fills_gaps_with_zeroes() {
+        let mut file = VirtualFile::new(b"ab".to_vec(), 0o644, SystemTime::UNIX_EPOCH);
+        file.write_at(4, b"cd").unwrap();
+        assert_eq!(file.data(), b"ab\0\0cd");
+    }
+
+    #[test]
+    fn write_at_refuses_to_grow_past_limit() {
+        let mut file = VirtualFile::new(Vec::new(), 0o644, SystemTime::UNIX_EPOCH);
+        let error = file.write_at(MAX_VIRTUAL_FILE_SIZE, b"x").unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::StorageFull);
+        let error = file.write_at(u64::MAX, b"x").unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::StorageFull);
+        assert!(file.is_empty());
+    }
+
+    #[test]
+    fn rename_moves_directory_contents() {
+        let mut vfs = VirtualFileSystem::new();
+        vfs.create_dir(Path::new("a"), 0o755).unwrap();
+        add_file(&mut vfs, "a/file", b"data");
+        vfs.rename(Path::new("a"), Path::new("b")).unwrap();
+        assert_eq!(vfs.read(Path::new("b/file")).unwrap(), b"data");
+        assert!(vfs.read(Path::new("a/file")).is_none());
+        assert_eq!(
+            vfs.paths().collect::<Vec<_>>(),
+            [Path::new("b"), Path::new("b/file")],
+        );
+    }
+
+    #[test]
+    fn rename_refuses_invalid_moves() {
+        let mut vfs = VirtualFileSystem::new();
+        vfs.create_dir(Path::new("a"), 0o755).unwrap();
+        vfs.create_dir(Path::new("b"), 0o755).unwrap();
+        add_file(&mut vfs, "b/file", b"data");
+        add_file(&mut vfs, "file", b"data");
+        let cases = [
+            ("a", "a/inner", ErrorKind::InvalidInput),
+            ("missing", "c", ErrorKind::NotFound),
+            ("file", "a", ErrorKind::IsADirectory),
+            ("a", "file", ErrorKind::NotADirectory),
+            ("a", "b", ErrorKind::DirectoryNotEmpty),
+            ("file", "missing/file", ErrorKind::NotFound),
+        ];
+        for (from, to, kind) in cases {
+            let error = vfs.rename(Path::new(from), Path::new(to)).unwrap_err();
+            assert_eq!(error.kind(), kind, "{from} -> {to}");
+        }
+    }
+
+    #[test]
+    fn remove_unlinks_files_and_empty_directories() {
+        let mut vfs = VirtualFileSystem::new();
+        vfs.create_dir(Path::new("dir"), 0o755).unwrap();
+        add_file(&mut vfs, "dir/file", b"data");
+        let error = vfs.remove(Path::new("dir"), true).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::DirectoryNotEmpty);
+        let error = vfs.remove(Path::new("dir/file"), true).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::NotADirectory);
+        let error = vfs.remove(Path::new("dir"), false).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::IsADirectory);
+        vfs.remove(Path::new("dir/file"), false).unwrap();
+        vfs.remove(Path::new("dir"), true).unwrap();
+        assert_eq!(vfs.paths().count(), 0);
+        let error = vfs.remove(Path::new("dir"), true).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::NotFound);
+        let error = vfs.remove(Path::new(""), true).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::PermissionDenied);
+    }
+
+    #[test]
+    fn load_tar_reads_files_and_directories() {
+        let data = b"hello";
+        let mut archive = Vec::new();
+        archive.extend(tar_header("dir/", b'5', 0));
+        archive.extend(tar_header("nested/file.txt", b'0', data.len()));
+        let mut block = [0; TAR_BLOCK];
+        block[..data.len()].copy_from_slice(data);
+        archive.extend(block);
+        archive.extend(tar_header("link", b'2', 0));
+        archive.extend([0; 2 * TAR_BLOCK]);
+
+        let mut vfs = VirtualFileSystem::new();
+        vfs.load_tar(&archive, SystemTime::UNIX_EPOCH).unwrap();
+        assert_eq!(
+            vfs.paths().collect::<Vec<_>>(),
+            [
+                Path::new("dir"),
+                Path::new("nested"),
+                Path::new("nested/file.txt"),
+            ],
+        );
+        assert_eq!(vfs.read(Path::new("nested/file.txt")).unwrap(), data);
+        assert_eq!(
+            vfs.status(Path::new("nested/file.txt"))
+                .unwrap()
+                .permissions,
+            0o644
+        );
+    }
+
+    #[test]
+    fn load_tar_refuses_unsafe_and_truncated_archives() {
+        let mut vfs = VirtualFileSystem::new();
+        let mut archive = tar_header("../escape", b'0', 0).to_vec();
+        let error = vfs.load_tar(&archive, SystemTime::UNIX_EPOCH).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+
+        archive = tar_header("file", b'0', TAR_BLOCK).to_vec();
+        let error = vfs.load_tar(&archive, SystemTime::UNIX_EPOCH).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+        assert_eq!(vfs.paths().count(), 0);
+    }
+}
@@ -0,0 +1,301 @@
+//! System services implemented by Lua scripts.
+//!
+//! Provides the struct [`ScriptedServices`], which runs the scripts bound to service codes in the
+//! config. Each script is run from the top every time its service is called. All scripts share a
+//! single Lua state, so globals they set persist between calls.
+//!
+//! While a script runs, the global table `seaside` gives it access to the interpreter:
+//!
+//! | Function                     | Description                                               |
+//! | ---------------------------- | --------------------------------------------------------- |
+//! | `seaside.reg(r)`             | Reads CPU register `r`, given by name (`"a0"`) or number. |
+//! | `seaside.set_reg(r, x)`      | Writes `x` to CPU register `r`.                           |
+//! | `seaside.freg(n)`            | Reads `$f<n>` as a single-precision float.                |
+//! | `seaside.set_freg(n, x)`     | Writes `x` to `$f<n>` as a single-precision float.        |
+//! | `seaside.read_byte(a)`       | Reads the unsigned byte at address `a`.                   |
+//! | `seaside.read_word(a)`       | Reads the signed word at the word-aligned address `a`.    |
+//! | `seaside.write_byte(a, x)`   | Writes the byte `x` to address `a`.                       |
+//! | `seaside.write_word(a, x)`   | Writes the word `x` to the word-aligned address `a`.      |
+//! | `seaside.read_string(a)`     | Reads the nul-terminated string at address `a`.           |
+//! | `seaside.write_string(a, s)` | Writes `s` followed by a nul byte to address `a`.         |
+//! | `seaside.print(s)`           | Prints `s` to stdout.                                     |
+//! | `seaside.read_line()`        | Reads a line from stdin, without the line break.          |
+//! | `seaside.exit(code)`         | Ends the program with `code` once the script returns.     |
+//!
+//! Accessing memory the program couldn't access raises the same exception it would have, once the
+//! script is abandoned. Any other error in a script fails the service.
+
+use crate::{
+    Exception, InterpreterState, SyscallFailureKind, memory::Region, register_file::IndexByRegister,
+};
+use anyhow::{Context, Result};
+use core::{cell::RefCell, ffi::CStr};
+use minimal_logging::macros::warnln;
+use mlua::{Function, Lua, Table};
+use num_traits::FromPrimitive;
+use seaside_config::features::Scripts;
+use seaside_constants::register::{CpuRegister, FpuRegister};
+use seaside_type_aliases::Address;
+use std::{
+    collections::HashMap,
+    fs::read_to_string,
+    io::{Write, stdin, stdout},
+};
+
+/// The scripts bound to service codes, compiled and ready to run.
+pub struct ScriptedServices {
+    lua: Lua,
+    scripts: HashMap<u32, Script>,
+}
+
+struct Script {
+    /// The file name of the script, without its extension.
+    name: String,
+    function: Function,
+}
+
+impl ScriptedServices {
+    /// Compiles every script in `scripts`, returning [`None`] if there aren't any.
+    pub fn init(scripts: &Scripts) -> Result<Option<Self>> {
+        if scripts.is_empty() {
+            return Ok(None);
+        }
+        let lua = Lua::new();
+        let mut compiled = HashMap::new();
+        for (&code, path) in scripts.iter() {
+            let source = read_to_string(path)
+                .with_context(|| format!("failed to read script {}", path.display()))?;
+            let function = lua
+                .load(source)
+                .set_name(format!("@{}", path.display()))
+                .into_function()
+                .with_context(|| format!("failed to compile script {}", path.display()))?;
+            let name = path.file_stem().map_or_else(
+                || path.display().to_string(),
+                |stem| stem.to_string_lossy().into_owned(),
+            );
+            compiled.insert(code, Script { name, function });
+        }
+        Ok(Some(Self {
+            lua,
+            scripts: compiled,
+        }))
+    }
+
+    /// Checks whether a script is bound to service `code`.
+    pub fn contains(&self, code: u32) -> bool {
+        self.scripts.contains_key(&code)
+    }
+
+    /// Gets the name of the script bound to service `code`.
+    pub fn name(&self, code: u32) -> Option<&str> {
+        self.scripts.get(&code).map(|script| script.name.as_str())
+    }
+
+    /// Runs the script bound to service `code` against `state`.
+    pub fn call(&self, code: u32, state: &mut InterpreterState) -> Result<(), Exception> {
+        let Some(script) = self.scripts.get(&code) else {
+            return Err(Exception::SyscallFailure(
+                SyscallFailureKind::UnknownServiceCode(code),
+            ));
+        };
+        let context = ScriptContext {
+            state: RefCell::new(state),
+            exception: RefCell::new(None),
+        };
+        let result = self.lua.scope(|scope| {
+            let api = self.lua.create_table()?;
+            context.register_api(scope, &api)?;
+            self.lua.globals().set("seaside", api)?;
+            script.function.call::<()>(())
+        });
+        if let Some(exception) = context.exception.take() {
+            return Err(exception);
+        }
+        result.map_err(|error| {
+            warnln!("script '{}' failed: {error}", script.name);
+            Exception::SyscallFailure(SyscallFailureKind::ScriptFailed(code))
+        })
+    }
+}
+
+/// What the functions in the `seaside` table work with while a script runs.
+struct ScriptContext<'a> {
+    state: RefCell<&'a mut InterpreterState>,
+    /// The first exception raised on behalf of the script, if any.
+    exception: RefCell<Option<Exception>>,
+}
+
+impl ScriptContext<'_> {
+    /// Fills `api` with the functions scripts use to reach the interpreter.
+    fn register_api<'scope>(
+        &'scope self,
+        scope: &'scope mlua::Scope<'scope, '_>,
+        api: &Table,
+    ) -> mlua::Result<()> {
+        api.set(
+            "reg",
+            scope.create_function(|_, register: String| {
+                let register = cpu_register(&register)?;
+                self.with_state(|state| {
+                    let value: i32 = state.registers.read(register);
+                    Ok(value)
+                })
+            })?,
+        )?;
+        api.set(
+            "set_reg",
+            scope.create_function(|_, (register, value): (String, i64)| {
+                let register = cpu_register(&register)?;
+                self.with_state(|state| {
+                    state.registers.write(register, value as u32);
+                    Ok(())
+                })
+            })?,
+        )?;
+        api.set(
+            "freg",
+            scope.create_function(|_, index: u8| {
+                let register = fpu_register(index)?;
+                self.with_state(|state| {
+                    let value: f32 = state.registers.read(register);
+                    Ok(value)
+                })
+            })?,
+        )?;
+        api.set(
+            "set_freg",
+            scope.create_function(|_, (index, value): (u8, f32)| {
+                let register = fpu_register(index)?;
+                self.with_state(|state| {
+                    state.registers.write(register, value);
+                    Ok(())
+                })
+            })?,
+        )?;
+        api.set(
+            "read_byte",
+            scope.create_function(|_, address: i64| {
+                self.with_state(|state| state.memory.read_u8(address as Address))
+            })?,
+        )?;
+        api.set(
+            "read_word",
+            scope.create_function(|_, address: i64| {
+                self.with_state(|state| state.memory.read_u32(address as Address, true))
+                    .map(|word| word as i32)
+            })?,
+        )?;
+        api.set(
+            "write_byte",
+            scope.create_function(|_, (address, value): (i64, i64)| {
+                self.with_state(|state| state.memory.write_u8(address as Address, value as u8))
+            })?,
+        )?;
+        api.set(
+            "write_word",
+            scope.create_function(|_, (address, value): (i64, i64)| {
+                self.with_state(|state| {
+                    state
+                        .memory
+                        .write_u32(address as Address, value as u32, true)
+                })
+            })?,
+        )?;
+        api.set(
+            "read_string",
+            scope.create_function(|_, address: i64| {
+                self.with_state(|state| {
+                    let bytes = state.memory.get_slice(address as Address)?;
+                    CStr::from_bytes_until_nul(bytes)
+                        .map(|string| string.to_string_lossy().into_owned())
+                        .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::NulNotFound))
+                })
+            })?,
+        )?;
+        api.set(
+            "write_string",
+            scope.create_function(|_, (address, string): (i64, mlua::String)| {
+                let bytes = string.as_bytes();
+                self.with_state(|state| {
+                    let address = address as Address;
+                    state.memory.prepare_slice_write(address, bytes.len() + 1);
+                    let buffer = state
+                        .memory
+                        .get_slice_mut(address)?
+                        .get_mut(..bytes.len() + 1)
+                        .ok_or(Exception::SyscallFailure(SyscallFailureKind::WriteFailed))?;
+                    buffer[..bytes.len()].copy_from_slice(&bytes);
+                    buffer[bytes.len()] = b'\0';
+                    Ok(())
+                })
+            })?,
+        )?;
+        api.set(
+            "print",
+            scope.create_function(|_, string: mlua::String| {
+                self.with_state(|state| {
+                    stdout()
+                        .write_all(&string.as_bytes())
+                        .map_err(|_| Exception::SyscallFailure(SyscallFailureKind::WriteFailed))?;
+                    state.stdout_pending_flush = true;
+                    Ok(())
+                })
+            })?,
+        )?;
+        api.set(
+            "read_line",
+            scope.create_function(|_, ()| {
+                self.with_state(|state| {
+                    state.flush_stdout_if_necessary().map_err(|_| {
+                        Exception::SyscallFailure(SyscallFailureKind::StdoutFlushFailed)
+                    })?;
+                    let mut line = String::new();
+                    stdin().read_line(&mut line).map_err(|_| {
+                        Exception::SyscallFailure(SyscallFailureKind::StdinReadFailed)
+                    })?;
+                    let line = line.strip_suffix('\n').unwrap_or(&line);
+                    Ok(line.strip_suffix('\r').unwrap_or(line).to_string())
+                })
+            })?,
+        )?;
+        api.set(
+            "exit",
+            scope.create_function(|_, exit_code: i64| {
+                self.with_state(|state| {
+                    state.exit_code = Some((exit_code & 0xff) as u8);
+                    Ok(())
+                })
+            })?,
+        )?;
+        Ok(())
+    }
+
+    /// Runs `f` against the interpreter, turning any exception it raises into a Lua error.
+    ///
+    /// The exception is remembered so it can be raised once the script is abandoned.
+    fn with_state<T, F>(&self, f: F) -> mlua::Result<T>
+    where
+        F: FnOnce(&mut InterpreterState) -> Result<T, Exception>,
+    {
+        let mut state = self.state.borrow_mut();
+        f(&mut state).map_err(|exception| {
+            self.exception.borrow_mut().get_or_insert(exception);
+            mlua::Error::runtime(exception)
+        })
+    }
+}
+
+/// Parses a CPU register given by name, with or without the `$`, or by number.
+fn cpu_register(register: &str) -> mlua::Result<CpuRegister> {
+    register
+        .parse()
+        .ok()
+        .or_else(|| register.parse().ok().and_then(CpuRegister::from_u8))
+        .ok_or_else(|| mlua::Error::runtime(format!("'{register}' isn't a CPU register")))
+}
+
+fn fpu_register(index: u8) -> mlua::Result<FpuRegister> {
+    FpuRegister::from_u8(index)
+        .ok_or_else(|| mlua::Error::runtime(format!("$f{index} isn't an FPU register")))
+}
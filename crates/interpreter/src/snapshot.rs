@@ -0,0 +1,877 @@
+//! Saving and restoring the state of the interpreter.
+//!
+//! Provides the struct [`Snapshot`], which captures everything a running program can change, along
+//! with its on-disk format.
+//!
+//! Snapshots don't contain the program's layout in memory, so they can only be restored into an
+//! interpreter initialized from the same program and config. Statistics collected by the
+//! profiler, caches, pipeline model and branch predictors aren't captured either.
+//!
+//! # Format
+//!
+//! All integers are little-endian.
+//!
+//! ```text
+//! magic             8 bytes    "SEASNAP\0"
+//! version           u32
+//! pc                u32
+//! exit code         u8 (0 if still running, 1 if exited) followed by a u8
+//! registers         71 × u32   see `RegisterFile::to_words`
+//! heap              u32 next heap address, u32 free heap space
+//! heap allocator    u32 count, then words   see `HeapAllocator::to_words`
+//! regions           u32 count, then per region: u32 start, u32 length, bytes
+//! next fd           u32
+//! open files        u32 count, then per file: u32 fd, u32 length, path, u8 kind, then
+//!                     kind 0 (host file): u32 flags, u64 offset
+//!                     kind 1 (virtual file): u32 file index, u8 mode, u64 offset
+//!                     kind 2 (directory): u32 count, then per unread entry: u32 length, name
+//! virtual files     u8 (0 unless the sandbox keeps files in memory), then
+//!                     u32 count, then per file: u32 permissions, u64 modification time in
+//!                       nanoseconds since the Unix epoch, u32 length, bytes
+//!                     u32 count, then per node: u32 length, path, u8 kind (0 for a file, 1 for a
+//!                       directory), u32 file index or permissions
+//! rngs              u32 count, then per generator: u32 id, u64 state
+//! clock             u8 (0 if there is no virtual clock, 1 if there is) followed by a u64 time
+//! ```
+//!
+//! The mode of a virtual file is a bit set: 1 for reading, 2 for writing, 4 for appending,
+//! 8 for truncating, 16 for creating and 32 for creating exclusively.
+
+use crate::{
+    InterpreterState,
+    clock::VirtualClock,
+    file_handle::{FileHandle, OpenMode},
+    heap_allocator::HeapAllocator,
+    register_file::RegisterFile,
+    rng::Rng,
+    sandbox::{Node, Sandbox, VirtualFile},
+};
+use anyhow::{Context, Error, Result};
+use seaside_error::EngineError;
+use seaside_type_aliases::Address;
+use std::{
+    cell::RefCell,
+    fs::File,
+    io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::{Duration, SystemTime},
+};
+
+const MAGIC: [u8; 8] = *b"SEASNAP\0";
+const VERSION: u32 = 5;
+
+/// A file or directory the program had open when a [`Snapshot`] was taken.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OpenFile {
+    pub fd: u32,
+    /// The absolute path a host file or directory was opened from, or the path of a virtual one
+    /// within the sandbox.
+    pub path: PathBuf,
+    pub kind: OpenFileKind,
+}
+
+/// What an [`OpenFile`] refers to.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum OpenFileKind {
+    /// A host file. Only its location is recorded, not its contents, so the file is reopened at
+    /// the same offset when the snapshot is restored.
+    Host {
+        /// The flags the file was opened with.
+        flags: u32,
+        /// How far into the file the program had read or written.
+        offset: u64,
+    },
+    /// A file in an in-memory sandbox.
+    Virtual {
+        /// The index of the file's contents in [`VirtualFiles::files`].
+        file: u32,
+        mode: OpenMode,
+        offset: u64,
+    },
+    /// A host or virtual directory.
+    Directory {
+        /// The names of the entries the program hasn't read yet.
+        entries: Vec<String>,
+    },
+}
+
+/// The contents of an in-memory sandbox when a [`Snapshot`] was taken.
+#[derive(Clone, Debug, Default)]
+pub struct VirtualFiles {
+    /// Every virtual file, including ones the program deleted or replaced but still has open.
+    pub files: Vec<VirtualFile>,
+    /// Every file and directory besides the root, in alphabetical order.
+    pub nodes: Vec<(PathBuf, VirtualNode)>,
+}
+
+/// A file or directory in [`VirtualFiles`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum VirtualNode {
+    /// A file, given as an index into [`VirtualFiles::files`].
+    File(u32),
+    Directory {
+        permissions: u32,
+    },
+}
+
+/// The state of a program at a single point in time.
+#[derive(Clone)]
+pub struct Snapshot {
+    pub pc: Address,
+    pub exit_code: Option<u8>,
+    pub registers: RegisterFile,
+    /// The contents of each memory region, keyed by the address it starts at.
+    pub regions: Vec<(Address, Vec<u8>)>,
+    pub next_heap_address: Address,
+    pub free_heap_space: u32,
+    pub heap_allocator: HeapAllocator,
+    pub next_fd: u32,
+    pub files: Vec<OpenFile>,
+    /// The contents of the sandbox, if it keeps files in memory.
+    pub vfs: Option<VirtualFiles>,
+    /// The internal state of each random number generator, keyed by its id.
+    pub rngs: Vec<(u32, u64)>,
+    /// The time on the virtual clock in nanoseconds, if there is one.
+    pub clock: Option<u64>,
+}
+
+impl Snapshot {
+    /// Captures the current state of `state`.
+    pub fn capture(state: &InterpreterState) -> Result<Self> {
+        let (files, vfs) = state.open_files()?;
+        Ok(Self {
+            pc: state.pc,
+            exit_code: state.exit_code,
+            registers: state.registers.clone(),
+            regions: state.memory.dump_regions(),
+            next_heap_address: *state.memory.next_heap_address(),
+            free_heap_space: *state.memory.free_heap_space(),
+            heap_allocator: state.heap_allocator.clone(),
+            next_fd: state.next_fd,
+            files,
+            vfs,
+            rngs: state.rng_states(),
+            clock: state.clock.as_ref().map(VirtualClock::nanos),
+        })
+    }
+
+    /// Overwrites `state` with the contents of this snapshot.
+    ///
+    /// Any files `state` has open are closed, and the ones recorded in the snapshot are reopened in
+    /// their place. If the sandbox keeps files in memory, its contents are replaced with the ones
+    /// recorded in the snapshot. If the snapshot doesn't fit the loaded program or a file can't be
+    /// reopened, `state` is left untouched.
+    pub fn apply(&self, state: &mut InterpreterState) -> Result<()> {
+        for (start, bytes) in &self.regions {
+            if state.memory.region_len(*start) != Some(bytes.len()) {
+                return Err(Error::new(EngineError::ParsingFailure)).with_context(|| {
+                    format!(
+                        "snapshot has {} bytes at 0x{start:08x}, which doesn't match the loaded \
+                         program",
+                        bytes.len(),
+                    )
+                });
+            }
+        }
+        if matches!(state.sandbox, Sandbox::Memory(_)) != self.vfs.is_some() {
+            return Err(Error::new(EngineError::ParsingFailure))
+                .context("snapshot was taken with a different sandbox mode");
+        }
+        state.reopen_files(&self.files, self.vfs.as_ref())?;
+        for (start, bytes) in &self.regions {
+            // Every region was checked above, so this can't fail.
+            state.memory.restore_region(*start, bytes);
+        }
+        state.restore_rng_states(&self.rngs);
+        state.pc = self.pc;
+        state.exit_code = self.exit_code;
+        state.registers = self.registers.clone();
+        *state.memory.next_heap_address_mut() = self.next_heap_address;
+        *state.memory.free_heap_space_mut() = self.free_heap_space;
+        state.heap_allocator = self.heap_allocator.clone();
+        state.next_fd = self.next_fd;
+        if let (Some(clock), Some(nanos)) = (&mut state.clock, self.clock) {
+            clock.set_nanos(nanos);
+        }
+        state.stdout_pending_flush = false;
+        state.last_branch = None;
+        Ok(())
+    }
+
+    /// Writes this snapshot to the file at `path`, replacing it if it already exists.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Reads a snapshot from the file at `path`.
+    pub fn load(path: &Path) -> Result<Self> {
+        Self::read_from(&mut BufReader::new(File::open(path)?))
+            .with_context(|| format!("failed to read snapshot from {}", path.display()))
+    }
+
+    pub fn write_to<W>(&self, writer: &mut W) -> std::io::Result<()>
+    where
+        W: Write,
+    {
+        writer.write_all(&MAGIC)?;
+        write_u32(writer, VERSION)?;
+        write_u32(writer, self.pc)?;
+        writer.write_all(&match self.exit_code {
+            Some(exit_code) => [1, exit_code],
+            None => [0, 0],
+        })?;
+        for word in self.registers.to_words() {
+            write_u32(writer, word)?;
+        }
+        write_u32(writer, self.next_heap_address)?;
+        write_u32(writer, self.free_heap_space)?;
+        let allocator_words = self.heap_allocator.to_words();
+        write_u32(writer, allocator_words.len() as u32)?;
+        for word in allocator_words {
+            write_u32(writer, word)?;
+        }
+        write_u32(writer, self.regions.len() as u32)?;
+        for (start, bytes) in &self.regions {
+            write_u32(writer, *start)?;
+            write_u32(writer, bytes.len() as u32)?;
+            writer.write_all(bytes)?;
+        }
+        write_u32(writer, self.next_fd)?;
+        write_u32(writer, self.files.len() as u32)?;
+        for file in &self.files {
+            write_u32(writer, file.fd)?;
+            write_string(writer, &file.path.to_string_lossy())?;
+            match &file.kind {
+                &OpenFileKind::Host { flags, offset } => {
+                    writer.write_all(&[0])?;
+                    write_u32(writer, flags)?;
+                    write_u64(writer, offset)?;
+                }
+                &OpenFileKind::Virtual { file, mode, offset } => {
+                    writer.write_all(&[1])?;
+                    write_u32(writer, file)?;
+                    writer.write_all(&[mode_to_bits(mode)])?;
+                    write_u64(writer, offset)?;
+                }
+                OpenFileKind::Directory { entries } => {
+                    writer.write_all(&[2])?;
+                    write_u32(writer, entries.len() as u32)?;
+                    for entry in entries {
+                        write_string(writer, entry)?;
+                    }
+                }
+            }
+        }
+        writer.write_all(&[self.vfs.is_some() as u8])?;
+        if let Some(vfs) = &self.vfs {
+            write_u32(writer, vfs.files.len() as u32)?;
+            for file in &vfs.files {
+                let status = file.status();
+                let modified = status
+                    .modified
+                    .and_then(|modified| modified.duration_since(SystemTime::UNIX_EPOCH).ok())
+                    .map_or(0, |duration| duration.as_nanos() as u64);
+                write_u32(writer, status.permissions)?;
+                write_u64(writer, modified)?;
+                write_u32(writer, file.data().len() as u32)?;
+                writer.write_all(file.data())?;
+            }
+            write_u32(writer, vfs.nodes.len() as u32)?;
+            for (path, node) in &vfs.nodes {
+                write_string(writer, &path.to_string_lossy())?;
+                match *node {
+                    VirtualNode::File(file) => {
+                        writer.write_all(&[0])?;
+                        write_u32(writer, file)?;
+                    }
+                    VirtualNode::Directory { permissions } => {
+                        writer.write_all(&[1])?;
+                        write_u32(writer, permissions)?;
+                    }
+                }
+            }
+        }
+        write_u32(writer, self.rngs.len() as u32)?;
+        for &(id, state) in &self.rngs {
+            write_u32(writer, id)?;
+            write_u64(writer, state)?;
+        }
+        writer.write_all(&[self.clock.is_some() as u8])?;
+        write_u64(writer, self.clock.unwrap_or(0))?;
+        Ok(())
+    }
+
+    pub fn read_from<R>(reader: &mut R) -> Result<Self>
+    where
+        R: Read,
+    {
+        let mut magic = [0; 8];
+        reader.read_exact(&mut magic)?;
+        if magic != MAGIC {
+            return Err(Error::new(EngineError::ParsingFailure).context("not a seaside snapshot"));
+        }
+        let version = read_u32(reader)?;
+        if version != VERSION {
+            return Err(Error::new(EngineError::OutdatedVersion))
+                .with_context(|| format!("unsupported snapshot version {version}"));
+        }
+        let pc = read_u32(reader)?;
+        let mut exit_code = [0; 2];
+        reader.read_exact(&mut exit_code)?;
+        let exit_code = (exit_code[0] != 0).then_some(exit_code[1]);
+        let mut words = [0; RegisterFile::N_WORDS];
+        for word in &mut words {
+            *word = read_u32(reader)?;
+        }
+        let registers = RegisterFile::from_words(&words);
+        let next_heap_address = read_u32(reader)?;
+        let free_heap_space = read_u32(reader)?;
+        let n_allocator_words = read_u32(reader)?;
+        let mut allocator_words = Vec::new();
+        for _ in 0..n_allocator_words {
+            allocator_words.push(read_u32(reader)?);
+        }
+        let mut allocator_words = allocator_words.into_iter();
+        let heap_allocator =
+            HeapAllocator::from_words(|| allocator_words.next()).ok_or_else(|| {
+                Error::new(EngineError::ParsingFailure).context("heap allocator state is truncated")
+            })?;
+        let n_regions = read_u32(reader)?;
+        let mut regions = Vec::new();
+        for _ in 0..n_regions {
+            let start = read_u32(reader)?;
+            let len = read_u32(reader)?;
+            regions.push((start, read_bytes(reader, len)?));
+        }
+        let next_fd = read_u32(reader)?;
+        let n_files = read_u32(reader)?;
+        let mut files = Vec::new();
+        for _ in 0..n_files {
+            let fd = read_u32(reader)?;
+            let path = PathBuf::from(read_string(reader, "file path")?);
+            let kind = match read_u8(reader)? {
+                0 => OpenFileKind::Host {
+                    flags: read_u32(reader)?,
+                    offset: read_u64(reader)?,
+                },
+                1 => OpenFileKind::Virtual {
+                    file: read_u32(reader)?,
+                    mode: mode_from_bits(read_u8(reader)?),
+                    offset: read_u64(reader)?,
+                },
+                2 => {
+                    let n_entries = read_u32(reader)?;
+                    let mut entries = Vec::new();
+                    for _ in 0..n_entries {
+                        entries.push(read_string(reader, "directory entry")?);
+                    }
+                    OpenFileKind::Directory { entries }
+                }
+                kind => {
+                    return Err(Error::new(EngineError::ParsingFailure))
+                        .with_context(|| format!("unknown kind of open file {kind}"));
+                }
+            };
+            files.push(OpenFile { fd, path, kind });
+        }
+        let vfs = match read_u8(reader)? {
+            0 => None,
+            _ => Some(read_virtual_files(reader)?),
+        };
+        let n_rngs = read_u32(reader)?;
+        let mut rngs = Vec::new();
+        for _ in 0..n_rngs {
+            let id = read_u32(reader)?;
+            rngs.push((id, read_u64(reader)?));
+        }
+        let mut has_clock = [0];
+        reader.read_exact(&mut has_clock)?;
+        let clock = read_u64(reader)?;
+        let clock = (has_clock[0] != 0).then_some(clock);
+        Ok(Self {
+            pc,
+            exit_code,
+            registers,
+            regions,
+            next_heap_address,
+            free_heap_space,
+            heap_allocator,
+            next_fd,
+            files,
+            vfs,
+            rngs,
+            clock,
+        })
+    }
+}
+
+fn read_virtual_files<R: Read>(reader: &mut R) -> Result<VirtualFiles> {
+    let n_files = read_u32(reader)?;
+    let mut files = Vec::new();
+    for _ in 0..n_files {
+        let permissions = read_u32(reader)?;
+        let modified = SystemTime::UNIX_EPOCH + Duration::from_nanos(read_u64(reader)?);
+        let len = read_u32(reader)?;
+        files.push(VirtualFile::new(
+            read_bytes(reader, len)?,
+            permissions,
+            modified,
+        ));
+    }
+    let n_nodes = read_u32(reader)?;
+    let mut nodes = Vec::new();
+    for _ in 0..n_nodes {
+        let path = PathBuf::from(read_string(reader, "file path")?);
+        let node = match read_u8(reader)? {
+            0 => VirtualNode::File(read_u32(reader)?),
+            1 => VirtualNode::Directory {
+                permissions: read_u32(reader)?,
+            },
+            kind => {
+                return Err(Error::new(EngineError::ParsingFailure))
+                    .with_context(|| format!("unknown kind of virtual node {kind}"));
+            }
+        };
+        nodes.push((path, node));
+    }
+    Ok(VirtualFiles { files, nodes })
+}
+
+fn mode_to_bits(mode: OpenMode) -> u8 {
+    [
+        mode.read,
+        mode.write,
+        mode.append,
+        mode.truncate,
+        mode.create,
+        mode.create_new,
+    ]
+    .into_iter()
+    .enumerate()
+    .fold(0, |bits, (i, set)| bits | ((set as u8) << i))
+}
+
+fn mode_from_bits(bits: u8) -> OpenMode {
+    OpenMode {
+        read: bits & 1 != 0,
+        write: bits & 2 != 0,
+        append: bits & 4 != 0,
+        truncate: bits & 8 != 0,
+        create: bits & 16 != 0,
+        create_new: bits & 32 != 0,
+    }
+}
+
+impl InterpreterState {
+    /// Lists the files and directories currently open, ordered by file descriptor, along with the
+    /// contents of the sandbox if it keeps files in memory.
+    pub(crate) fn open_files(&self) -> Result<(Vec<OpenFile>, Option<VirtualFiles>)> {
+        // Virtual files are shared between the file system and the handles open on them, so each
+        // one is only recorded once.
+        let mut virtual_files: Vec<Rc<RefCell<VirtualFile>>> = Vec::new();
+        let vfs = self.sandbox.vfs().map(|vfs| {
+            let nodes = vfs
+                .nodes()
+                .map(|(path, node)| {
+                    let node = match node {
+                        Node::File(file) => {
+                            virtual_files.push(Rc::clone(file));
+                            VirtualNode::File(virtual_files.len() as u32 - 1)
+                        }
+                        &Node::Directory { permissions } => VirtualNode::Directory { permissions },
+                    };
+                    (path.to_path_buf(), node)
+                })
+                .collect();
+            VirtualFiles {
+                files: Vec::new(),
+                nodes,
+            }
+        });
+        // Going in order of file descriptor keeps the indices of deleted files consistent.
+        let mut handles: Vec<(&u32, &FileHandle)> = self.files.iter().collect();
+        handles.sort_by_key(|&(&fd, _)| fd);
+        let mut files = Vec::new();
+        for (&fd, handle) in handles {
+            let (path, kind) = match handle {
+                FileHandle::File { file, path, flags } => {
+                    let mut host_file: &File = file;
+                    let offset = host_file
+                        .stream_position()
+                        .with_context(|| format!("failed to get offset into {}", path.display()))?;
+                    let kind = OpenFileKind::Host {
+                        flags: *flags,
+                        offset,
+                    };
+                    (path, kind)
+                }
+                FileHandle::Virtual {
+                    file,
+                    path,
+                    mode,
+                    offset,
+                } => {
+                    let index = match virtual_files
+                        .iter()
+                        .position(|other| Rc::ptr_eq(other, file))
+                    {
+                        Some(index) => index,
+                        None => {
+                            virtual_files.push(Rc::clone(file));
+                            virtual_files.len() - 1
+                        }
+                    };
+                    let kind = OpenFileKind::Virtual {
+                        file: index as u32,
+                        mode: *mode,
+                        offset: *offset,
+                    };
+                    (path, kind)
+                }
+                FileHandle::Directory { entries, path } => {
+                    let entries = entries.iter().cloned().collect();
+                    (path, OpenFileKind::Directory { entries })
+                }
+                _ => continue,
+            };
+            files.push(OpenFile {
+                fd,
+                path: path.clone(),
+                kind,
+            });
+        }
+        let vfs = vfs.map(|vfs| VirtualFiles {
+            files: virtual_files
+                .iter()
+                .map(|file| file.borrow().clone())
+                .collect(),
+            ..vfs
+        });
+        Ok((files, vfs))
+    }
+
+    /// Makes the open files match `files`. If `vfs` is given, the contents of an in-memory sandbox
+    /// are replaced with it, and virtual files are looked up in it.
+    ///
+    /// Host files that are already open at the right descriptor are simply moved to the recorded
+    /// offset. The rest are closed or reopened as needed. Files are reopened before any are
+    /// closed, so if one can't be reopened, the open files are left as they were.
+    pub(crate) fn reopen_files(
+        &mut self,
+        files: &[OpenFile],
+        vfs: Option<&VirtualFiles>,
+    ) -> Result<()> {
+        let virtual_files: Vec<Rc<RefCell<VirtualFile>>> = vfs
+            .map(|vfs| {
+                vfs.files
+                    .iter()
+                    .map(|file| Rc::new(RefCell::new(file.clone())))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let virtual_file = |index: u32| {
+            virtual_files
+                .get(index as usize)
+                .ok_or_else(|| {
+                    Error::new(EngineError::ParsingFailure)
+                        .context(format!("virtual file {index} doesn't exist"))
+                })
+                .map(Rc::clone)
+        };
+        let mut nodes = Vec::new();
+        for (path, node) in vfs.iter().flat_map(|vfs| &vfs.nodes) {
+            let node = match *node {
+                VirtualNode::File(index) => Node::File(virtual_file(index)?),
+                VirtualNode::Directory { permissions } => Node::Directory { permissions },
+            };
+            nodes.push((path.clone(), node));
+        }
+        let mut reopened = Vec::new();
+        for file in files {
+            let handle = match &file.kind {
+                &OpenFileKind::Host { flags, .. } => {
+                    let already_open = matches!(
+                        self.files.get(&file.fd),
+                        Some(FileHandle::File { path, .. }) if *path == file.path
+                    );
+                    if already_open {
+                        continue;
+                    }
+                    FileHandle::open(file.path.clone(), flags)
+                        .with_context(|| format!("failed to reopen {}", file.path.display()))?
+                }
+                &OpenFileKind::Virtual {
+                    file: index,
+                    mode,
+                    offset,
+                } => FileHandle::Virtual {
+                    file: virtual_file(index)?,
+                    path: file.path.clone(),
+                    mode,
+                    offset,
+                },
+                OpenFileKind::Directory { entries } => FileHandle::Directory {
+                    entries: entries.iter().cloned().collect(),
+                    path: file.path.clone(),
+                },
+            };
+            reopened.push((file.fd, handle));
+        }
+        if let (Sandbox::Memory(sandbox_vfs), Some(_)) = (&mut self.sandbox, vfs) {
+            sandbox_vfs.replace_nodes(nodes);
+        }
+        self.files.retain(|fd, handle| match handle {
+            FileHandle::File { path, .. } => files.iter().any(|file| {
+                file.fd == *fd
+                    && file.path == *path
+                    && matches!(file.kind, OpenFileKind::Host { .. })
+            }),
+            FileHandle::Virtual { .. } | FileHandle::Directory { .. } => false,
+            _ => true,
+        });
+        self.files.extend(reopened);
+        for file in files {
+            if let (
+                Some(FileHandle::File {
+                    file: host_file, ..
+                }),
+                &OpenFileKind::Host { offset, .. },
+            ) = (self.files.get_mut(&file.fd), &file.kind)
+            {
+                host_file.seek(SeekFrom::Start(offset))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Gets the internal state of each random number generator, ordered by id.
+    pub(crate) fn rng_states(&self) -> Vec<(u32, u64)> {
+        let mut rngs: Vec<(u32, u64)> = self
+            .rngs
+            .iter()
+            .map(|(&id, rng)| (id, rng.state()))
+            .collect();
+        rngs.sort();
+        rngs
+    }
+
+    /// Replaces every random number generator with the ones described by `rngs`.
+    pub(crate) fn restore_rng_states(&mut self, rngs: &[(u32, u64)]) {
+        self.rngs = rngs
+            .iter()
+            .map(|&(id, state)| (id, Rng::from_state(state)))
+            .collect();
+    }
+}
+
+pub(crate) fn write_u32<W: Write>(writer: &mut W, value: u32) -> std::io::Result<()> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+pub(crate) fn write_u64<W: Write>(writer: &mut W, value: u64) -> std::io::Result<()> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+fn write_string<W: Write>(writer: &mut W, string: &str) -> std::io::Result<()> {
+    write_u32(writer, string.len() as u32)?;
+    writer.write_all(string.as_bytes())
+}
+
+fn read_u8<R: Read>(reader: &mut R) -> std::io::Result<u8> {
+    let mut byte = [0];
+    reader.read_exact(&mut byte)?;
+    Ok(byte[0])
+}
+
+pub(crate) fn read_u32<R: Read>(reader: &mut R) -> std::io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+pub(crate) fn read_u64<R: Read>(reader: &mut R) -> std::io::Result<u64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+pub(crate) fn read_bytes<R: Read>(reader: &mut R, len: u32) -> std::io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    reader.by_ref().take(len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() != len as usize {
+        return Err(std::io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(bytes)
+}
+
+/// Reads a string prefixed with its length, using `what` to describe it if it isn't valid UTF-8.
+fn read_string<R: Read>(reader: &mut R, what: &str) -> Result<String> {
+    let len = read_u32(reader)?;
+    String::from_utf8(read_bytes(reader, len)?).map_err(|_| {
+        Error::new(EngineError::ParsingFailure).context(format!("{what} is not valid UTF-8"))
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::Snapshot;
+    use crate::{Interpreter, Startup};
+    use seaside_config::{
+        Config, Features, MemoryMap, RegisterDefaults,
+        features::{
+            SandboxMode, Service,
+            services::spim::{Spim, System},
+        },
+    };
+    use seaside_int_utils::{AllZeroes, Endian};
+    use std::{
+        fs,
+        io::Write,
+        path::{Path, PathBuf},
+        time::SystemTime,
+    };
+
+    /// Loads `text` as the text segment of an interpreter with the default features, as adjusted
+    /// by `configure`. The program is written to a directory named after `name`, which is
+    /// returned so the test can remove it.
+    pub(crate) fn interpreter(
+        name: &str,
+        text: &[u32],
+        configure: impl FnOnce(&mut Config),
+    ) -> (Interpreter, PathBuf) {
+        let mut config = Config {
+            endian: Endian::Little,
+            features: Features::default(),
+            memory_map: MemoryMap::default(),
+            register_defaults: RegisterDefaults::default(),
+            ..Config::all_zeroes()
+        };
+        configure(&mut config);
+        let directory = std::env::temp_dir().join(format!("seaside-{name}-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let bytes: Vec<u8> = text.iter().flat_map(|word| word.to_le_bytes()).collect();
+        fs::write(directory.join("text"), bytes).unwrap();
+        let interpreter = Interpreter::init(
+            &config,
+            directory.join("text"),
+            None,
+            None,
+            None,
+            None,
+            Startup::default(),
+        )
+        .unwrap();
+        (interpreter, directory)
+    }
+
+    /// Allocates 64 bytes with `sbrk`, then fills them with a countdown from 16, taking 69 steps.
+    pub(crate) const FILL: [u32; 9] = [
+        0x2402_0009, // addiu $v0, $zero, 9
+        0x2404_0040, // addiu $a0, $zero, 64
+        0x0000_000c, // syscall
+        0x0040_4021, // addu $t0, $v0, $zero
+        0x2409_0010, // addiu $t1, $zero, 16
+        0xad09_0000, // loop: sw $t1, 0($t0)
+        0x2508_0004, // addiu $t0, $t0, 4
+        0x2529_ffff, // addiu $t1, $t1, -1
+        0x1520_fffc, // bne $t1, $zero, loop
+    ];
+
+    /// The offset of the loop in [`FILL`] from the start of the program.
+    pub(crate) const FILL_LOOP: u32 = 20;
+
+    /// Loads [`FILL`] with `sbrk` bound to service 9 and the undo history enabled.
+    pub(crate) fn fill(name: &str) -> (Interpreter, PathBuf) {
+        interpreter(name, &FILL, |config| {
+            let sbrk = Service::Spim(Spim::System(System::Sbrk));
+            config.features.services.insert(9, sbrk);
+            config.features.history.enabled = true;
+        })
+    }
+
+    /// Serializes everything a snapshot records about `interpreter`.
+    pub(crate) fn state_of(interpreter: &Interpreter) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        interpreter
+            .snapshot()
+            .unwrap()
+            .write_to(&mut bytes)
+            .unwrap();
+        bytes
+    }
+
+    fn in_memory(config: &mut Config) {
+        config.features.sandbox.mode = SandboxMode::Memory;
+    }
+
+    #[test]
+    fn snapshot_restores_virtual_files() {
+        let (mut original, directory) = interpreter("virtual-files", &[0], in_memory);
+        let state = &mut original.state;
+        let now = SystemTime::UNIX_EPOCH;
+        state.sandbox.create_dir(Path::new("notes"), 0o700).unwrap();
+        let mut kept = state.sandbox.open(Path::new("notes/kept"), 1, now).unwrap();
+        kept.write_all(b"kept").unwrap();
+        let mut deleted = state.sandbox.open(Path::new("deleted"), 1, now).unwrap();
+        deleted.write_all(b"deleted").unwrap();
+        state.sandbox.remove_file(Path::new("deleted")).unwrap();
+        let listing = state.sandbox.open_directory(Path::new(".")).unwrap();
+        state.files.extend([(3, kept), (4, deleted), (5, listing)]);
+        state.next_fd = 6;
+        let saved = state_of(&original);
+        let snapshot = Snapshot::read_from(&mut saved.as_slice()).unwrap();
+
+        let (mut resumed, _) = interpreter("virtual-files", &[0], in_memory);
+        resumed.restore(&snapshot).unwrap();
+        assert_eq!(state_of(&resumed), saved);
+        // The restored handles share their files with the restored file system.
+        let file = resumed.state.files.get_mut(&3).unwrap();
+        file.write_all(b"!").unwrap();
+        let vfs = resumed.state.sandbox.vfs().unwrap();
+        assert_eq!(vfs.read(Path::new("notes/kept")), Some(b"kept!".to_vec()));
+        assert_eq!(vfs.read(Path::new("deleted")), None);
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn snapshot_refuses_different_sandbox_mode() {
+        let (original, directory) = interpreter("sandbox-mode", &[0], in_memory);
+        let snapshot = original.snapshot().unwrap();
+        let (mut resumed, _) = interpreter("sandbox-mode", &[0], |_| {});
+        assert!(resumed.restore(&snapshot).is_err());
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn snapshot_round_trips_through_file() {
+        let (mut interpreter, directory) = fill("snapshot");
+        interpreter.run_for(20).unwrap();
+        let saved = state_of(&interpreter);
+        let path = directory.join("snapshot");
+        interpreter.snapshot().unwrap().save(&path).unwrap();
+        let snapshot = Snapshot::load(&path).unwrap();
+        interpreter.run_for(20).unwrap();
+        assert_ne!(state_of(&interpreter), saved);
+        interpreter.restore(&snapshot).unwrap();
+        assert_eq!(state_of(&interpreter), saved);
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn snapshot_that_does_not_fit_changes_nothing() {
+        let (mut interpreter, directory) = fill("bad-snapshot");
+        let mut snapshot = interpreter.snapshot().unwrap();
+        interpreter.run_for(20).unwrap();
+        let before = state_of(&interpreter);
+        // Only the last region is the wrong size, so every other one would be overwritten if
+        // regions were restored as they were checked.
+        snapshot.regions.last_mut().unwrap().1.pop();
+        assert!(interpreter.restore(&snapshot).is_err());
+        assert_eq!(state_of(&interpreter), before);
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,337 @@
+//! Compatibility with SPIM.
+//!
+//! Provides [`Interpreter::load_spim_kernel`], which loads a kernel modelled on the `exceptions.s`
+//! file SPIM bundles, along with the lenient parsing SPIM's read services use.
+//!
+//! The kernel's exception handler sits at the configured exception handler address. Like SPIM's,
+//! it prints the exception code and a description of it, exits if the program counter itself was
+//! misaligned, and otherwise resumes at the instruction after the one that raised the exception.
+//! `__start` follows the handler:
+//!
+//! ```text
+//! __start:
+//!     lw    $a0, 0($sp)           # argc
+//!     addiu $a1, $sp, 4           # argv
+//!     addiu $a2, $a1, 4
+//!     sll   $v0, $a0, 2
+//!     addu  $a2, $a2, $v0         # envp
+//!     lui   $at, %hi(main)
+//!     addiu $at, $at, %lo(main)
+//!     jalr  $at
+//!     nop
+//!     li    $v0, 10               # exit
+//!     syscall
+//! ```
+//!
+//! The table of exception descriptions, the words the handler saves `$v0` and `$a0` in and the
+//! strings it prints are placed at the start of kdata, in that order.
+
+use crate::Interpreter;
+use anyhow::{Error, Result};
+use seaside_config::Config;
+use seaside_constants::{
+    Opcode,
+    fn_codes::{Coprocessor0Fn, SpecialFn},
+    register::{Coprocessor0Register, CpuRegister},
+};
+use seaside_error::EngineError;
+use seaside_int_utils::Endian;
+use seaside_type_aliases::{Address, Instruction};
+
+/// SPIM's description of each exception code, padded the way SPIM prints them.
+const DESCRIPTIONS: [&str; 32] = [
+    "  [Interrupt] ",
+    "  [TLB]",
+    "  [TLB]",
+    "  [TLB]",
+    "  [Address error in inst/data fetch] ",
+    "  [Address error in store] ",
+    "  [Bad instruction address] ",
+    "  [Bad data address] ",
+    "  [Error in syscall] ",
+    "  [Breakpoint] ",
+    "  [Reserved instruction] ",
+    "",
+    "  [Arithmetic overflow] ",
+    "  [Trap] ",
+    "",
+    "  [Floating point] ",
+    "",
+    "",
+    "  [Coproc 2]",
+    "",
+    "",
+    "",
+    "  [MDMX]",
+    "  [Watch]",
+    "  [Machine check]",
+    "",
+    "",
+    "",
+    "",
+    "",
+    "  [Cache]",
+    "",
+];
+
+/// Where the handler saves `$v0` and `$a0`, relative to the start of kdata.
+const SAVED_V0_OFFSET: u32 = 4 * DESCRIPTIONS.len() as u32;
+const SAVED_A0_OFFSET: u32 = SAVED_V0_OFFSET + 4;
+/// Where the strings the handler prints start, relative to the start of kdata.
+const STRINGS_OFFSET: u32 = SAVED_A0_OFFSET + 4;
+
+const NOP: Instruction = 0;
+const SYSCALL: Instruction = SpecialFn::SystemCall as Instruction;
+const ERET: Instruction = 0x42000018;
+
+impl Interpreter {
+    /// Loads the default SPIM kernel into kernel space, then starts the program at its `__start`
+    /// routine, which calls `main` and exits once it returns.
+    ///
+    /// See the [module documentation](self) for what the kernel does.
+    pub fn load_spim_kernel(&mut self, config: &Config, main: Address) -> Result<()> {
+        let segments = &config.memory_map.segments;
+        let ktext_base = segments.ktext.range.base;
+        let kdata_base = segments.kdata.range.base;
+
+        let mut kdata = vec![0u8; STRINGS_OFFSET as usize];
+        let mut push_string = |string: &str| {
+            let address = kdata_base + kdata.len() as Address;
+            kdata.extend(string.as_bytes());
+            kdata.push(b'\0');
+            address
+        };
+        let exception_message = push_string("  Exception ");
+        let ignored_message = push_string(" occurred and ignored\n");
+        let descriptions: Vec<Address> = DESCRIPTIONS.into_iter().map(push_string).collect();
+        for (i, address) in descriptions.into_iter().enumerate() {
+            let bytes = match config.endian {
+                Endian::Little => address.to_le_bytes(),
+                Endian::Big => address.to_be_bytes(),
+            };
+            kdata[4 * i..4 * i + 4].copy_from_slice(&bytes);
+        }
+
+        let handler = exception_handler(
+            kdata_base + SAVED_V0_OFFSET,
+            kdata_base + SAVED_A0_OFFSET,
+            kdata_base,
+            exception_message,
+            ignored_message,
+        );
+        let start = start(main);
+        let fits = |handler_address: &Address| {
+            let offset = handler_address.wrapping_sub(ktext_base);
+            let size = 4 * (handler.len() + start.len()) as u32;
+            handler_address % 4 == 0
+                && *handler_address >= ktext_base
+                && offset
+                    .checked_add(size)
+                    .is_some_and(|end| end <= segments.ktext.allocate)
+        };
+        let Some(handler_address) = config.memory_map.exception_handler.filter(fits) else {
+            return Err(Error::new(EngineError::InvalidConfig)
+                .context("the SPIM kernel doesn't fit in ktext at the exception handler address"));
+        };
+        if kdata.len() as u32 > segments.kdata.allocate {
+            return Err(Error::new(EngineError::InvalidConfig)
+                .context("the SPIM kernel doesn't fit in kdata"));
+        }
+
+        let mut ktext = vec![NOP; ((handler_address - ktext_base) / 4) as usize];
+        ktext.extend(handler);
+        let start_address = ktext_base + 4 * ktext.len() as Address;
+        ktext.extend(start);
+        self.state
+            .memory
+            .load_kernel(&ktext, kdata, handler_address);
+        self.state.pc = start_address;
+        Ok(())
+    }
+}
+
+/// Assembles SPIM's default exception handler.
+fn exception_handler(
+    saved_v0: Address,
+    saved_a0: Address,
+    descriptions: Address,
+    exception_message: Address,
+    ignored_message: Address,
+) -> Vec<Instruction> {
+    use CpuRegister::*;
+    use Opcode::*;
+    use SpecialFn::*;
+    vec![
+        special(AsmTemp, Zero, Kernel1, 0, AddUnsigned), // move  $k1, $at
+        immediate(LoadUpperImmediate, Zero, AsmTemp, hi(saved_v0)),
+        immediate(StoreWord, AsmTemp, Val0, lo(saved_v0)),
+        immediate(LoadUpperImmediate, Zero, AsmTemp, hi(saved_a0)),
+        immediate(StoreWord, AsmTemp, Arg0, lo(saved_a0)),
+        coprocessor_0(
+            Coprocessor0Fn::MoveFromCoprocessor0,
+            Kernel0,
+            Coprocessor0Register::CAUSE,
+        ),
+        // Print "Exception <code>" and a description of the exception.
+        immediate(OrImmediate, Zero, Val0, 4),
+        immediate(LoadUpperImmediate, Zero, AsmTemp, hi(exception_message)),
+        immediate(AddImmediateUnsigned, AsmTemp, Arg0, lo(exception_message)),
+        SYSCALL,
+        immediate(OrImmediate, Zero, Val0, 1),
+        special(Zero, Kernel0, Arg0, 2, ShiftRightLogical),
+        immediate(AndImmediate, Arg0, Arg0, 0x1f),
+        SYSCALL,
+        immediate(OrImmediate, Zero, Val0, 4),
+        immediate(AndImmediate, Kernel0, Arg0, 0x3c),
+        immediate(LoadUpperImmediate, Zero, AsmTemp, hi(descriptions)),
+        special(AsmTemp, Arg0, AsmTemp, 0, AddUnsigned),
+        immediate(LoadWord, AsmTemp, Arg0, lo(descriptions)),
+        SYSCALL,
+        // A bad program counter is only recoverable if it's word-aligned.
+        immediate(AndImmediate, Kernel0, Arg0, 0x3c),
+        immediate(OrImmediate, Zero, AsmTemp, 6 << 2),
+        immediate(BranchNotEqual, Arg0, AsmTemp, 7), // bne   $a0, $at, ok_pc
+        NOP,
+        coprocessor_0(
+            Coprocessor0Fn::MoveFromCoprocessor0,
+            Arg0,
+            Coprocessor0Register::EPC,
+        ),
+        immediate(AndImmediate, Arg0, Arg0, 0x3),
+        immediate(BranchEqual, Arg0, Zero, 3), // beq   $a0, $zero, ok_pc
+        NOP,
+        immediate(OrImmediate, Zero, Val0, 10),
+        SYSCALL,
+        // ok_pc:
+        immediate(OrImmediate, Zero, Val0, 4),
+        immediate(LoadUpperImmediate, Zero, AsmTemp, hi(ignored_message)),
+        immediate(AddImmediateUnsigned, AsmTemp, Arg0, lo(ignored_message)),
+        SYSCALL,
+        // Skip the faulting instruction, then restore the registers used and return.
+        coprocessor_0(
+            Coprocessor0Fn::MoveFromCoprocessor0,
+            Kernel0,
+            Coprocessor0Register::EPC,
+        ),
+        immediate(AddImmediateUnsigned, Kernel0, Kernel0, 4),
+        coprocessor_0(
+            Coprocessor0Fn::MoveToCoprocessor0,
+            Kernel0,
+            Coprocessor0Register::EPC,
+        ),
+        immediate(LoadUpperImmediate, Zero, AsmTemp, hi(saved_v0)),
+        immediate(LoadWord, AsmTemp, Val0, lo(saved_v0)),
+        immediate(LoadUpperImmediate, Zero, AsmTemp, hi(saved_a0)),
+        immediate(LoadWord, AsmTemp, Arg0, lo(saved_a0)),
+        special(Kernel1, Zero, AsmTemp, 0, AddUnsigned), // move  $at, $k1
+        coprocessor_0(
+            Coprocessor0Fn::MoveToCoprocessor0,
+            Zero,
+            Coprocessor0Register::CAUSE,
+        ),
+        coprocessor_0(
+            Coprocessor0Fn::MoveFromCoprocessor0,
+            Kernel0,
+            Coprocessor0Register::STATUS,
+        ),
+        immediate(OrImmediate, Kernel0, Kernel0, 0x1),
+        coprocessor_0(
+            Coprocessor0Fn::MoveToCoprocessor0,
+            Kernel0,
+            Coprocessor0Register::STATUS,
+        ),
+        ERET,
+    ]
+}
+
+/// Assembles SPIM's `__start` routine, which calls `main`.
+fn start(main: Address) -> Vec<Instruction> {
+    use CpuRegister::*;
+    use Opcode::*;
+    use SpecialFn::*;
+    vec![
+        immediate(LoadWord, StackPtr, Arg0, 0),
+        immediate(AddImmediateUnsigned, StackPtr, Arg1, 4),
+        immediate(AddImmediateUnsigned, Arg1, Arg2, 4),
+        special(Zero, Arg0, Val0, 2, ShiftLeftLogical),
+        special(Arg2, Val0, Arg2, 0, AddUnsigned),
+        immediate(LoadUpperImmediate, Zero, AsmTemp, hi(main)),
+        immediate(AddImmediateUnsigned, AsmTemp, AsmTemp, lo(main)),
+        special(AsmTemp, Zero, ReturnAddr, 0, JumpAndLinkRegister),
+        NOP,
+        immediate(OrImmediate, Zero, Val0, 10),
+        SYSCALL,
+    ]
+}
+
+const fn special(
+    rs: CpuRegister,
+    rt: CpuRegister,
+    rd: CpuRegister,
+    shamt: u32,
+    r#fn: SpecialFn,
+) -> Instruction {
+    ((rs as u32) << 21) | ((rt as u32) << 16) | ((rd as u32) << 11) | (shamt << 6) | r#fn as u32
+}
+
+const fn immediate(
+    opcode: Opcode,
+    rs: CpuRegister,
+    rt: CpuRegister,
+    immediate: u16,
+) -> Instruction {
+    ((opcode as u32) << 26) | ((rs as u32) << 21) | ((rt as u32) << 16) | immediate as u32
+}
+
+const fn coprocessor_0(r#fn: Coprocessor0Fn, rt: CpuRegister, rd: u8) -> Instruction {
+    ((Opcode::Coprocessor0 as u32) << 26)
+        | ((r#fn as u32) << 21)
+        | ((rt as u32) << 16)
+        | ((rd as u32) << 11)
+}
+
+/// Gets the upper half of `address`, adjusted for the sign extension of its [lower half](lo).
+const fn hi(address: Address) -> u16 {
+    (address.wrapping_add(0x8000) >> 16) as u16
+}
+
+const fn lo(address: Address) -> u16 {
+    address as u16
+}
+
+/// Parses the integer at the start of `string` the way C's `atol` does, as SPIM does.
+///
+/// Leading whitespace is skipped and anything after the digits is ignored. If there are no
+/// digits, this returns 0.
+pub fn parse_int(string: &str) -> i32 {
+    let string = string.trim_start();
+    let (negative, digits) = match string.as_bytes().first() {
+        Some(b'-') => (true, &string[1..]),
+        Some(b'+') => (false, &string[1..]),
+        _ => (false, string),
+    };
+    let magnitude = digits
+        .bytes()
+        .take_while(u8::is_ascii_digit)
+        .fold(0i64, |value, digit| {
+            value.wrapping_mul(10).wrapping_add((digit - b'0') as i64)
+        });
+    (if negative {
+        magnitude.wrapping_neg()
+    } else {
+        magnitude
+    }) as i32
+}
+
+/// Parses the number at the start of `string` the way C's `atof` does, as SPIM does.
+///
+/// Leading whitespace is skipped and anything after the number is ignored. If there is no number,
+/// this returns 0.
+pub fn parse_float(string: &str) -> f64 {
+    let string = string.trim_start();
+    (1..=string.len())
+        .rev()
+        .filter(|&end| string.is_char_boundary(end))
+        .find_map(|end| string[..end].parse().ok())
+        .unwrap_or(0.0)
+}
@@ -0,0 +1,205 @@
+//! Stack discipline checks.
+//!
+//! Provides the struct [`StackChecker`], which follows `$sp` through every procedure call and
+//! return to catch procedures that don't clean up their stack frames, as well as `$sp` falling
+//! out of alignment. Stack overflows are diagnosed separately, since they end the program.
+
+use crate::{
+    Exception, InterpreterState,
+    calls::{self, Transfer},
+    register_file::IndexByRegister,
+    symbols::SymbolTable,
+};
+use core::fmt::{Display, Formatter, Result as FmtResult};
+use seaside_config::features::StackCheckOptions;
+use seaside_constants::register::CpuRegister;
+use seaside_type_aliases::{Address, Instruction};
+use std::{collections::HashMap, io::Write};
+
+/// A procedure call that hasn't returned yet.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Frame {
+    /// The address of the instruction that made the call.
+    pub call_site: Address,
+    /// The address of the procedure that was called.
+    pub entry: Address,
+    /// The value of `$sp` when the procedure was called.
+    pub sp: Address,
+}
+
+/// A way a program can misuse its stack.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum StackWarningKind {
+    /// A procedure returned with a different `$sp` than it was called with.
+    UnrestoredSp,
+    /// `$sp` wasn't a multiple of the configured alignment.
+    MisalignedSp,
+}
+
+impl Display for StackWarningKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::UnrestoredSp => write!(f, "$sp not restored"),
+            Self::MisalignedSp => write!(f, "$sp misaligned"),
+        }
+    }
+}
+
+/// A misuse of the stack found by [`StackChecker`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StackWarning {
+    pub kind: StackWarningKind,
+    /// The address of the instruction that returned or moved `$sp`.
+    pub pc: Address,
+    /// The value of `$sp` the first time this warning was raised.
+    pub sp: Address,
+    /// The procedure calls in progress the first time this warning was raised, innermost first.
+    ///
+    /// For [`StackWarningKind::UnrestoredSp`], this includes the call being returned from.
+    pub call_chain: Vec<Frame>,
+    /// How many times the instruction raised this warning.
+    pub occurrences: u64,
+}
+
+/// Watches how a program uses `$sp`.
+#[derive(Clone, Debug)]
+pub struct StackChecker {
+    /// Whether to check that procedures restore `$sp` before returning.
+    check_calls: bool,
+    /// The alignment `$sp` must have, or 0 if it isn't checked.
+    alignment: u32,
+    /// The procedure calls in progress, outermost first.
+    frames: Vec<Frame>,
+    last_sp: Address,
+    warnings: Vec<StackWarning>,
+    /// Maps each instruction and kind of warning it raised to the warning's index in `warnings`.
+    warning_indices: HashMap<(Address, StackWarningKind), usize>,
+}
+
+impl StackChecker {
+    pub fn new(options: &StackCheckOptions, sp: Address) -> Self {
+        Self {
+            check_calls: options.enabled,
+            alignment: options.alignment,
+            frames: Vec::new(),
+            last_sp: sp,
+            warnings: Vec::new(),
+            warning_indices: HashMap::new(),
+        }
+    }
+
+    /// The procedure calls in progress, outermost first.
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    /// The warnings raised so far, in the order they were first raised.
+    pub fn warnings(&self) -> &[StackWarning] {
+        &self.warnings
+    }
+
+    /// Records that `instruction` was executed at `pc`, leaving `$sp` at `sp`, after which
+    /// execution continued at `next_pc`.
+    pub fn record(&mut self, pc: Address, instruction: Instruction, next_pc: Address, sp: Address) {
+        match calls::transfer(pc, instruction, next_pc) {
+            Some(Transfer::Call(entry)) => self.frames.push(Frame {
+                call_site: pc,
+                entry,
+                sp,
+            }),
+            Some(Transfer::Return) => {
+                if self.check_calls && self.frames.last().is_some_and(|frame| frame.sp != sp) {
+                    self.warn(StackWarningKind::UnrestoredSp, pc, sp);
+                }
+                self.frames.pop();
+            }
+            None => {}
+        }
+        if self.alignment != 0 && sp != self.last_sp && sp % self.alignment != 0 {
+            self.warn(StackWarningKind::MisalignedSp, pc, sp);
+        }
+        self.last_sp = sp;
+    }
+
+    /// Writes every warning raised so far to `writer`.
+    ///
+    /// `symbols` is used to describe code in terms of the labels enclosing it.
+    pub fn write_report<W>(&self, writer: &mut W, symbols: &SymbolTable) -> std::io::Result<()>
+    where
+        W: Write,
+    {
+        let total: u64 = self
+            .warnings
+            .iter()
+            .map(|warning| warning.occurrences)
+            .sum();
+        writeln!(
+            writer,
+            "stack checks: {total} warnings from {} instructions",
+            self.warnings.len(),
+        )?;
+        for warning in &self.warnings {
+            write!(
+                writer,
+                "  {} by {} (pc: 0x{:08x}): $sp is 0x{:08x}",
+                warning.kind,
+                symbols.describe(warning.pc),
+                warning.pc,
+                warning.sp,
+            )?;
+            match (warning.kind, warning.call_chain.first()) {
+                (StackWarningKind::UnrestoredSp, Some(frame)) => {
+                    write!(writer, " on return, but was 0x{:08x} when called", frame.sp)?;
+                }
+                (StackWarningKind::MisalignedSp, _) => {
+                    write!(writer, ", which isn't a multiple of {}", self.alignment)?;
+                }
+                _ => {}
+            }
+            if warning.occurrences > 1 {
+                write!(writer, " ({} times)", warning.occurrences)?;
+            }
+            writeln!(writer)?;
+            for frame in &warning.call_chain {
+                writeln!(
+                    writer,
+                    "    in {}, called from {}",
+                    symbols.describe(frame.entry),
+                    symbols.describe(frame.call_site),
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    fn warn(&mut self, kind: StackWarningKind, pc: Address, sp: Address) {
+        match self.warning_indices.get(&(pc, kind)) {
+            Some(&index) => self.warnings[index].occurrences += 1,
+            None => {
+                self.warning_indices.insert((pc, kind), self.warnings.len());
+                self.warnings.push(StackWarning {
+                    kind,
+                    pc,
+                    sp,
+                    call_chain: self.frames.iter().rev().copied().collect(),
+                    occurrences: 1,
+                });
+            }
+        }
+    }
+}
+
+impl InterpreterState {
+    /// Replaces `exception` with [`Exception::StackOverflow`] if it was caused by pushing onto a
+    /// stack that has already overflowed.
+    pub(crate) fn diagnose_stack_overflow(&self, exception: Exception) -> Exception {
+        let stack_start = self.memory.stack_addresses().start;
+        let sp: Address = self.registers.read(CpuRegister::StackPtr);
+        match exception {
+            Exception::InvalidStore(address) if (sp..stack_start).contains(&address) => {
+                Exception::StackOverflow(address)
+            }
+            exception => exception,
+        }
+    }
+}
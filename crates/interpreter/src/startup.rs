@@ -0,0 +1,104 @@
+//! The initial stack a program starts with.
+//!
+//! Like a MIPS Linux process, a program given arguments or environment variables finds them at the
+//! top of its stack. Starting from `$sp` and moving up, the stack holds:
+//!
+//! ```text
+//! $sp ->  argc
+//!         argv[0], ..., argv[argc - 1]    pointers to nul-terminated strings
+//!         0
+//!         envp[0], ..., envp[n - 1]       pointers to nul-terminated "KEY=VALUE" strings
+//!         0
+//!         auxv                            pairs of words: a type followed by a value
+//!         0, 0                            the pair ending auxv
+//!         padding
+//!         strings                         argv strings, then envp strings
+//! ```
+//!
+//! Each word is aligned, and `$sp` is aligned to 8 bytes. The auxiliary vector holds
+//! [`AT_PAGESZ`] and [`AT_ENTRY`].
+//!
+//! For convenience, `$a0`, `$a1` and `$a2` are set to `argc`, the address of `argv` and the
+//! address of `envp`, so programs can treat them like the arguments to `main`. To stay compatible
+//! with MARS, programs without arguments or environment variables start with an untouched stack.
+
+use crate::{Exception, InterpreterState, memory::Region, register_file::IndexByRegister};
+use seaside_constants::register::CpuRegister;
+use seaside_type_aliases::Address;
+
+/// Marks the end of the auxiliary vector.
+pub const AT_NULL: u32 = 0;
+/// The auxiliary vector entry holding the page size.
+pub const AT_PAGESZ: u32 = 6;
+/// The auxiliary vector entry holding the address of the program's entry point.
+pub const AT_ENTRY: u32 = 9;
+
+/// The page size reported in the auxiliary vector.
+const PAGE_SIZE: u32 = 4096;
+
+/// What a program is started with.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Startup {
+    /// The program's arguments.
+    pub argv: Vec<String>,
+    /// The program's environment variables, each of the form "KEY=VALUE".
+    pub envp: Vec<String>,
+}
+
+impl InterpreterState {
+    /// Lays out the arguments and environment in `startup` at the top of the stack, which ends at
+    /// `stack_base`.
+    ///
+    /// See the [module documentation](self) for the layout.
+    pub fn init_process(&mut self, startup: Startup, stack_base: Address) -> Result<(), Exception> {
+        let Startup { argv, envp } = startup;
+        if argv.is_empty() && envp.is_empty() {
+            return Ok(());
+        }
+
+        let mut current: Address = stack_base;
+        let mut arg_addresses: Vec<Address> = Vec::with_capacity(argv.len());
+        for arg in &argv {
+            current = self.push_string(current, arg)?;
+            arg_addresses.push(current);
+        }
+        let mut var_addresses: Vec<Address> = Vec::with_capacity(envp.len());
+        for var in &envp {
+            current = self.push_string(current, var)?;
+            var_addresses.push(current);
+        }
+        let auxv = [AT_PAGESZ, PAGE_SIZE, AT_ENTRY, self.pc, AT_NULL, 0];
+
+        let mut words: Vec<u32> = vec![argv.len() as u32];
+        words.extend(&arg_addresses);
+        words.push(0);
+        words.extend(&var_addresses);
+        words.push(0);
+        words.extend(auxv);
+
+        let sp: Address = self.registers.read(CpuRegister::StackPtr);
+        let frame_top = Address::min(sp, current & !3);
+        let frame_base = (frame_top - 4 * words.len() as u32) & !7;
+        for (i, &word) in words.iter().enumerate() {
+            self.memory
+                .write_u32(frame_base + 4 * i as u32, word, true)?;
+        }
+        let argv_address = frame_base + 4;
+        let envp_address = argv_address + 4 * (arg_addresses.len() as u32 + 1);
+        self.registers.write(CpuRegister::StackPtr, frame_base);
+        self.registers.write(CpuRegister::Arg0, argv.len() as u32);
+        self.registers.write(CpuRegister::Arg1, argv_address);
+        self.registers.write(CpuRegister::Arg2, envp_address);
+        self.envp = envp_address;
+        Ok(())
+    }
+
+    /// Writes `string` and a nul byte just below `top`, returning the address it starts at.
+    fn push_string(&mut self, top: Address, string: &str) -> Result<Address, Exception> {
+        let start = top - string.len() as u32 - 1;
+        for (i, byte) in string.bytes().chain([b'\0']).enumerate() {
+            self.memory.write_u8(start + i as u32, byte)?;
+        }
+        Ok(start)
+    }
+}
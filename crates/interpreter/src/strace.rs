@@ -0,0 +1,344 @@
+//! Tracing for system services.
+//!
+//! Provides methods on [`InterpreterState`] that decode the arguments and return values of a
+//! [`Service`] into human-readable strings. These are used by [`Interpreter::syscall`] when
+//! [`Interpreter::strace`] is enabled.
+//!
+//! [`Interpreter::syscall`]: crate::Interpreter::syscall
+//! [`Interpreter::strace`]: crate::Interpreter::strace
+
+use crate::{
+    InterpreterState, errno,
+    memory::Region,
+    register_file::{IndexByRegister, TryIndexByRegister},
+};
+use core::ffi::CStr;
+use seaside_config::features::{
+    Service,
+    services::{
+        mars::{self, Mars},
+        posix::{self, Posix},
+        seaside::{self, Seaside},
+        spim::{self, Spim},
+    },
+};
+use seaside_constants::register::{CpuRegister, FpuRegister};
+use seaside_type_aliases::Address;
+
+/// The maximum number of bytes of a buffer to show in a trace.
+const MAX_BUFFER_PREVIEW: usize = 32;
+
+impl InterpreterState {
+    /// Describes the arguments `service` would receive if it were invoked right now.
+    pub fn describe_service_args(&self, service: Service) -> String {
+        match service {
+            Service::Spim(Spim::Print(print)) => match print {
+                spim::Print::Int => format!("{}", self.cpu_i32(CpuRegister::Arg0)),
+                spim::Print::Float => format!("{}", self.fpu_f32(FpuRegister::F12)),
+                spim::Print::Double => self.fpu_f64(FpuRegister::F12),
+                spim::Print::Char => self.describe_char(self.cpu_u32(CpuRegister::Arg0)),
+                spim::Print::String => self.describe_string(self.cpu_u32(CpuRegister::Arg0)),
+            },
+            Service::Spim(Spim::Read(read)) => match read {
+                spim::Read::String => format!(
+                    "buffer: 0x{:08x}, length: {}",
+                    self.cpu_u32(CpuRegister::Arg0),
+                    self.cpu_u32(CpuRegister::Arg1),
+                ),
+                _ => String::new(),
+            },
+            Service::Spim(Spim::File(file)) => match file {
+                spim::File::Open => format!(
+                    "{}, flags: {}, mode: {}",
+                    self.describe_string(self.cpu_u32(CpuRegister::Arg0)),
+                    self.cpu_u32(CpuRegister::Arg1),
+                    self.cpu_u32(CpuRegister::Arg2),
+                ),
+                spim::File::Read => format!(
+                    "fd: {}, buffer: 0x{:08x}, length: {}",
+                    self.cpu_i32(CpuRegister::Arg0),
+                    self.cpu_u32(CpuRegister::Arg1),
+                    self.cpu_u32(CpuRegister::Arg2),
+                ),
+                spim::File::Write => format!(
+                    "fd: {}, {}, length: {}",
+                    self.cpu_i32(CpuRegister::Arg0),
+                    self.describe_buffer(
+                        self.cpu_u32(CpuRegister::Arg1),
+                        self.cpu_u32(CpuRegister::Arg2),
+                    ),
+                    self.cpu_u32(CpuRegister::Arg2),
+                ),
+                spim::File::Close => format!("fd: {}", self.cpu_i32(CpuRegister::Arg0)),
+            },
+            Service::Spim(Spim::System(system)) => match system {
+                spim::System::Sbrk => format!("{}", self.cpu_i32(CpuRegister::Arg0)),
+                spim::System::Exit => String::new(),
+                spim::System::Exit2 => format!("{}", self.cpu_i32(CpuRegister::Arg0)),
+            },
+            Service::Mars(Mars::Print(print)) => match print {
+                mars::Print::Uint => format!("{}", self.cpu_u32(CpuRegister::Arg0)),
+                mars::Print::Bin | mars::Print::Hex => {
+                    format!("0x{:08x}", self.cpu_u32(CpuRegister::Arg0))
+                }
+            },
+            Service::Mars(Mars::System(system)) => match system {
+                mars::System::Time => String::new(),
+                mars::System::Sleep => format!("{} ms", self.cpu_u32(CpuRegister::Arg0)),
+                mars::System::MidiOut | mars::System::MidiOutSync => format!(
+                    "pitch: {}, duration: {} ms, instrument: {}, volume: {}",
+                    self.cpu_u32(CpuRegister::Arg0),
+                    self.cpu_u32(CpuRegister::Arg1),
+                    self.cpu_u32(CpuRegister::Arg2),
+                    self.cpu_u32(CpuRegister::Arg3),
+                ),
+            },
+            Service::Mars(Mars::Random(random)) => match random {
+                mars::Random::SetSeed => format!(
+                    "id: {}, seed: {}",
+                    self.cpu_u32(CpuRegister::Arg0),
+                    self.cpu_u32(CpuRegister::Arg1),
+                ),
+                mars::Random::RandIntRange => format!(
+                    "id: {}, upper bound: {}",
+                    self.cpu_u32(CpuRegister::Arg0),
+                    self.cpu_u32(CpuRegister::Arg1),
+                ),
+                mars::Random::RandInt | mars::Random::RandFloat | mars::Random::RandDouble => {
+                    format!("id: {}", self.cpu_u32(CpuRegister::Arg0))
+                }
+            },
+            Service::Mars(Mars::Dialog(mars::Dialog::Input(_))) => {
+                self.describe_string(self.cpu_u32(CpuRegister::Arg0))
+            }
+            Service::Mars(Mars::Dialog(mars::Dialog::Message(message))) => {
+                let prompt = self.describe_string(self.cpu_u32(CpuRegister::Arg0));
+                match message {
+                    mars::MessageDialog::General => {
+                        format!("{prompt}, type: {}", self.cpu_u32(CpuRegister::Arg1))
+                    }
+                    mars::MessageDialog::Int => {
+                        format!("{prompt}, {}", self.cpu_i32(CpuRegister::Arg1))
+                    }
+                    mars::MessageDialog::Float => {
+                        format!("{prompt}, {}", self.fpu_f32(FpuRegister::F12))
+                    }
+                    mars::MessageDialog::Double => {
+                        format!("{prompt}, {}", self.fpu_f64(FpuRegister::F12))
+                    }
+                    mars::MessageDialog::String => format!(
+                        "{prompt}, {}",
+                        self.describe_string(self.cpu_u32(CpuRegister::Arg1))
+                    ),
+                }
+            }
+            Service::Posix(Posix::File(file)) => match file {
+                posix::File::Open => format!(
+                    "{}, flags: 0x{:x}, mode: 0o{:o}",
+                    self.describe_string(self.cpu_u32(CpuRegister::Arg0)),
+                    self.cpu_u32(CpuRegister::Arg1),
+                    self.cpu_u32(CpuRegister::Arg2),
+                ),
+                posix::File::Lseek => format!(
+                    "fd: {}, offset: {}, whence: {}",
+                    self.cpu_i32(CpuRegister::Arg0),
+                    self.cpu_i32(CpuRegister::Arg1),
+                    self.cpu_u32(CpuRegister::Arg2),
+                ),
+                posix::File::Fstat => format!(
+                    "fd: {}, buffer: 0x{:08x}",
+                    self.cpu_i32(CpuRegister::Arg0),
+                    self.cpu_u32(CpuRegister::Arg1),
+                ),
+                posix::File::Stat => format!(
+                    "{}, buffer: 0x{:08x}",
+                    self.describe_string(self.cpu_u32(CpuRegister::Arg0)),
+                    self.cpu_u32(CpuRegister::Arg1),
+                ),
+                posix::File::Unlink => self.describe_string(self.cpu_u32(CpuRegister::Arg0)),
+                posix::File::Rename => format!(
+                    "{}, {}",
+                    self.describe_string(self.cpu_u32(CpuRegister::Arg0)),
+                    self.describe_string(self.cpu_u32(CpuRegister::Arg1)),
+                ),
+            },
+            Service::Posix(Posix::Directory(directory)) => match directory {
+                posix::Directory::Make => format!(
+                    "{}, mode: 0o{:o}",
+                    self.describe_string(self.cpu_u32(CpuRegister::Arg0)),
+                    self.cpu_u32(CpuRegister::Arg1),
+                ),
+                posix::Directory::Remove | posix::Directory::Open => {
+                    self.describe_string(self.cpu_u32(CpuRegister::Arg0))
+                }
+                posix::Directory::Read => format!(
+                    "fd: {}, buffer: 0x{:08x}, length: {}",
+                    self.cpu_i32(CpuRegister::Arg0),
+                    self.cpu_u32(CpuRegister::Arg1),
+                    self.cpu_u32(CpuRegister::Arg2),
+                ),
+            },
+            Service::Seaside(Seaside::Heap(heap)) => match heap {
+                seaside::Heap::Malloc => format!("size: {}", self.cpu_u32(CpuRegister::Arg0)),
+                seaside::Heap::Calloc => format!(
+                    "count: {}, size: {}",
+                    self.cpu_u32(CpuRegister::Arg0),
+                    self.cpu_u32(CpuRegister::Arg1),
+                ),
+                seaside::Heap::Realloc => format!(
+                    "0x{:08x}, size: {}",
+                    self.cpu_u32(CpuRegister::Arg0),
+                    self.cpu_u32(CpuRegister::Arg1),
+                ),
+                seaside::Heap::Free => format!("0x{:08x}", self.cpu_u32(CpuRegister::Arg0)),
+            },
+            Service::Seaside(Seaside::Process(seaside::Process::Getenv)) => {
+                self.describe_string(self.cpu_u32(CpuRegister::Arg0))
+            }
+        }
+    }
+
+    /// Describes the values `service` returned, assuming it was just invoked.
+    ///
+    /// Returns [`None`] if `service` doesn't return anything.
+    pub fn describe_service_returns(&self, service: Service) -> Option<String> {
+        Some(match service {
+            Service::Spim(Spim::Read(read)) => match read {
+                spim::Read::Int => format!("$v0 = {}", self.cpu_i32(CpuRegister::Val0)),
+                spim::Read::Float => format!("$f0 = {}", self.fpu_f32(FpuRegister::F0)),
+                spim::Read::Double => format!("$f0 = {}", self.fpu_f64(FpuRegister::F0)),
+                spim::Read::Char => format!(
+                    "$v0 = {}",
+                    self.describe_char(self.cpu_u32(CpuRegister::Val0))
+                ),
+                spim::Read::String => format!(
+                    "buffer = {}",
+                    self.describe_string(self.cpu_u32(CpuRegister::Arg0))
+                ),
+            },
+            Service::Spim(Spim::File(spim::File::Open | spim::File::Read | spim::File::Write)) => {
+                format!("$v0 = {}", self.cpu_i32(CpuRegister::Val0))
+            }
+            Service::Spim(Spim::System(spim::System::Sbrk)) => {
+                format!("$v0 = 0x{:08x}", self.cpu_u32(CpuRegister::Val0))
+            }
+            Service::Mars(Mars::System(mars::System::Time)) => format!(
+                "$a0 = {}, $a1 = {}",
+                self.cpu_u32(CpuRegister::Arg0),
+                self.cpu_u32(CpuRegister::Arg1),
+            ),
+            Service::Mars(Mars::Random(random)) => match random {
+                mars::Random::SetSeed => return None,
+                mars::Random::RandInt | mars::Random::RandIntRange => {
+                    format!("$a0 = {}", self.cpu_u32(CpuRegister::Arg0))
+                }
+                mars::Random::RandFloat => format!("$f0 = {}", self.fpu_f32(FpuRegister::F0)),
+                mars::Random::RandDouble => format!("$f0 = {}", self.fpu_f64(FpuRegister::F0)),
+            },
+            Service::Mars(Mars::Dialog(mars::Dialog::Input(input))) => match input {
+                mars::InputDialog::Confirm => {
+                    format!("$a0 = {}", self.cpu_i32(CpuRegister::Arg0))
+                }
+                mars::InputDialog::Int => format!(
+                    "$a0 = {}, $a1 = {}",
+                    self.cpu_i32(CpuRegister::Arg0),
+                    self.cpu_i32(CpuRegister::Arg1),
+                ),
+                mars::InputDialog::Float => format!(
+                    "$f0 = {}, $a1 = {}",
+                    self.fpu_f32(FpuRegister::F0),
+                    self.cpu_i32(CpuRegister::Arg1),
+                ),
+                mars::InputDialog::Double => format!(
+                    "$f0 = {}, $a1 = {}",
+                    self.fpu_f64(FpuRegister::F0),
+                    self.cpu_i32(CpuRegister::Arg1),
+                ),
+                mars::InputDialog::String => {
+                    format!("$a1 = {}", self.cpu_i32(CpuRegister::Arg1))
+                }
+            },
+            Service::Posix(Posix::Directory(posix::Directory::Read)) => {
+                match self.cpu_i32(CpuRegister::Val0) {
+                    length if length > 0 => format!(
+                        "$v0 = {length}, buffer = {}",
+                        self.describe_string(self.cpu_u32(CpuRegister::Arg1))
+                    ),
+                    result => format!("$v0 = {}", describe_posix_result(result)),
+                }
+            }
+            Service::Seaside(Seaside::Heap(
+                seaside::Heap::Malloc | seaside::Heap::Calloc | seaside::Heap::Realloc,
+            )) => format!("$v0 = 0x{:08x}", self.cpu_u32(CpuRegister::Val0)),
+            Service::Seaside(Seaside::Process(seaside::Process::Getenv)) => {
+                match self.cpu_u32(CpuRegister::Val0) {
+                    0 => String::from("$v0 = NULL"),
+                    address => format!("$v0 = {}", self.describe_string(address)),
+                }
+            }
+            Service::Posix(_) => format!(
+                "$v0 = {}",
+                describe_posix_result(self.cpu_i32(CpuRegister::Val0))
+            ),
+            _ => return None,
+        })
+    }
+
+    fn cpu_u32(&self, register: CpuRegister) -> u32 {
+        self.registers.read(register)
+    }
+
+    fn cpu_i32(&self, register: CpuRegister) -> i32 {
+        self.registers.read(register)
+    }
+
+    fn fpu_f32(&self, register: FpuRegister) -> f32 {
+        self.registers.read(register)
+    }
+
+    fn fpu_f64(&self, register: FpuRegister) -> String {
+        match <_ as TryIndexByRegister<_, f64>>::try_read(&self.registers, register) {
+            Ok(x) => x.to_string(),
+            Err(_) => String::from("<misaligned>"),
+        }
+    }
+
+    fn describe_char(&self, c: u32) -> String {
+        match char::from_u32(c) {
+            Some(c) => format!("{c:?}"),
+            None => format!("0x{c:08x} (invalid char)"),
+        }
+    }
+
+    fn describe_string(&self, address: Address) -> String {
+        let Ok(slice) = self.memory.get_slice(address) else {
+            return format!("0x{address:08x} (invalid address)");
+        };
+        match CStr::from_bytes_until_nul(slice) {
+            Ok(string) => format!("{:?}", string.to_string_lossy()),
+            Err(_) => format!("0x{address:08x} (missing nul byte)"),
+        }
+    }
+
+    fn describe_buffer(&self, address: Address, length: u32) -> String {
+        let Ok(slice) = self.memory.get_slice(address) else {
+            return format!("0x{address:08x} (invalid address)");
+        };
+        let length = usize::min(length as usize, slice.len());
+        let shown = usize::min(length, MAX_BUFFER_PREVIEW);
+        let preview = format!("{:?}", String::from_utf8_lossy(&slice[..shown]));
+        if shown < length {
+            format!("{preview}...")
+        } else {
+            preview
+        }
+    }
+}
+
+/// Describes the return value of a POSIX-like service, naming the error number if it failed.
+fn describe_posix_result(result: i32) -> String {
+    match errno::name(result.wrapping_neg()) {
+        Some(name) if result < 0 => format!("{result} ({name})"),
+        _ => result.to_string(),
+    }
+}
@@ -0,0 +1,88 @@
+//! Labels associated with a program.
+//!
+//! Provides the struct [`SymbolTable`], which maps addresses to the labels defined at them. These
+//! are read from the 'symbols' file the assembler places alongside the segments it builds.
+
+use anyhow::{Error, Result};
+use seaside_error::EngineError;
+use seaside_type_aliases::Address;
+use std::{collections::BTreeMap, fs::read_to_string, path::Path};
+
+/// Maps addresses to the labels defined at them.
+#[derive(Clone, Debug, Default)]
+pub struct SymbolTable {
+    symbols: BTreeMap<Address, String>,
+}
+
+impl SymbolTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads a symbol table from the file at `path`.
+    ///
+    /// Each line of the file must contain the address of a label in hexadecimal, followed by a
+    /// space and the label itself.
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let mut symbol_table = Self::new();
+        for (line_number, line) in read_to_string(path)?.lines().enumerate() {
+            let (address, label) = line
+                .split_once(' ')
+                .and_then(|(address, label)| {
+                    Some((Address::from_str_radix(address, 16).ok()?, label))
+                })
+                .ok_or_else(|| {
+                    Error::new(EngineError::ParsingFailure).context(format!(
+                        "malformed symbol on line {} of {}",
+                        line_number + 1,
+                        path.display(),
+                    ))
+                })?;
+            symbol_table.insert(address, label.to_string());
+        }
+        Ok(symbol_table)
+    }
+
+    /// Records that `label` is defined at `address`.
+    ///
+    /// If another label is already defined at `address`, the original is kept.
+    pub fn insert(&mut self, address: Address, label: String) {
+        self.symbols.entry(address).or_insert(label);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    /// Gets the label defined at exactly `address`, if there is one.
+    pub fn get(&self, address: Address) -> Option<&str> {
+        self.symbols.get(&address).map(String::as_str)
+    }
+
+    /// Gets the address `label` is defined at, if it's defined.
+    pub fn address_of(&self, label: &str) -> Option<Address> {
+        self.symbols
+            .iter()
+            .find(|(_, defined)| *defined == label)
+            .map(|(&address, _)| address)
+    }
+
+    /// Gets the closest label defined at or before `address` along with the offset of `address`
+    /// from it.
+    pub fn enclosing(&self, address: Address) -> Option<(&str, u32)> {
+        self.symbols
+            .range(..=address)
+            .next_back()
+            .map(|(&base, label)| (label.as_str(), address - base))
+    }
+
+    /// Describes `address` in terms of its enclosing label, falling back to hexadecimal if there
+    /// isn't one.
+    pub fn describe(&self, address: Address) -> String {
+        match self.enclosing(address) {
+            Some((label, 0)) => label.to_string(),
+            Some((label, offset)) => format!("{label}+{offset:#x}"),
+            None => format!("0x{address:08x}"),
+        }
+    }
+}
@@ -31,6 +31,15 @@ pub enum SyscallFailureKind {
     /// config.
     #[error("can't free memory from heap because it is disabled")]
     HeapFreeDisabled,
+    /// Attempted to free a heap block that was already freed.
+    #[error("double free of heap block at 0x{0:08x}")]
+    DoubleFree(u32),
+    /// Attempted to free or reallocate an address that was never returned by the heap allocator.
+    #[error("free of 0x{0:08x}, which isn't an allocated heap block")]
+    InvalidFree(u32),
+    /// The script bound to a service raised an error.
+    #[error("script for service {0} failed")]
+    ScriptFailed(u32),
     /// The current system time is before the unix epoch (1970-01-01T00:00:00).
     #[error("system time is before January 1, 1970")]
     BeforeUnixEpoch,
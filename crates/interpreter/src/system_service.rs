@@ -0,0 +1,33 @@
+//! System services provided by programs embedding the interpreter.
+//!
+//! Provides the trait [`SystemService`], which lets library users add services of their own with
+//! [`Interpreter::register_service`](crate::Interpreter::register_service), such as a grading
+//! harness that wants programs to submit their results through a `syscall`.
+
+use crate::{Exception, InterpreterState};
+
+/// A system service registered on an [`Interpreter`](crate::Interpreter) at run time.
+///
+/// Closures taking an [`InterpreterState`] implement this trait, so simple services don't need a
+/// type of their own.
+pub trait SystemService {
+    /// Performs the service.
+    ///
+    /// The pc has already moved past the `syscall` instruction, and `$v0` still holds the service
+    /// code.
+    fn call(&mut self, state: &mut InterpreterState) -> Result<(), Exception>;
+
+    /// The name strace uses for the service.
+    fn name(&self) -> &str {
+        "custom"
+    }
+}
+
+impl<F> SystemService for F
+where
+    F: FnMut(&mut InterpreterState) -> Result<(), Exception>,
+{
+    fn call(&mut self, state: &mut InterpreterState) -> Result<(), Exception> {
+        self(state)
+    }
+}
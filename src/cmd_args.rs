@@ -23,6 +23,8 @@ pub enum Commands {
     Assemble(AssemblyArgs),
     /// Disassembles the input machine code into human-readable assembly.
     Disassemble(DisassemblyArgs),
+    /// Examines the core dump left behind by a crashed program.
+    Inspect(InspectArgs),
     /// Prints the file path of the seaside executable.
     ExePath,
     /// Runs experimental code.
@@ -36,6 +38,113 @@ pub struct RunArgs {
     pub directory: PathBuf,
     /// A list of arguments to the program.
     pub argv: Vec<String>,
+    /// Logs each system service invoked by the program.
+    #[arg(long)]
+    pub strace: bool,
+    /// Reports execution statistics once the program finishes.
+    ///
+    /// Call stacks are also written to 'profile.folded' in the project directory, which can be
+    /// turned into a flamegraph.
+    #[arg(long)]
+    pub profile: bool,
+    /// Reports reads of uninitialized heap or stack memory and accesses to heap memory that
+    /// wasn't allocated with sbrk.
+    #[arg(long)]
+    pub memcheck: bool,
+    /// Restores the program to the state saved in a snapshot before running it.
+    #[arg(long, value_name = "SNAPSHOT")]
+    pub resume: Option<PathBuf>,
+    /// Saves a snapshot of the program to this path once it pauses or finishes.
+    #[arg(long, value_name = "PATH")]
+    pub snapshot: Option<PathBuf>,
+    /// Pauses the program after executing this many instructions.
+    ///
+    /// Use with '--snapshot' to continue the program later with '--resume'. A value of 0 captures
+    /// the program right after initialization.
+    #[arg(long, value_name = "N", requires = "snapshot")]
+    pub pause_after: Option<u64>,
+    /// Steps backwards this many instructions once the program pauses, finishes or crashes.
+    ///
+    /// Requires 'features.history.enabled', and can't be combined with '--profile', '--memcheck'
+    /// or other reports, since they aren't rewound. The rewound program is saved with '--snapshot', even
+    /// if it crashed, so the steps leading up to a crash can be examined with '--resume'.
+    #[arg(long, value_name = "N", requires = "snapshot")]
+    pub step_back: Option<u64>,
+    /// Steps backwards until the pc reaches this address. Can be given more than once.
+    ///
+    /// Applied after '--step-back'. Stops at whichever of these addresses or '--reverse-watch'
+    /// addresses is reached first.
+    #[arg(
+        long,
+        value_name = "ADDRESS",
+        requires = "snapshot",
+        value_parser = ValueParser::new(parse_u32),
+    )]
+    pub reverse_to: Vec<u32>,
+    /// Steps backwards until an instruction that wrote to this address is undone. Can be given
+    /// more than once.
+    #[arg(
+        long,
+        value_name = "ADDRESS",
+        requires = "snapshot",
+        value_parser = ValueParser::new(parse_u32),
+    )]
+    pub reverse_watch: Vec<u32>,
+    /// Answers the program's dialogs with the lines of this file instead of asking.
+    ///
+    /// Each input dialog consumes one line: the text to enter, 'yes' or 'no' for confirmation
+    /// dialogs, or '<cancel>' to cancel. Dialogs are cancelled once the file runs out.
+    #[arg(long, value_name = "PATH")]
+    pub dialog_script: Option<PathBuf>,
+    /// Records the notes played by the program's MIDI out services to this path once it pauses
+    /// or finishes.
+    ///
+    /// Paths ending in '.wav' get a synthesized recording; anything else gets a Standard MIDI
+    /// File. Notes are timed by a virtual clock, which only advances when the program sleeps or
+    /// plays a note synchronously.
+    #[arg(long, value_name = "PATH")]
+    pub midi_out: Option<PathBuf>,
+    /// Copies the contents of the in-memory sandbox to this directory once the program pauses or
+    /// finishes.
+    ///
+    /// Only has an effect when 'features.sandbox.mode' is 'memory'. Useful for grading what a
+    /// program wrote without letting it touch the real file system.
+    #[arg(long, value_name = "DIR")]
+    pub export_vfs: Option<PathBuf>,
+    /// Makes the run reproducible, overriding 'features.deterministic.enabled'.
+    ///
+    /// Time is kept by a virtual clock that advances with each instruction executed, sleeping
+    /// doesn't block, and every random number generator is derived from a master seed.
+    #[arg(long)]
+    pub deterministic: bool,
+    /// The master seed for deterministic mode, overriding 'features.deterministic.seed'.
+    ///
+    /// Implies '--deterministic'.
+    #[arg(long, value_name = "SEED")]
+    pub seed: Option<u64>,
+    /// Runs the program the way SPIM would, applying the SPIM preset to the config.
+    ///
+    /// The preset binds SPIM's service codes and uses SPIM's memory map. Unless the project has its
+    /// own kernel, a default one handles exceptions and starts the program at '__start', which
+    /// calls 'main'. argv[0] defaults to the project directory, as SPIM passes the program's path.
+    #[arg(long)]
+    pub spim: bool,
+    /// Passes '<NAME>' to the program as argv[0], ahead of its other arguments.
+    ///
+    /// Without this, argv only holds the arguments given after the project directory, as in MARS.
+    #[arg(long, value_name = "NAME")]
+    pub argv0: Option<String>,
+    /// Sets an environment variable for the program. Can be given more than once.
+    ///
+    /// The environment is laid out on the stack after argv, like a MIPS Linux process, and can be
+    /// searched with the 'seaside.process.getenv' service.
+    #[arg(long = "env", value_name = "KEY=VALUE", value_parser = ValueParser::new(parse_env_var))]
+    pub env: Vec<(String, String)>,
+    /// Passes seaside's own environment variables on to the program.
+    ///
+    /// Variables set with '--env' take precedence over inherited ones.
+    #[arg(long)]
+    pub inherit_env: bool,
 }
 
 #[derive(Args, Debug)]
@@ -67,6 +176,21 @@ pub struct DisassemblyTarget {
     pub segment: Option<PathBuf>,
 }
 
+#[derive(Args, Debug)]
+pub struct InspectArgs {
+    /// The path of a core dump.
+    pub core: PathBuf,
+    /// The number of instructions to disassemble on either side of the one that crashed.
+    #[arg(long, default_value_t = 5)]
+    pub context: u32,
+    /// Dumps memory starting at this address.
+    #[arg(long, alias = "addr", value_parser = ValueParser::new(parse_u32))]
+    pub memory: Option<u32>,
+    /// The number of bytes of memory to dump.
+    #[arg(long, default_value_t = 64, requires = "memory")]
+    pub length: u32,
+}
+
 fn parse_u32(input: &str) -> Result<Instruction, ParseIntError> {
     if let Some(bits) = input.strip_prefix("0b") {
         Instruction::from_str_radix(bits, 2)
@@ -78,3 +202,10 @@ fn parse_u32(input: &str) -> Result<Instruction, ParseIntError> {
         input.parse::<Instruction>()
     }
 }
+
+fn parse_env_var(input: &str) -> Result<(String, String), String> {
+    match input.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!("expected KEY=VALUE, found '{input}'")),
+    }
+}
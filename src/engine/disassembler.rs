@@ -4,7 +4,7 @@
 //! which disassemble their respective objects into a human-readable assembly representation.
 
 use anyhow::{Error, Result};
-use seaside_config::Config;
+use seaside_config::{Config, features::IsaOptions};
 use seaside_error::EngineError;
 use seaside_int_utils::ByteStream;
 use seaside_type_aliases::{Address, Instruction};
@@ -13,15 +13,25 @@ use std::path::PathBuf;
 /// Prints the human-readable assembly form of `instruction`.
 ///
 /// If `address` is not [`None`], that value is interpreted as the instruction's address for the
-/// purposes of branches and jumps.
-pub fn disassemble_instruction(instruction: Instruction, address: Option<Address>) -> Result<()> {
+/// purposes of branches and jumps. Instructions outside the instruction set described by `isa` are
+/// flagged with a comment.
+pub fn disassemble_instruction(
+    instruction: Instruction,
+    address: Option<Address>,
+    isa: &IsaOptions,
+) -> Result<()> {
     match seaside_disassembler::disassemble_advanced(
         instruction,
         address.unwrap_or_default(),
         address.is_some(),
     ) {
         Some(disassembly) => {
-            println!("{disassembly}");
+            match seaside_disassembler::mnemonic(instruction) {
+                Some(mnemonic) if !isa.allows(&mnemonic) => {
+                    println!("{disassembly}  # not in the configured instruction set")
+                }
+                _ => println!("{disassembly}"),
+            }
             Ok(())
         }
         None => Err(Error::new(EngineError::MalformedMachineCode)),
@@ -44,7 +54,7 @@ pub fn disassemble_segment(
     };
     let bytes = std::fs::read(segment)?;
     for instruction in ByteStream::<'_, u32>::new(&bytes, config.endian) {
-        disassemble_instruction(instruction, Some(address))?;
+        disassemble_instruction(instruction, Some(address), &config.features.isa)?;
         address += 4;
     }
     Ok(())
@@ -1,20 +1,35 @@
 //! Wraps the [`seaside_interpreter`] crate.
 //!
 //! Provides the wrapper functions [`init_interpreter`] and [`run`], which initialize and run the
-//! interpreter, respectively.
+//! interpreter, respectively. Runs can be paused and resumed with [`save_snapshot`] and
+//! [`resume`], and rewound with [`rewind`] once [`check_rewind`] allows it. Statistics collected
+//! while running can be reported with [`report_profile`], [`report_caches`], [`report_pipeline`], [`report_branch_predictors`],
+//! [`report_memcheck`], [`report_heap_leaks`], [`report_stack_checks`] and
+//! [`report_calling_convention`].
+//! Core dumps written when a program crashes can be examined with [`inspect`].
 
 use super::resolve_if_exists;
 use anyhow::{Context, Error, Result};
+use minimal_logging::macros::{grayln, warnln};
 use seaside_config::Config;
 use seaside_error::EngineError;
-use seaside_interpreter::Interpreter;
-use std::{env::set_current_dir, path::PathBuf};
+use seaside_interpreter::{
+    CoreDump, Interpreter, RegisterFile, Snapshot, Startup, StopReason, SymbolTable,
+};
+use seaside_type_aliases::Address;
+use std::{
+    collections::HashSet,
+    env::{set_current_dir, vars_os},
+    fs::File,
+    io::{Write, stderr},
+    path::{Path, PathBuf},
+};
 
 /// Initializes the interpreter in preparation for execution via the [`run`] function.
 pub fn init_interpreter(
     config: Config,
     mut directory: PathBuf,
-    argv: Vec<String>,
+    startup: Startup,
 ) -> Result<Interpreter> {
     if !directory.is_dir() {
         return Err(Error::new(EngineError::InvalidProjectDirectory))
@@ -38,22 +53,355 @@ pub fn init_interpreter(
     let data = resolve_if_exists(&directory, "data");
     let ktext = resolve_if_exists(&directory, "ktext");
     let kdata = resolve_if_exists(&directory, "kdata");
-    Interpreter::init(&config, text, r#extern, data, ktext, kdata, argv)
+    let has_kernel = ktext.is_some() || kdata.is_some();
+    let mut interpreter = Interpreter::init(&config, text, r#extern, data, ktext, kdata, startup)?;
+    if let Some(symbols) = resolve_if_exists(&directory, "symbols") {
+        interpreter.symbols = SymbolTable::from_file(&symbols)?;
+    }
+    if config.features.spim.enabled {
+        start_like_spim(&mut interpreter, &config, has_kernel)?;
+    }
+    Ok(interpreter)
+}
+
+/// Prepares `interpreter` to start the way SPIM starts programs.
+///
+/// Like SPIM given an exception file, a project with its own kernel starts at its `__start` label
+/// if it has one. Otherwise, the default SPIM kernel is loaded, and its `__start` routine calls
+/// `main`.
+fn start_like_spim(interpreter: &mut Interpreter, config: &Config, has_kernel: bool) -> Result<()> {
+    if has_kernel {
+        if let Some(start) = interpreter.symbols.address_of("__start") {
+            interpreter.state.pc = start;
+        }
+        return Ok(());
+    }
+    let main = match interpreter.symbols.address_of("main") {
+        Some(main) => main,
+        None => {
+            warnln!("no 'main' label, so '__start' will call the start of the text segment");
+            interpreter.state.memory.initial_pc()
+        }
+    };
+    interpreter.load_spim_kernel(config, main)
+}
+
+/// Builds the environment to start a program with, as "KEY=VALUE" strings.
+///
+/// If `inherit` is true, the environment seaside was started with comes first; variables it has
+/// that aren't valid UTF-8 are skipped. Each variable in `vars` replaces any inherited variable
+/// with the same name.
+pub fn environment(inherit: bool, vars: Vec<(String, String)>) -> Vec<String> {
+    let mut environment: Vec<(String, String)> = Vec::new();
+    if inherit {
+        environment.extend(
+            vars_os().filter_map(|(key, value)| {
+                Some((key.into_string().ok()?, value.into_string().ok()?))
+            }),
+        );
+    }
+    for (key, value) in vars {
+        environment.retain(|(existing, _)| *existing != key);
+        environment.push((key, value));
+    }
+    environment
+        .into_iter()
+        .map(|(key, value)| format!("{key}={value}"))
+        .collect()
 }
 
 /// Runs `interpreter`.
 ///
 /// Upon success, this returns the exit code. If the interpreter raises an
 /// [`Exception`](seaside_interpreter::Exception), this wraps it in an [`Error`] and, if enabled in
-/// the config, prints the crash handler.
-pub fn run(interpreter: &mut Interpreter) -> Result<Option<u8>> {
-    match interpreter.run() {
+/// the config, prints the crash handler and writes a core dump to `core_path`.
+///
+/// If `max_steps` is provided, the interpreter pauses after executing that many instructions.
+pub fn run(
+    interpreter: &mut Interpreter,
+    max_steps: Option<u64>,
+    core_path: &Path,
+) -> Result<Option<u8>> {
+    let result = match max_steps {
+        Some(max_steps) => interpreter.run_for(max_steps).map(|_| ()),
+        None => interpreter.run(),
+    };
+    match result {
         Ok(()) => Ok(interpreter.state.exit_code),
         Err(exception) => {
             if interpreter.show_crash_handler {
                 interpreter.state.print_crash_handler();
             }
+            if interpreter.write_core_dump {
+                match interpreter
+                    .core_dump(exception)
+                    .and_then(|core_dump| core_dump.save(core_path))
+                {
+                    Ok(()) => grayln!("wrote core dump to {}", core_path.display()),
+                    Err(error) => warnln!("failed to write core dump: {error}"),
+                }
+            }
             Err(exception.into())
         }
     }
 }
+
+/// Restores `interpreter` to the state saved in the snapshot at `path`.
+pub fn resume(interpreter: &mut Interpreter, path: &Path) -> Result<()> {
+    interpreter.restore(&Snapshot::load(path)?)
+}
+
+/// Steps `interpreter` backwards `steps` instructions, then keeps going until the pc reaches one
+/// of `breakpoints` or a write to one of `watchpoints` is undone.
+///
+/// Returns false without doing anything if there is nothing to rewind.
+pub fn rewind(
+    interpreter: &mut Interpreter,
+    steps: Option<u64>,
+    breakpoints: HashSet<Address>,
+    watchpoints: HashSet<Address>,
+) -> Result<bool> {
+    if steps.is_none() && breakpoints.is_empty() && watchpoints.is_empty() {
+        return Ok(false);
+    }
+    check_rewind(interpreter)?;
+    for step in 0..steps.unwrap_or(0) {
+        if !interpreter.step_back()? {
+            warnln!("the history ran out after stepping back {step} instructions");
+            return Ok(true);
+        }
+    }
+    if breakpoints.is_empty() && watchpoints.is_empty() {
+        return Ok(true);
+    }
+    match interpreter.reverse_continue(&breakpoints, &watchpoints)? {
+        StopReason::Breakpoint(address) => {
+            grayln!("stepped back to {}", interpreter.symbols.describe(address));
+        }
+        StopReason::Watchpoint(address) => grayln!(
+            "stepped back past a write to {}",
+            interpreter.symbols.describe(address),
+        ),
+        StopReason::HistoryExhausted => {
+            warnln!("the history ran out before reaching a breakpoint or watchpoint");
+        }
+    }
+    Ok(true)
+}
+
+/// Checks that `interpreter` can be stepped backwards with [`rewind`].
+///
+/// The undo history has to be enabled, and nothing that collects statistics or checks the program
+/// while it runs can be, since none of them are rewound. Their reports would describe steps that
+/// were undone.
+pub fn check_rewind(interpreter: &Interpreter) -> Result<()> {
+    if interpreter.history.is_none() {
+        return Err(Error::new(EngineError::InvalidConfig))
+            .context("stepping backwards requires 'features.history.enabled'");
+    }
+    let memory = &interpreter.state.memory;
+    let collectors = [
+        ("the profiler", interpreter.profiler.is_some()),
+        ("memcheck", memory.memcheck().is_some()),
+        (
+            "caches",
+            memory.instruction_cache().is_some() || memory.data_cache().is_some(),
+        ),
+        ("the pipeline model", interpreter.pipeline.is_some()),
+        ("branch predictors", interpreter.branch_predictors.is_some()),
+        ("stack checks", interpreter.stack_checker.is_some()),
+        (
+            "calling convention checks",
+            interpreter.convention_checker.is_some(),
+        ),
+    ];
+    match collectors.iter().find(|(_, enabled)| *enabled) {
+        Some((name, _)) => Err(Error::new(EngineError::InvalidConfig)).with_context(|| {
+            format!(
+                "stepping backwards can't be combined with {name}, whose results aren't rewound"
+            )
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Saves the current state of `interpreter` as a snapshot at `path`.
+pub fn save_snapshot(interpreter: &Interpreter, path: &Path) -> Result<()> {
+    interpreter.snapshot()?.save(path)?;
+    grayln!("saved snapshot to {}", path.display());
+    Ok(())
+}
+
+/// Writes the notes played by `interpreter` to `path`, as a WAV file if it ends in `.wav` or a
+/// Standard MIDI File otherwise.
+pub fn save_midi(interpreter: &Interpreter, path: &Path) -> Result<()> {
+    let midi = &interpreter.state.midi;
+    midi.save(path)?;
+    grayln!("wrote {} notes to {}", midi.notes().len(), path.display());
+    Ok(())
+}
+
+/// Copies the files in `interpreter`'s in-memory sandbox to the directory `path`.
+pub fn export_vfs(interpreter: &Interpreter, path: &Path) -> Result<()> {
+    let Some(vfs) = interpreter.state.sandbox.vfs() else {
+        warnln!("the sandbox isn't in memory, so there are no files to export");
+        return Ok(());
+    };
+    vfs.export(path)
+        .with_context(|| format!("failed to export sandbox to {}", path.display()))?;
+    grayln!(
+        "exported {} sandbox entries to {}",
+        vfs.paths().count(),
+        path.display()
+    );
+    Ok(())
+}
+
+/// Prints the statistics collected by `interpreter`'s profiler (if enabled), then writes its call
+/// stacks to `folded_stacks_path` in a format suitable for generating flamegraphs.
+pub fn report_profile(interpreter: &Interpreter, folded_stacks_path: &Path) -> Result<()> {
+    let Some(profiler) = &interpreter.profiler else {
+        return Ok(());
+    };
+    profiler.write_report(
+        &mut stderr().lock(),
+        &interpreter.state.memory,
+        &interpreter.symbols,
+    )?;
+    profiler.write_folded_stacks(&mut File::create(folded_stacks_path)?, &interpreter.symbols)?;
+    grayln!(
+        "wrote folded call stacks to {}",
+        folded_stacks_path.display()
+    );
+    Ok(())
+}
+
+/// Prints the hit/miss statistics of each cache simulated by `interpreter`.
+pub fn report_caches(interpreter: &Interpreter) -> Result<()> {
+    let memory = &interpreter.state.memory;
+    let mut stderr = stderr().lock();
+    if let Some(cache) = memory.instruction_cache() {
+        writeln!(stderr, "{}: {}", cache.name(), cache.stats())?;
+    }
+    if let Some(cache) = memory.data_cache() {
+        writeln!(stderr, "{}: {}", cache.name(), cache.stats())?;
+    }
+    Ok(())
+}
+
+/// Prints the cycle count and stalls estimated by `interpreter`'s pipeline model (if enabled).
+pub fn report_pipeline(interpreter: &Interpreter) -> Result<()> {
+    if let Some(pipeline) = &interpreter.pipeline {
+        pipeline.write_report(&mut stderr().lock())?;
+    }
+    Ok(())
+}
+
+/// Prints how accurately each of `interpreter`'s branch predictors (if enabled) predicted the
+/// program's conditional branches.
+pub fn report_branch_predictors(interpreter: &Interpreter) -> Result<()> {
+    if let Some(predictors) = &interpreter.branch_predictors {
+        predictors.write_report(&mut stderr().lock(), &interpreter.symbols)?;
+    }
+    Ok(())
+}
+
+/// Prints every misuse of memory found by `interpreter`'s memory checker (if enabled).
+pub fn report_memcheck(interpreter: &Interpreter) -> Result<()> {
+    if let Some(memcheck) = interpreter.state.memory.memcheck() {
+        memcheck.write_report(&mut stderr().lock(), &interpreter.symbols)?;
+    }
+    Ok(())
+}
+
+/// Prints the heap blocks `interpreter`'s program never freed, if it used the heap allocator
+/// services and has finished.
+pub fn report_heap_leaks(interpreter: &Interpreter) -> Result<()> {
+    let allocator = &interpreter.state.heap_allocator;
+    if interpreter.is_finished() && !allocator.is_unused() {
+        allocator.write_leak_report(&mut stderr().lock(), |site| {
+            interpreter.symbols.describe(site)
+        })?;
+    }
+    Ok(())
+}
+
+/// Prints every misuse of the stack found by `interpreter`'s stack checker (if enabled).
+pub fn report_stack_checks(interpreter: &Interpreter) -> Result<()> {
+    if let Some(stack_checker) = &interpreter.stack_checker {
+        stack_checker.write_report(&mut stderr().lock(), &interpreter.symbols)?;
+    }
+    Ok(())
+}
+
+/// Prints every violation of the calling convention found by `interpreter` (if enabled).
+pub fn report_calling_convention(interpreter: &Interpreter) -> Result<()> {
+    if let Some(convention_checker) = &interpreter.convention_checker {
+        convention_checker.write_report(&mut stderr().lock(), &interpreter.symbols)?;
+    }
+    Ok(())
+}
+
+/// Prints what the core dump at `path` recorded about a crashed program: the exception, its
+/// registers, the `context` instructions on either side of the one that crashed, and its open
+/// files.
+///
+/// If `memory` is provided, the given number of bytes starting at the given address are dumped,
+/// too.
+pub fn inspect(path: &Path, context: u32, memory: Option<(Address, u32)>) -> Result<()> {
+    let core_dump = CoreDump::load(path)?;
+    let registers: &RegisterFile = &core_dump.snapshot.registers;
+    println!(
+        "exception {}: {} (pc: {:#010x})",
+        core_dump.exception_code, core_dump.exception, core_dump.pc,
+    );
+    println!("{registers}");
+    println!(
+        "vaddr: {:08x}  status: {:08x}  cause: {:08x}  epc: {:08x}",
+        registers.vaddr, registers.status, registers.cause, registers.epc,
+    );
+    println!("fcsr: {:08x}", registers.fcsr.bits());
+
+    println!("\ndisassembly:");
+    let first = core_dump.pc.saturating_sub(context.saturating_mul(4));
+    let last = core_dump.pc.saturating_add(context.saturating_mul(4));
+    for address in (first..=last).step_by(4) {
+        let marker = if address == core_dump.pc { "=>" } else { "  " };
+        let disassembly = core_dump
+            .read_instruction(address)
+            .map(|instruction| {
+                seaside_disassembler::disassemble_advanced(instruction, address, true)
+                    .unwrap_or_else(|| format!("<invalid instruction {instruction:#010x}>"))
+            })
+            .unwrap_or_else(|| "<unmapped>".to_string());
+        println!("{marker} {address:#010x}  {disassembly}");
+    }
+
+    println!("\nopen files:");
+    for (fd, description) in &core_dump.fds {
+        println!("  {fd:>3}  {description}");
+    }
+
+    if let Some((start, len)) = memory {
+        println!("\nmemory:");
+        for row in (0..len).step_by(16) {
+            let address = start.wrapping_add(row);
+            let bytes: Vec<Option<u8>> = (0..u32::min(16, len - row))
+                .map(|offset| core_dump.read_u8(address.wrapping_add(offset)))
+                .collect();
+            let hex: Vec<String> = bytes
+                .iter()
+                .map(|byte| byte.map_or("--".to_string(), |byte| format!("{byte:02x}")))
+                .collect();
+            let ascii: String = bytes
+                .iter()
+                .map(|byte| match byte {
+                    Some(byte) if byte.is_ascii_graphic() || *byte == b' ' => *byte as char,
+                    _ => '.',
+                })
+                .collect();
+            println!("  {address:#010x}  {:<47}  {ascii}", hex.join(" "));
+        }
+    }
+    Ok(())
+}
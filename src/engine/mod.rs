@@ -11,7 +11,11 @@ pub mod interpreter;
 pub use assembler::assemble;
 pub use config::get_config;
 pub use disassembler::{disassemble_instruction, disassemble_segment};
-pub use interpreter::{init_interpreter, run};
+pub use interpreter::{
+    check_rewind, environment, export_vfs, init_interpreter, inspect, report_branch_predictors,
+    report_caches, report_calling_convention, report_heap_leaks, report_memcheck, report_pipeline,
+    report_profile, report_stack_checks, resume, rewind, run, save_midi, save_snapshot,
+};
 
 use std::path::{Path, PathBuf};
 
@@ -24,9 +28,5 @@ where
     P: AsRef<Path>,
 {
     let path = directory.join(name);
-    if path.exists() {
-        Some(path)
-    } else {
-        None
-    }
+    if path.exists() { Some(path) } else { None }
 }
@@ -3,10 +3,13 @@ mod engine;
 
 use anyhow::{Error, Result};
 use clap::Parser;
-use cmd_args::{AssemblyArgs, CmdArgs, Commands, DisassemblyArgs, DisassemblyTarget, RunArgs};
+use cmd_args::{
+    AssemblyArgs, CmdArgs, Commands, DisassemblyArgs, DisassemblyTarget, InspectArgs, RunArgs,
+};
 use minimal_logging::macros::{fatalln, grayln};
-use seaside_config::Config;
-use std::env::current_exe;
+use seaside_config::{Config, Validate};
+use seaside_interpreter::{Dialogs, Startup};
+use std::{env::current_exe, path::absolute};
 
 fn main() {
     let args: CmdArgs = CmdArgs::parse();
@@ -18,18 +21,7 @@ fn main() {
         }
     };
     if let Err(error) = match args.command {
-        Commands::Run(RunArgs { directory, argv }) => {
-            match engine::init_interpreter(config, directory, argv) {
-                Ok(mut interpreter) => engine::run(&mut interpreter).map(|exit_code| {
-                    if let Some(exit_code) = exit_code {
-                        grayln!("program terminated with exit code {exit_code}")
-                    } else {
-                        grayln!("program dropped off the bottom")
-                    }
-                }),
-                Err(error) => Err(error),
-            }
-        }
+        Commands::Run(args) => run(config, args),
         Commands::Assemble(AssemblyArgs {
             source,
             output_directory,
@@ -41,7 +33,7 @@ fn main() {
                     segment: None,
                 },
             address: start_address,
-        }) => engine::disassemble_instruction(instruction, start_address),
+        }) => engine::disassemble_instruction(instruction, start_address, &config.features.isa),
         Commands::Disassemble(DisassemblyArgs {
             target:
                 DisassemblyTarget {
@@ -50,6 +42,12 @@ fn main() {
                 },
             address: start_address,
         }) => engine::disassemble_segment(config, segment, start_address),
+        Commands::Inspect(InspectArgs {
+            core,
+            context,
+            memory,
+            length,
+        }) => engine::inspect(&core, context, memory.map(|address| (address, length))),
         Commands::ExePath => print_exe_path(),
         #[cfg(debug_assertions)]
         Commands::Experiment => experimental_code(),
@@ -59,6 +57,117 @@ fn main() {
     }
 }
 
+fn run(mut config: Config, args: RunArgs) -> Result<()> {
+    let RunArgs {
+        directory,
+        mut argv,
+        strace,
+        profile,
+        memcheck,
+        resume,
+        snapshot,
+        pause_after,
+        step_back,
+        reverse_to,
+        reverse_watch,
+        dialog_script,
+        midi_out,
+        export_vfs,
+        deterministic,
+        seed,
+        spim,
+        argv0,
+        env,
+        inherit_env,
+    } = args;
+    // The interpreter may change the cwd to the project directory, so these have to be resolved
+    // ahead of time.
+    let folded_stacks_path = absolute(&directory)?.join("profile.folded");
+    let core_path = absolute(&directory)?.join("core");
+    let resume = resume.map(absolute).transpose()?;
+    let snapshot = snapshot.map(absolute).transpose()?;
+    let dialog_script = dialog_script.map(absolute).transpose()?;
+    let midi_out = midi_out.map(absolute).transpose()?;
+    let export_vfs = export_vfs.map(absolute).transpose()?;
+    if deterministic || seed.is_some() {
+        let options = &mut config.features.deterministic;
+        options.enabled = true;
+        if let Some(seed) = seed {
+            options.seed = seed;
+        }
+    }
+    if spim {
+        config.apply_spim_preset();
+        config.validate()?;
+    }
+    let argv0 = argv0.or_else(|| {
+        config
+            .features
+            .spim
+            .enabled
+            .then(|| directory.display().to_string())
+    });
+    if let Some(argv0) = argv0 {
+        argv.insert(0, argv0);
+    }
+    let startup = Startup {
+        argv,
+        envp: engine::environment(inherit_env, env),
+    };
+    let mut interpreter = engine::init_interpreter(config, directory, startup)?;
+    interpreter.strace = strace;
+    if let Some(dialog_script) = &dialog_script {
+        interpreter.state.dialogs = Dialogs::scripted(dialog_script)?;
+    }
+    if let Some(resume) = &resume {
+        engine::resume(&mut interpreter, resume)?;
+    }
+    if profile {
+        interpreter.enable_profiler();
+    }
+    if memcheck {
+        interpreter.enable_memcheck();
+    }
+    // Checked ahead of time so a run that can't be rewound isn't wasted.
+    if step_back.is_some() || !reverse_to.is_empty() || !reverse_watch.is_empty() {
+        engine::check_rewind(&interpreter)?;
+    }
+    let result = engine::run(&mut interpreter, pause_after, &core_path);
+    engine::report_profile(&interpreter, &folded_stacks_path)?;
+    engine::report_caches(&interpreter)?;
+    engine::report_pipeline(&interpreter)?;
+    engine::report_branch_predictors(&interpreter)?;
+    engine::report_memcheck(&interpreter)?;
+    engine::report_heap_leaks(&interpreter)?;
+    engine::report_stack_checks(&interpreter)?;
+    engine::report_calling_convention(&interpreter)?;
+    if let Some(midi_out) = &midi_out {
+        engine::save_midi(&interpreter, midi_out)?;
+    }
+    if let Some(export_vfs) = &export_vfs {
+        engine::export_vfs(&interpreter, export_vfs)?;
+    }
+    let rewound = engine::rewind(
+        &mut interpreter,
+        step_back,
+        reverse_to.into_iter().collect(),
+        reverse_watch.into_iter().collect(),
+    )?;
+    // A program that crashed can still be saved once it's been rewound to before the crash.
+    if let Some(snapshot) = snapshot.as_ref().filter(|_| result.is_ok() || rewound) {
+        engine::save_snapshot(&interpreter, snapshot)?;
+    }
+    let exit_code = result?;
+    if !interpreter.is_finished() {
+        grayln!("program paused at pc {:#010x}", interpreter.state.pc);
+    } else if let Some(exit_code) = exit_code {
+        grayln!("program terminated with exit code {exit_code}");
+    } else {
+        grayln!("program dropped off the bottom");
+    }
+    Ok(())
+}
+
 fn print_exe_path() -> Result<(), Error> {
     println!("{}", current_exe()?.display());
     Ok(())